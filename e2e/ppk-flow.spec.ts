@@ -32,7 +32,6 @@ async function createOrganization(page: Page, name = 'Test Sp. z o.o.') {
   await page.getByPlaceholder('Nazwa firmy').fill(name);
   await page.getByPlaceholder('0000000000', { exact: true }).fill('1234563218');
   await page.getByPlaceholder('000000000', { exact: true }).fill('123456785');
-  await page.getByPlaceholder('Imię Nazwisko').fill('Jan Kowalski');
   await page.getByRole('button', { name: 'Utwórz' }).click();
   await expect(page.getByText('Organizacja utworzona')).toBeVisible();
 }
@@ -93,7 +92,7 @@ test.describe('PPK Generator — mock backend', () => {
     await page.goto('/');
     await expect(page.getByText('Wybierz lub utwórz organizację')).toBeVisible();
 
-    await page.getByRole('combobox').selectOption('+ Nowa organizacja');
+    await page.locator('select:has(option:text("+ Nowa organizacja"))').selectOption('+ Nowa organizacja');
     await expect.poll(() => logs.some(l => l.includes('[mock]'))).toBe(true);
   });
 
@@ -192,21 +191,26 @@ test.describe('PPK Generator — mock backend', () => {
     await expect(page.getByRole('cell', { name: '1', exact: true })).toBeVisible();
   });
 
-  test('delete organization cascades', async ({ page }) => {
+  test('archived organization can be restored from the trash', async ({ page }) => {
     await createOrganization(page);
     await addMember(page, TEST_PESEL, 'Adam', 'Nowak');
 
     await goToOrgForm(page);
-    await page.getByRole('button', { name: 'Usuń' }).click();
+    await page.getByRole('button', { name: 'Przenieś do kosza' }).click();
 
     const confirmDialog = page.getByRole('dialog');
     await expect(confirmDialog).toBeVisible();
-    await confirmDialog.getByRole('button', { name: 'Usuń' }).click();
+    await confirmDialog.getByRole('button', { name: 'Przenieś do kosza' }).click();
 
-    await expect(page.getByText('Organizacja usunięta')).toBeVisible();
+    await expect(page.getByText('Organizacja przeniesiona do kosza')).toBeVisible();
     await expect(page.getByRole('heading', { name: 'Nowa organizacja' })).toBeVisible();
     const combobox = page.getByRole('combobox').first();
     await expect(combobox).not.toContainText('Test Sp. z o.o.');
+
+    await page.getByRole('button', { name: 'Kosz' }).click();
+    await page.getByRole('button', { name: 'Przywróć' }).click();
+    await expect(page.getByText('Przywrócono: Test Sp. z o.o.')).toBeVisible();
+    await expect(combobox).toContainText('Test Sp. z o.o.');
   });
 
   // =====================
@@ -242,27 +246,32 @@ test.describe('PPK Generator — mock backend', () => {
   });
 
   // =====================
-  // 2. Delete member
+  // 2. Archive member
   // =====================
 
-  test('delete member removes them from the list', async ({ page }) => {
+  test('archived member leaves the list and can be purged from the trash', async ({ page }) => {
     await createOrganization(page);
     await addMember(page, TEST_PESEL, 'Adam', 'Nowak');
 
-    // Click Usuń on the member row
-    await page.getByRole('cell', { name: 'Edytuj Usuń' }).getByRole('button', { name: 'Usuń' }).click();
+    await page.getByRole('button', { name: 'Do kosza' }).click();
 
     // Confirm in dialog
     const confirmDialog = page.getByRole('dialog');
     await expect(confirmDialog).toBeVisible();
     await expect(confirmDialog.getByText('Adam Nowak')).toBeVisible();
-    await confirmDialog.getByRole('button', { name: 'Usuń' }).click();
+    await confirmDialog.getByRole('button', { name: 'Przenieś do kosza' }).click();
 
-    await expect(page.getByText('Uczestnik usunięty')).toBeVisible();
+    await expect(page.getByText('Uczestnik przeniesiony do kosza')).toBeVisible();
     await expect(page.getByText('Brak uczestników')).toBeVisible();
+
+    // No generation lists them, so they can go for good
+    await page.getByRole('button', { name: 'Kosz' }).click();
+    await page.getByRole('button', { name: 'Usuń trwale' }).click();
+    await page.getByRole('dialog').getByRole('button', { name: 'Usuń trwale' }).click();
+    await expect(page.getByText('Kosz jest pusty')).toBeVisible();
   });
 
-  test('delete member cascades contributions', async ({ page }) => {
+  test('archived member hides their contributions', async ({ page }) => {
     await createOrganization(page);
     await addMember(page, TEST_PESEL, 'Adam', 'Nowak');
     await goToContributions(page);
@@ -271,12 +280,12 @@ test.describe('PPK Generator — mock backend', () => {
     // Verify contribution row exists
     await expect(page.getByText('Nowak Adam')).toBeVisible();
 
-    // Go back to members and delete
+    // Go back to members and archive
     await goToMembers(page);
-    await page.getByRole('cell', { name: 'Edytuj Usuń' }).getByRole('button', { name: 'Usuń' }).click();
+    await page.getByRole('button', { name: 'Do kosza' }).click();
     const confirmDialog = page.getByRole('dialog');
-    await confirmDialog.getByRole('button', { name: 'Usuń' }).click();
-    await expect(page.getByText('Uczestnik usunięty')).toBeVisible();
+    await confirmDialog.getByRole('button', { name: 'Przenieś do kosza' }).click();
+    await expect(page.getByText('Uczestnik przeniesiony do kosza')).toBeVisible();
 
     // Go to contributions — table should be empty now
     await goToContributions(page);
@@ -295,7 +304,7 @@ test.describe('PPK Generator — mock backend', () => {
 
     // Change the name
     await page.getByPlaceholder('Nazwa firmy').fill('Updated Sp. z o.o.');
-    await page.getByRole('button', { name: 'Zapisz' }).click();
+    await page.getByRole('button', { name: 'Zapisz', exact: true }).click();
     await expect(page.getByText('Organizacja zaktualizowana')).toBeVisible();
 
     // Verify selector updated
@@ -303,6 +312,31 @@ test.describe('PPK Generator — mock backend', () => {
     await expect(combobox).toContainText('Updated Sp. z o.o.');
   });
 
+  test('contact persons — first is the default, another can replace it', async ({ page }) => {
+    await createOrganization(page);
+
+    await page.getByRole('button', { name: '+ Dodaj' }).click();
+    await page.getByPlaceholder('Imię Nazwisko').fill('Jan Kowalski');
+    await page.getByPlaceholder('600 123 456').fill('600 12');
+    await page.getByRole('button', { name: 'Zapisz', exact: true }).last().click();
+    await expect(page.getByText('Numer telefonu musi mieć 9 cyfr')).toBeVisible();
+
+    await page.getByPlaceholder('600 123 456').fill('600 123 456');
+    await page.getByRole('button', { name: 'Zapisz', exact: true }).last().click();
+    await expect(page.getByText('Osoba kontaktowa dodana')).toBeVisible();
+    await expect(page.getByRole('row', { name: /Jan Kowalski/ })).toContainText('domyślna');
+
+    await page.getByRole('button', { name: '+ Dodaj' }).click();
+    await page.getByPlaceholder('Imię Nazwisko').fill('Anna Nowak');
+    await page.getByPlaceholder('adres@firma.pl').fill('kadry@firma.pl');
+    await page.getByRole('button', { name: 'Zapisz', exact: true }).last().click();
+    await expect(page.getByRole('row', { name: /Anna Nowak/ })).not.toContainText('domyślna');
+
+    await page.getByRole('row', { name: /Anna Nowak/ }).getByRole('button', { name: 'Ustaw domyślną' }).click();
+    await expect(page.getByRole('row', { name: /Anna Nowak/ })).toContainText('domyślna');
+    await expect(page.getByRole('row', { name: /Jan Kowalski/ })).not.toContainText('domyślna');
+  });
+
   // =====================
   // 4. Upsert update path (edit existing contribution)
   // =====================
@@ -336,7 +370,7 @@ test.describe('PPK Generator — mock backend', () => {
   // 5. Export/download ZIP
   // =====================
 
-  test('Zapisz ZIP in generation summary triggers save', async ({ page }) => {
+  test('generation summary shows the saved file path', async ({ page }) => {
     await createOrganization(page);
     await addMember(page, TEST_PESEL, 'Adam', 'Nowak');
     await goToContributions(page);
@@ -345,10 +379,7 @@ test.describe('PPK Generator — mock backend', () => {
     await page.getByRole('button', { name: 'Generuj plik PPK' }).click();
     const dialog = page.getByRole('dialog');
     await expect(dialog).toBeVisible();
-
-    // Click Zapisz ZIP
-    await dialog.getByRole('button', { name: 'Zapisz ZIP' }).click();
-    await expect(page.getByText('Plik ZIP zapisany pomyślnie')).toBeVisible();
+    await expect(dialog.getByText(/^\/mock\/SKLADKA_\d{6}\.zip$/)).toBeVisible();
   });
 
   test('Pobierz ZIP in generation log triggers export', async ({ page }) => {
@@ -446,7 +477,7 @@ test.describe('PPK Generator — mock backend', () => {
   // =====================
 
   test('org creation fails with invalid NIP', async ({ page }) => {
-    await page.getByRole('combobox').selectOption('+ Nowa organizacja');
+    await page.locator('select:has(option:text("+ Nowa organizacja"))').selectOption('+ Nowa organizacja');
     await page.getByPlaceholder('Nazwa firmy').fill('Test Org');
     await page.getByPlaceholder('0000000000', { exact: true }).fill('1234567890');
     await page.getByPlaceholder('000000000', { exact: true }).fill('123456785');
@@ -459,7 +490,7 @@ test.describe('PPK Generator — mock backend', () => {
   });
 
   test('org creation fails with invalid REGON', async ({ page }) => {
-    await page.getByRole('combobox').selectOption('+ Nowa organizacja');
+    await page.locator('select:has(option:text("+ Nowa organizacja"))').selectOption('+ Nowa organizacja');
     await page.getByPlaceholder('Nazwa firmy').fill('Test Org');
     await page.getByPlaceholder('0000000000', { exact: true }).fill('1234563218');
     await page.getByPlaceholder('000000000', { exact: true }).fill('111111111');
@@ -469,8 +500,20 @@ test.describe('PPK Generator — mock backend', () => {
     await expect(page.getByText('Organizacja utworzona')).not.toBeVisible();
   });
 
+  test('sole proprietor can be identified by PESEL without NIP or REGON', async ({ page }) => {
+    await page.locator('select:has(option:text("+ Nowa organizacja"))').selectOption('+ Nowa organizacja');
+    await page.getByPlaceholder('Nazwa firmy').fill('Jan Kowalski Usługi');
+    await page.locator('select:has(option[value="PESEL"])').selectOption('PESEL');
+    await page.getByRole('button', { name: 'Utwórz' }).click();
+    await expect(page.getByText('PESEL jest wymagany')).toBeVisible();
+
+    await page.getByPlaceholder('00000000000', { exact: true }).fill(TEST_PESEL);
+    await page.getByRole('button', { name: 'Utwórz' }).click();
+    await expect(page.getByText('Organizacja utworzona')).toBeVisible();
+  });
+
   test('org creation fails with empty name', async ({ page }) => {
-    await page.getByRole('combobox').selectOption('+ Nowa organizacja');
+    await page.locator('select:has(option:text("+ Nowa organizacja"))').selectOption('+ Nowa organizacja');
     // Leave name empty
     await page.getByPlaceholder('0000000000', { exact: true }).fill('1234563218');
     await page.getByPlaceholder('000000000', { exact: true }).fill('123456785');
@@ -720,4 +763,24 @@ test.describe('PPK Generator — mock backend', () => {
     const exportButtons = page.getByRole('button', { name: 'Pobierz ZIP' });
     await expect(exportButtons).toHaveCount(2);
   });
+
+  // =====================
+  // Batch generation
+  // =====================
+
+  test('batch generation reports each organization in the summary', async ({ page }) => {
+    await createOrganization(page, 'Pierwsza Sp. z o.o.');
+    await addMember(page, TEST_PESEL, 'Adam', 'Nowak');
+    await goToContributions(page);
+    await prefillContributions(page);
+    await createOrganization(page, 'Pusta Sp. z o.o.');
+
+    await page.getByRole('button', { name: '📦 Generacja zbiorcza' }).click();
+    await page.getByRole('button', { name: 'Generuj pliki' }).click();
+
+    await expect(page.getByText('Gotowe: 1, do sprawdzenia: 0, błędy: 1')).toBeVisible();
+    const rows = page.locator('tbody tr');
+    await expect(rows.filter({ hasText: 'Pierwsza Sp. z o.o.' })).toContainText('Gotowe');
+    await expect(rows.filter({ hasText: 'Pusta Sp. z o.o.' })).toContainText('Brak składek do wygenerowania');
+  });
 });
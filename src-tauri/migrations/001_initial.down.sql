@@ -0,0 +1,4 @@
+DROP TABLE generations;
+DROP TABLE contributions;
+DROP TABLE members;
+DROP TABLE organizations;
@@ -0,0 +1,4 @@
+ALTER TABLE generations DROP COLUMN checksum;
+
+DROP TABLE organization_settings;
+DROP TABLE app_settings;
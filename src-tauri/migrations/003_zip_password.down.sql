@@ -0,0 +1,3 @@
+ALTER TABLE generations DROP COLUMN encrypted;
+
+ALTER TABLE organization_settings DROP COLUMN zip_password;
@@ -0,0 +1,3 @@
+ALTER TABLE organization_settings ADD COLUMN zip_password TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE generations ADD COLUMN encrypted INTEGER NOT NULL DEFAULT 0;
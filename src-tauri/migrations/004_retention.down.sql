@@ -0,0 +1,6 @@
+DROP TABLE audit_log;
+
+ALTER TABLE organization_settings DROP COLUMN retention_years;
+
+ALTER TABLE members DROP COLUMN anonymized_at;
+ALTER TABLE members DROP COLUMN terminated_at;
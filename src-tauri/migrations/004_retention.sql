@@ -0,0 +1,17 @@
+ALTER TABLE members ADD COLUMN terminated_at TEXT;
+ALTER TABLE members ADD COLUMN anonymized_at TEXT;
+
+-- Best guess for people who left before the date was recorded
+UPDATE members SET terminated_at = date(updated_at) WHERE status != 'active';
+
+ALTER TABLE organization_settings ADD COLUMN retention_years INTEGER;
+
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    details_json TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_audit_log_org ON audit_log(organization_id);
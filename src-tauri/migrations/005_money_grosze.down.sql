@@ -0,0 +1,54 @@
+CREATE TABLE contributions_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL CHECK(period_month >= 1 AND period_month <= 12),
+    employee_basic TEXT NOT NULL DEFAULT '0.00',
+    employee_additional TEXT NOT NULL DEFAULT '0.00',
+    employer_basic TEXT NOT NULL DEFAULT '0.00',
+    employer_additional TEXT NOT NULL DEFAULT '0.00',
+    reduced_basic_flag TEXT NOT NULL DEFAULT 'N' CHECK(reduced_basic_flag IN ('T', 'N')),
+    source TEXT NOT NULL DEFAULT 'manual' CHECK(source IN ('manual', 'prefilled')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(member_id, period_year, period_month)
+);
+
+INSERT INTO contributions_old
+SELECT id, member_id, period_year, period_month,
+    printf('%d.%02d', employee_basic / 100, employee_basic % 100),
+    printf('%d.%02d', employee_additional / 100, employee_additional % 100),
+    printf('%d.%02d', employer_basic / 100, employer_basic % 100),
+    printf('%d.%02d', employer_additional / 100, employer_additional % 100),
+    reduced_basic_flag, source, updated_at
+FROM contributions;
+
+DROP TABLE contributions;
+ALTER TABLE contributions_old RENAME TO contributions;
+CREATE INDEX idx_contributions_member ON contributions(member_id);
+CREATE INDEX idx_contributions_period ON contributions(period_year, period_month);
+
+CREATE TABLE generations_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL,
+    generated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    snapshot_json TEXT NOT NULL,
+    file_path TEXT NOT NULL DEFAULT '',
+    total_employee_basic TEXT NOT NULL DEFAULT '0.00',
+    total_employer_basic TEXT NOT NULL DEFAULT '0.00',
+    member_count INTEGER NOT NULL DEFAULT 0,
+    checksum TEXT NOT NULL DEFAULT '',
+    encrypted INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO generations_old
+SELECT id, organization_id, period_year, period_month, generated_at, snapshot_json, file_path,
+    printf('%d.%02d', total_employee_basic / 100, total_employee_basic % 100),
+    printf('%d.%02d', total_employer_basic / 100, total_employer_basic % 100),
+    member_count, checksum, encrypted
+FROM generations;
+
+DROP TABLE generations;
+ALTER TABLE generations_old RENAME TO generations;
+CREATE INDEX idx_generations_org ON generations(organization_id);
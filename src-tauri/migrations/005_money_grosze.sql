@@ -0,0 +1,76 @@
+-- Amounts become integer grosze. SQLite cannot change a column's type, so
+-- both tables are rebuilt. A stored amount that is not a valid decimal with
+-- at most two places turns into NULL, which fails the NOT NULL constraint and
+-- aborts the migration instead of silently becoming zero.
+
+-- Amounts were entered as decimals, which accepted a point with no digits on
+-- one side (".5", "1."). Those get a zero there so they convert below.
+UPDATE contributions SET employee_basic = '0' || employee_basic WHERE employee_basic GLOB '.*';
+UPDATE contributions SET employee_additional = '0' || employee_additional WHERE employee_additional GLOB '.*';
+UPDATE contributions SET employer_basic = '0' || employer_basic WHERE employer_basic GLOB '.*';
+UPDATE contributions SET employer_additional = '0' || employer_additional WHERE employer_additional GLOB '.*';
+UPDATE contributions SET employee_basic = employee_basic || '0' WHERE employee_basic GLOB '*.';
+UPDATE contributions SET employee_additional = employee_additional || '0' WHERE employee_additional GLOB '*.';
+UPDATE contributions SET employer_basic = employer_basic || '0' WHERE employer_basic GLOB '*.';
+UPDATE contributions SET employer_additional = employer_additional || '0' WHERE employer_additional GLOB '*.';
+
+CREATE TABLE contributions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL CHECK(period_month >= 1 AND period_month <= 12),
+    employee_basic INTEGER NOT NULL DEFAULT 0 CHECK(employee_basic >= 0),
+    employee_additional INTEGER NOT NULL DEFAULT 0 CHECK(employee_additional >= 0),
+    employer_basic INTEGER NOT NULL DEFAULT 0 CHECK(employer_basic >= 0),
+    employer_additional INTEGER NOT NULL DEFAULT 0 CHECK(employer_additional >= 0),
+    reduced_basic_flag TEXT NOT NULL DEFAULT 'N' CHECK(reduced_basic_flag IN ('T', 'N')),
+    source TEXT NOT NULL DEFAULT 'manual' CHECK(source IN ('manual', 'prefilled')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(member_id, period_year, period_month)
+);
+
+INSERT INTO contributions_new
+SELECT id, member_id, period_year, period_month,
+    CASE WHEN employee_basic GLOB '[0-9]*' AND employee_basic NOT GLOB '*[^0-9.]*' AND employee_basic NOT GLOB '*.*.*' AND employee_basic NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(employee_basic AS REAL) * 100) AS INTEGER) END,
+    CASE WHEN employee_additional GLOB '[0-9]*' AND employee_additional NOT GLOB '*[^0-9.]*' AND employee_additional NOT GLOB '*.*.*' AND employee_additional NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(employee_additional AS REAL) * 100) AS INTEGER) END,
+    CASE WHEN employer_basic GLOB '[0-9]*' AND employer_basic NOT GLOB '*[^0-9.]*' AND employer_basic NOT GLOB '*.*.*' AND employer_basic NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(employer_basic AS REAL) * 100) AS INTEGER) END,
+    CASE WHEN employer_additional GLOB '[0-9]*' AND employer_additional NOT GLOB '*[^0-9.]*' AND employer_additional NOT GLOB '*.*.*' AND employer_additional NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(employer_additional AS REAL) * 100) AS INTEGER) END,
+    reduced_basic_flag, source, updated_at
+FROM contributions;
+
+DROP TABLE contributions;
+ALTER TABLE contributions_new RENAME TO contributions;
+CREATE INDEX idx_contributions_member ON contributions(member_id);
+CREATE INDEX idx_contributions_period ON contributions(period_year, period_month);
+
+CREATE TABLE generations_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL,
+    generated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    snapshot_json TEXT NOT NULL,
+    file_path TEXT NOT NULL DEFAULT '',
+    total_employee_basic INTEGER NOT NULL DEFAULT 0,
+    total_employer_basic INTEGER NOT NULL DEFAULT 0,
+    member_count INTEGER NOT NULL DEFAULT 0,
+    checksum TEXT NOT NULL DEFAULT '',
+    encrypted INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO generations_new
+SELECT id, organization_id, period_year, period_month, generated_at, snapshot_json, file_path,
+    CASE WHEN total_employee_basic GLOB '[0-9]*' AND total_employee_basic NOT GLOB '*[^0-9.]*' AND total_employee_basic NOT GLOB '*.*.*' AND total_employee_basic NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(total_employee_basic AS REAL) * 100) AS INTEGER) END,
+    CASE WHEN total_employer_basic GLOB '[0-9]*' AND total_employer_basic NOT GLOB '*[^0-9.]*' AND total_employer_basic NOT GLOB '*.*.*' AND total_employer_basic NOT GLOB '*.[0-9][0-9][0-9]*'
+        THEN CAST(ROUND(CAST(total_employer_basic AS REAL) * 100) AS INTEGER) END,
+    member_count, checksum, encrypted
+FROM generations;
+
+DROP TABLE generations;
+ALTER TABLE generations_new RENAME TO generations;
+CREATE INDEX idx_generations_org ON generations(organization_id);
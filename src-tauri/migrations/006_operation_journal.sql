@@ -0,0 +1,12 @@
+-- Undo/redo journal. Each entry holds the rows one operation changed, as
+-- they were before and after it, so it can be replayed in either direction.
+CREATE TABLE operation_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    changes_json TEXT NOT NULL,
+    undone INTEGER NOT NULL DEFAULT 0 CHECK(undone IN (0, 1)),
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_operation_journal_org ON operation_journal(organization_id, undone);
@@ -0,0 +1 @@
+DROP INDEX idx_members_org_name;
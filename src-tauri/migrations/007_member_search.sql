@@ -0,0 +1,2 @@
+-- Member lists are sorted by name and paged by id within an organization.
+CREATE INDEX idx_members_org_name ON members(organization_id, last_name, first_name, id);
@@ -0,0 +1,12 @@
+-- Links the membership a participant left with the one opened for them in
+-- another organization, so their history reads as one.
+CREATE TABLE member_transfers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    to_member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    transferred_on TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_member_transfers_from ON member_transfers(from_member_id);
+CREATE INDEX idx_member_transfers_to ON member_transfers(to_member_id);
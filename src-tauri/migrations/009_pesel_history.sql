@@ -0,0 +1,15 @@
+-- PESELs a member had before a correction, with the data derived from them.
+-- Each applies to the periods before `replaced_from`, the first period of the
+-- PESEL that replaced it.
+CREATE TABLE member_pesel_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    pesel TEXT NOT NULL,
+    gender TEXT NOT NULL,
+    date_of_birth TEXT NOT NULL,
+    replaced_from_year INTEGER NOT NULL,
+    replaced_from_month INTEGER NOT NULL,
+    changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_member_pesel_history_member ON member_pesel_history(member_id);
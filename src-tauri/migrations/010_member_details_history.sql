@@ -0,0 +1,19 @@
+-- Names, citizenship and identity document a member had before a dated
+-- change, such as a new surname after marriage. Each row applies to the
+-- periods before `replaced_from`, the first period of the values that
+-- replaced it.
+CREATE TABLE member_details_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    second_name TEXT NOT NULL,
+    citizenship TEXT NOT NULL,
+    doc_type TEXT NOT NULL,
+    doc_number TEXT NOT NULL,
+    replaced_from_year INTEGER NOT NULL,
+    replaced_from_month INTEGER NOT NULL,
+    changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_member_details_history_member ON member_details_history(member_id);
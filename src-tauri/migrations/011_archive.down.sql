@@ -0,0 +1,2 @@
+ALTER TABLE members DROP COLUMN archived_at;
+ALTER TABLE organizations DROP COLUMN archived_at;
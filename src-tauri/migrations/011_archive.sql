@@ -0,0 +1,5 @@
+-- Deleting an organization or member archives it instead. Contributions and
+-- generation history must be kept, so rows only go once purged from the
+-- trash.
+ALTER TABLE organizations ADD COLUMN archived_at TEXT;
+ALTER TABLE members ADD COLUMN archived_at TEXT;
@@ -0,0 +1,12 @@
+ALTER TABLE organizations DROP COLUMN collection_account;
+ALTER TABLE organizations DROP COLUMN operation_agreement_date;
+ALTER TABLE organizations DROP COLUMN operation_agreement_number;
+ALTER TABLE organizations DROP COLUMN management_agreement_date;
+ALTER TABLE organizations DROP COLUMN management_agreement_number;
+ALTER TABLE organizations DROP COLUMN bank_account;
+ALTER TABLE organizations DROP COLUMN krs;
+ALTER TABLE organizations DROP COLUMN city;
+ALTER TABLE organizations DROP COLUMN postal_code;
+ALTER TABLE organizations DROP COLUMN apartment_number;
+ALTER TABLE organizations DROP COLUMN building_number;
+ALTER TABLE organizations DROP COLUMN street;
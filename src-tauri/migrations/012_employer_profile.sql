@@ -0,0 +1,16 @@
+-- Employer details needed by other institutions' formats, the participant
+-- registration file and payment orders. Empty when not filled in.
+ALTER TABLE organizations ADD COLUMN street TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN building_number TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN apartment_number TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN postal_code TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN city TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN krs TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN bank_account TEXT NOT NULL DEFAULT '';
+-- Agreements with the financial institution managing the PPK and the
+-- account it collects contributions on
+ALTER TABLE organizations ADD COLUMN management_agreement_number TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN management_agreement_date TEXT;
+ALTER TABLE organizations ADD COLUMN operation_agreement_number TEXT NOT NULL DEFAULT '';
+ALTER TABLE organizations ADD COLUMN operation_agreement_date TEXT;
+ALTER TABLE organizations ADD COLUMN collection_account TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,11 @@
+ALTER TABLE organization_settings DROP COLUMN contact_template;
+
+UPDATE organizations SET contact_person = COALESCE(
+    (SELECT trim(name
+        || CASE WHEN phone != '' THEN ' ' || phone ELSE '' END
+        || CASE WHEN email != '' THEN ' ' || email ELSE '' END)
+     FROM contact_persons
+     WHERE organization_id = organizations.id AND is_default),
+    '');
+
+DROP TABLE contact_persons;
@@ -0,0 +1,22 @@
+-- Contact persons of an employer. The default one fills `<KONTAKT>` in the
+-- generated files, formatted by `contact_template`.
+CREATE TABLE contact_persons (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    phone TEXT NOT NULL DEFAULT '',
+    email TEXT NOT NULL DEFAULT '',
+    is_default INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_contact_persons_org ON contact_persons(organization_id);
+CREATE UNIQUE INDEX idx_contact_persons_default ON contact_persons(organization_id) WHERE is_default;
+
+-- The free-text contact becomes the default contact person. The old column
+-- is no longer read, but stays so reverting does not rebuild `organizations`.
+INSERT INTO contact_persons (organization_id, name, is_default)
+SELECT id, trim(contact_person), 1 FROM organizations WHERE trim(contact_person) != '';
+
+ALTER TABLE organization_settings ADD COLUMN contact_template TEXT NOT NULL DEFAULT '';
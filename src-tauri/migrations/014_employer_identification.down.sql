@@ -0,0 +1,2 @@
+ALTER TABLE organizations DROP COLUMN pesel;
+ALTER TABLE organizations DROP COLUMN id_type;
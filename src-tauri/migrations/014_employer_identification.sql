@@ -0,0 +1,5 @@
+-- How the employer is identified in generated files. Local units go by
+-- their 14-digit REGON and some sole proprietors by PESEL, so NIP and REGON
+-- may be left empty when they are not the identifier.
+ALTER TABLE organizations ADD COLUMN id_type TEXT NOT NULL DEFAULT 'NIP' CHECK(id_type IN ('NIP', 'REGON', 'PESEL'));
+ALTER TABLE organizations ADD COLUMN pesel TEXT;
@@ -0,0 +1,14 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::models::audit::AuditEntry;
+use crate::services::audit;
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_audit_log(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<Vec<AuditEntry>, AppError> {
+    audit::list(&state.db.pool()?, organization_id)
+}
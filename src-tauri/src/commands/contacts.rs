@@ -0,0 +1,42 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::models::contact::{ContactPerson, SaveContactPerson};
+use crate::services::contacts;
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_contacts(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<Vec<ContactPerson>, AppError> {
+    contacts::list(&state.db.pool()?, organization_id)
+}
+
+#[tauri::command]
+pub fn create_contact(
+    state: State<AppState>,
+    organization_id: i64,
+    data: SaveContactPerson,
+) -> Result<ContactPerson, AppError> {
+    contacts::create(&state.db.pool()?, organization_id, &data)
+}
+
+#[tauri::command]
+pub fn update_contact(
+    state: State<AppState>,
+    id: i64,
+    data: SaveContactPerson,
+) -> Result<ContactPerson, AppError> {
+    contacts::update(&state.db.pool()?, id, &data)
+}
+
+#[tauri::command]
+pub fn set_default_contact(state: State<AppState>, id: i64) -> Result<ContactPerson, AppError> {
+    contacts::set_default(&state.db.pool()?, id)
+}
+
+#[tauri::command]
+pub fn delete_contact(state: State<AppState>, id: i64) -> Result<(), AppError> {
+    contacts::delete(&state.db.pool()?, id)
+}
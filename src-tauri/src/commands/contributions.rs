@@ -1,25 +1,28 @@
-use serde::Serialize;
 use tauri::State;
 
+use crate::domain::period::Period;
 use crate::error::AppError;
-use crate::models::contribution::{Contribution, ContributionWithMember, UpsertContribution};
+use crate::models::contribution::{ContributionWithMember, UpsertContribution};
+use crate::models::member::MemberFilter;
+use crate::services::contributions::{self, UpsertBatchResult};
+use crate::services::prefill::{self, PrefillPreview, PrefillRequest, PrefillResult};
 use crate::state::AppState;
 
-#[derive(Serialize)]
-pub struct Period {
-    pub year: i32,
-    pub month: i32,
-}
-
 #[tauri::command]
 pub fn list_contributions(
     state: State<AppState>,
     organization_id: i64,
     year: i32,
     month: i32,
+    filter: Option<MemberFilter>,
 ) -> Result<Vec<ContributionWithMember>, AppError> {
-    let conn = state.db.get()?;
-    Contribution::list_for_period(&conn, organization_id, year, month)
+    contributions::list_for_period_filtered(
+        &state.db.pool()?,
+        organization_id,
+        year,
+        month,
+        &filter.unwrap_or_default(),
+    )
 }
 
 #[tauri::command]
@@ -27,19 +30,31 @@ pub fn upsert_contribution(
     state: State<AppState>,
     data: UpsertContribution,
 ) -> Result<(), AppError> {
-    let conn = state.db.get()?;
-    Contribution::upsert(&conn, &data)
+    contributions::upsert(&state.db.pool()?, &data)
+}
+
+#[tauri::command]
+pub fn upsert_contributions(
+    state: State<AppState>,
+    rows: Vec<UpsertContribution>,
+) -> Result<UpsertBatchResult, AppError> {
+    contributions::upsert_batch(&state.db.pool()?, &rows)
+}
+
+#[tauri::command]
+pub fn preview_prefill(
+    state: State<AppState>,
+    request: PrefillRequest,
+) -> Result<PrefillPreview, AppError> {
+    prefill::preview(&state.db.pool()?, &request)
 }
 
 #[tauri::command]
 pub fn prefill_contributions(
     state: State<AppState>,
-    organization_id: i64,
-    year: i32,
-    month: i32,
-) -> Result<i64, AppError> {
-    let conn = state.db.get()?;
-    Contribution::prefill(&conn, organization_id, year, month)
+    request: PrefillRequest,
+) -> Result<PrefillResult, AppError> {
+    prefill::apply(&state.db.pool()?, &request)
 }
 
 #[tauri::command]
@@ -47,10 +62,5 @@ pub fn get_available_periods(
     state: State<AppState>,
     organization_id: i64,
 ) -> Result<Vec<Period>, AppError> {
-    let conn = state.db.get()?;
-    let periods = Contribution::get_available_periods(&conn, organization_id)?;
-    Ok(periods
-        .into_iter()
-        .map(|(year, month)| Period { year, month })
-        .collect())
+    contributions::available_periods(&state.db.pool()?, organization_id)
 }
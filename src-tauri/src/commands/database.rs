@@ -0,0 +1,70 @@
+use std::path::Path;
+use tauri::State;
+
+use crate::db::backup::{BackupFile, BackupKind, BackupOptions};
+use crate::db::database::DatabaseStatus;
+use crate::error::AppError;
+use crate::secret::Password;
+use crate::services::settings;
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn get_database_status(state: State<AppState>) -> DatabaseStatus {
+    state.db.status()
+}
+
+#[tauri::command]
+pub fn unlock_database(
+    state: State<AppState>,
+    password: Password,
+) -> Result<DatabaseStatus, AppError> {
+    let status = state.db.unlock(&password)?;
+    if let Err(e) = settings::load_language(&state.db.pool()?) {
+        log::warn!("Failed to load language: {}", e);
+    }
+    // The startup backup of an encrypted database waits until it is unlocked
+    if let Err(e) = state.db.auto_backup(BackupKind::Startup) {
+        log::warn!("Startup backup failed: {}", e);
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn encrypt_database(
+    state: State<AppState>,
+    password: Password,
+) -> Result<DatabaseStatus, AppError> {
+    state.db.encrypt(&password)
+}
+
+#[tauri::command]
+pub fn change_database_password(
+    state: State<AppState>,
+    current_password: Password,
+    new_password: Password,
+) -> Result<DatabaseStatus, AppError> {
+    state.db.change_password(&current_password, &new_password)
+}
+
+#[tauri::command]
+pub fn create_backup(
+    state: State<AppState>,
+    path: String,
+    options: BackupOptions,
+) -> Result<(), AppError> {
+    state.db.backup(Path::new(&path), &options)
+}
+
+#[tauri::command]
+pub fn list_backups(state: State<AppState>) -> Result<Vec<BackupFile>, AppError> {
+    state.db.list_backups()
+}
+
+#[tauri::command]
+pub fn restore_backup(
+    state: State<AppState>,
+    path: String,
+    password: Option<Password>,
+) -> Result<(), AppError> {
+    state.db.restore(Path::new(&path), password.as_ref())
+}
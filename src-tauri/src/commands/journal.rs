@@ -0,0 +1,30 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::models::journal::JournalEntry;
+use crate::services::journal;
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_journal(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<Vec<JournalEntry>, AppError> {
+    journal::list(&state.db.pool()?, organization_id)
+}
+
+#[tauri::command]
+pub fn undo(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<Option<JournalEntry>, AppError> {
+    journal::undo(&state.db.pool()?, organization_id)
+}
+
+#[tauri::command]
+pub fn redo(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<Option<JournalEntry>, AppError> {
+    journal::redo(&state.db.pool()?, organization_id)
+}
@@ -1,55 +1,36 @@
-use serde::Serialize;
 use tauri::State;
 
 use crate::error::AppError;
-use crate::models::member::{CreateMember, Member, UpdateMember};
+use crate::models::details_history::DetailsChange;
+use crate::models::member::{CreateMember, Member, MemberPage, MemberQuery, UpdateMember};
+use crate::models::pesel_history::{ChangePesel, PeselChange};
+use crate::models::transfer::{MemberTransfer, TransferMember};
+use crate::services::members::{self, PeselValidationResult};
+use crate::services::merge::{self, MergePreview, MergeRequest, MergeResult};
 use crate::state::AppState;
-use crate::validation::pesel;
-
-#[derive(Serialize)]
-pub struct PeselValidationResult {
-    pub valid: bool,
-    pub date_of_birth: Option<String>,
-    pub gender: Option<String>,
-    pub error: Option<String>,
+
+#[tauri::command]
+pub fn list_members(state: State<AppState>, organization_id: i64) -> Result<Vec<Member>, AppError> {
+    members::list(&state.db.pool()?, organization_id)
 }
 
 #[tauri::command]
-pub fn list_members(
+pub fn query_members(
     state: State<AppState>,
     organization_id: i64,
-) -> Result<Vec<Member>, AppError> {
-    let conn = state.db.get()?;
-    Member::list(&conn, organization_id)
+    query: MemberQuery,
+) -> Result<MemberPage, AppError> {
+    members::query(&state.db.pool()?, organization_id, &query)
 }
 
 #[tauri::command]
 pub fn get_member(state: State<AppState>, id: i64) -> Result<Member, AppError> {
-    let conn = state.db.get()?;
-    Member::get(&conn, id)
+    members::get(&state.db.pool()?, id)
 }
 
 #[tauri::command]
-pub fn create_member(
-    state: State<AppState>,
-    data: CreateMember,
-) -> Result<Member, AppError> {
-    // Validate PESEL and auto-derive DOB/gender
-    let info = pesel::validate(&data.pesel)?;
-
-    if data.first_name.trim().is_empty() {
-        return Err(AppError::Validation("Imię jest wymagane".into()));
-    }
-    if data.last_name.trim().is_empty() {
-        return Err(AppError::Validation("Nazwisko jest wymagane".into()));
-    }
-
-    let mut data = data;
-    data.date_of_birth = info.date_of_birth;
-    data.gender = info.gender;
-
-    let conn = state.db.get()?;
-    Member::create(&conn, &data)
+pub fn create_member(state: State<AppState>, data: CreateMember) -> Result<Member, AppError> {
+    members::create(&state.db.pool()?, data)
 }
 
 #[tauri::command]
@@ -58,37 +39,74 @@ pub fn update_member(
     id: i64,
     data: UpdateMember,
 ) -> Result<Member, AppError> {
-    if data.first_name.trim().is_empty() {
-        return Err(AppError::Validation("Imię jest wymagane".into()));
-    }
-    if data.last_name.trim().is_empty() {
-        return Err(AppError::Validation("Nazwisko jest wymagane".into()));
-    }
-
-    let conn = state.db.get()?;
-    Member::update(&conn, id, &data)
+    members::update(&state.db.pool()?, id, &data)
+}
+
+#[tauri::command]
+pub fn list_details_history(
+    state: State<AppState>,
+    id: i64,
+) -> Result<Vec<DetailsChange>, AppError> {
+    members::list_details_history(&state.db.pool()?, id)
+}
+
+#[tauri::command]
+pub fn change_member_pesel(
+    state: State<AppState>,
+    id: i64,
+    data: ChangePesel,
+) -> Result<Member, AppError> {
+    members::change_pesel(&state.db.pool()?, id, &data)
+}
+
+#[tauri::command]
+pub fn list_pesel_history(state: State<AppState>, id: i64) -> Result<Vec<PeselChange>, AppError> {
+    members::list_pesel_history(&state.db.pool()?, id)
+}
+
+#[tauri::command]
+pub fn transfer_member(
+    state: State<AppState>,
+    data: TransferMember,
+) -> Result<MemberTransfer, AppError> {
+    members::transfer(&state.db.pool()?, &data)
+}
+
+#[tauri::command]
+pub fn list_member_transfers(
+    state: State<AppState>,
+    member_id: i64,
+) -> Result<Vec<MemberTransfer>, AppError> {
+    members::list_transfers(&state.db.pool()?, member_id)
+}
+
+#[tauri::command]
+pub fn preview_merge(
+    state: State<AppState>,
+    request: MergeRequest,
+) -> Result<MergePreview, AppError> {
+    merge::preview(&state.db.pool()?, &request)
+}
+
+#[tauri::command]
+pub fn merge_members(
+    state: State<AppState>,
+    request: MergeRequest,
+) -> Result<MergeResult, AppError> {
+    merge::apply(&state.db.pool()?, &request)
+}
+
+#[tauri::command]
+pub fn archive_member(state: State<AppState>, id: i64) -> Result<(), AppError> {
+    members::archive(&state.db.pool()?, id)
 }
 
 #[tauri::command]
-pub fn delete_member(state: State<AppState>, id: i64) -> Result<(), AppError> {
-    let conn = state.db.get()?;
-    Member::delete(&conn, id)
+pub fn restore_member(state: State<AppState>, id: i64) -> Result<Member, AppError> {
+    members::restore(&state.db.pool()?, id)
 }
 
 #[tauri::command]
 pub fn validate_pesel(pesel_str: String) -> PeselValidationResult {
-    match pesel::validate(&pesel_str) {
-        Ok(info) => PeselValidationResult {
-            valid: true,
-            date_of_birth: Some(info.date_of_birth),
-            gender: Some(info.gender),
-            error: None,
-        },
-        Err(e) => PeselValidationResult {
-            valid: false,
-            date_of_birth: None,
-            gender: None,
-            error: Some(e.to_string()),
-        },
-    }
+    members::validate_pesel(&pesel_str)
 }
@@ -1,4 +1,13 @@
+pub mod audit;
+pub mod contacts;
 pub mod contributions;
+pub mod database;
 pub mod generations;
+pub mod journal;
 pub mod members;
 pub mod organizations;
+pub mod persons;
+pub mod retention;
+pub mod settings;
+pub mod subject_access;
+pub mod trash;
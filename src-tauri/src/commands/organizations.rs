@@ -2,19 +2,17 @@ use tauri::State;
 
 use crate::error::AppError;
 use crate::models::organization::{CreateOrganization, Organization, UpdateOrganization};
+use crate::services::organizations;
 use crate::state::AppState;
-use crate::validation::{nip, regon};
 
 #[tauri::command]
 pub fn list_organizations(state: State<AppState>) -> Result<Vec<Organization>, AppError> {
-    let conn = state.db.get()?;
-    Organization::list(&conn)
+    organizations::list(&state.db.pool()?)
 }
 
 #[tauri::command]
 pub fn get_organization(state: State<AppState>, id: i64) -> Result<Organization, AppError> {
-    let conn = state.db.get()?;
-    Organization::get(&conn, id)
+    organizations::get(&state.db.pool()?, id)
 }
 
 #[tauri::command]
@@ -22,15 +20,7 @@ pub fn create_organization(
     state: State<AppState>,
     data: CreateOrganization,
 ) -> Result<Organization, AppError> {
-    nip::validate(&data.nip)?;
-    regon::validate(&data.regon)?;
-
-    if data.name.trim().is_empty() {
-        return Err(AppError::Validation("Nazwa organizacji jest wymagana".into()));
-    }
-
-    let conn = state.db.get()?;
-    Organization::create(&conn, &data)
+    organizations::create(&state.db.pool()?, &data)
 }
 
 #[tauri::command]
@@ -39,19 +29,15 @@ pub fn update_organization(
     id: i64,
     data: UpdateOrganization,
 ) -> Result<Organization, AppError> {
-    nip::validate(&data.nip)?;
-    regon::validate(&data.regon)?;
-
-    if data.name.trim().is_empty() {
-        return Err(AppError::Validation("Nazwa organizacji jest wymagana".into()));
-    }
+    organizations::update(&state.db.pool()?, id, &data)
+}
 
-    let conn = state.db.get()?;
-    Organization::update(&conn, id, &data)
+#[tauri::command]
+pub fn archive_organization(state: State<AppState>, id: i64) -> Result<(), AppError> {
+    organizations::archive(&state.db.pool()?, id)
 }
 
 #[tauri::command]
-pub fn delete_organization(state: State<AppState>, id: i64) -> Result<(), AppError> {
-    let conn = state.db.get()?;
-    Organization::delete(&conn, id)
+pub fn restore_organization(state: State<AppState>, id: i64) -> Result<Organization, AppError> {
+    organizations::restore(&state.db.pool()?, id)
 }
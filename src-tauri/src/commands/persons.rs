@@ -0,0 +1,20 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::persons::{self, Person, PersonFilter, PersonKey, PersonReport};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_persons(state: State<AppState>, filter: PersonFilter) -> Result<Vec<Person>, AppError> {
+    persons::list(&state.db.pool()?, &filter)
+}
+
+#[tauri::command]
+pub fn get_person(state: State<AppState>, key: PersonKey) -> Result<Person, AppError> {
+    persons::get(&state.db.pool()?, &key)
+}
+
+#[tauri::command]
+pub fn get_person_report(state: State<AppState>, key: PersonKey) -> Result<PersonReport, AppError> {
+    persons::report(&state.db.pool()?, &key)
+}
@@ -0,0 +1,21 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::retention::{self, RetentionPreview, RetentionResult};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn preview_retention(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<RetentionPreview, AppError> {
+    retention::preview(&state.db.pool()?, organization_id)
+}
+
+#[tauri::command]
+pub fn apply_retention(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<RetentionResult, AppError> {
+    retention::apply(&state.db.pool()?, organization_id)
+}
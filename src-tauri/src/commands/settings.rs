@@ -0,0 +1,36 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::models::settings::{AppSettings, OrganizationSettings, UpdateOrganizationSettings};
+use crate::services::settings;
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn get_app_settings(state: State<AppState>) -> Result<AppSettings, AppError> {
+    settings::get_app(&state.db.pool()?)
+}
+
+#[tauri::command]
+pub fn update_app_settings(
+    state: State<AppState>,
+    data: AppSettings,
+) -> Result<AppSettings, AppError> {
+    settings::update_app(&state.db.pool()?, &data)
+}
+
+#[tauri::command]
+pub fn get_organization_settings(
+    state: State<AppState>,
+    organization_id: i64,
+) -> Result<OrganizationSettings, AppError> {
+    settings::get_organization(&state.db.pool()?, organization_id)
+}
+
+#[tauri::command]
+pub fn update_organization_settings(
+    state: State<AppState>,
+    organization_id: i64,
+    data: UpdateOrganizationSettings,
+) -> Result<OrganizationSettings, AppError> {
+    settings::update_organization(&state.db.pool()?, organization_id, &data)
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::subject_access::{self, ExportFormat, SubjectQuery, SubjectReport};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn get_subject_data(
+    state: State<AppState>,
+    query: SubjectQuery,
+) -> Result<SubjectReport, AppError> {
+    subject_access::collect(&state.db.pool()?, &query)
+}
+
+#[tauri::command]
+pub fn export_subject_data(
+    state: State<AppState>,
+    query: SubjectQuery,
+    format: ExportFormat,
+    path: String,
+) -> Result<String, AppError> {
+    let path = subject_access::export(&state.db.pool()?, &query, format, Path::new(&path))?;
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,21 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::models::audit::AuditEntry;
+use crate::services::trash::{self, Trash};
+use crate::state::AppState;
+
+#[tauri::command]
+pub fn list_trash(state: State<AppState>) -> Result<Trash, AppError> {
+    trash::list(&state.db.pool()?)
+}
+
+#[tauri::command]
+pub fn purge_member(state: State<AppState>, id: i64) -> Result<AuditEntry, AppError> {
+    trash::purge_member(&state.db.pool()?, id)
+}
+
+#[tauri::command]
+pub fn purge_organization(state: State<AppState>, id: i64) -> Result<AuditEntry, AppError> {
+    trash::purge_organization(&state.db.pool()?, id)
+}
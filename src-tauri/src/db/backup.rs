@@ -0,0 +1,392 @@
+//! Database backups taken with SQLite's online backup API, optionally packed
+//! into a compressed and/or AES-encrypted ZIP.
+//!
+//! A backup holds the database exactly as stored, so a backup of an encrypted
+//! database is encrypted with the master password. The automatic backups are
+//! re-written whenever that password is set or changed.
+
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::db::{database, migrations};
+use crate::error::{AppError, Message};
+use crate::secret::Password;
+
+/// Automatic backups of each kind kept before the oldest are removed.
+const KEEP_PER_KIND: usize = 10;
+/// Name of the database inside a ZIP backup.
+const ENTRY_NAME: &str = "ppk_gen.db";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    Startup,
+    Migration,
+    Daily,
+    /// Taken right before another backup is restored.
+    Restore,
+}
+
+impl BackupKind {
+    const ALL: [BackupKind; 4] = [
+        BackupKind::Startup,
+        BackupKind::Migration,
+        BackupKind::Daily,
+        BackupKind::Restore,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Startup => "startup",
+            BackupKind::Migration => "migration",
+            BackupKind::Daily => "daily",
+            BackupKind::Restore => "restore",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BackupOptions {
+    #[serde(default)]
+    pub compress: bool,
+    #[serde(default)]
+    pub password: Option<Password>,
+}
+
+/// An automatic backup found in the backup directory.
+#[derive(Debug, Serialize)]
+pub struct BackupFile {
+    pub path: String,
+    pub kind: BackupKind,
+    /// `YYYY-MM-DD HH:MM:SS`, local time.
+    pub created_at: String,
+    pub size: u64,
+}
+
+/// Automatic backups live next to the database file.
+pub fn dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("backups")
+}
+
+/// Copy the live database behind `conn` to `target`. `key` is the key of the
+/// live database, which the copy is written with.
+pub fn write(
+    conn: &Connection,
+    key: Option<&Password>,
+    target: &Path,
+    options: &BackupOptions,
+) -> Result<(), AppError> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let scratch = scratch_path(target, ".tmp");
+    let result = write_via(conn, key, &scratch, target, options);
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+fn write_via(
+    conn: &Connection,
+    key: Option<&Password>,
+    scratch: &Path,
+    target: &Path,
+    options: &BackupOptions,
+) -> Result<(), AppError> {
+    {
+        let mut copy = open(scratch, key)?;
+        Backup::new(conn, &mut copy)?.run_to_completion(256, Duration::ZERO, None)?;
+    }
+
+    if !options.compress && options.password.is_none() {
+        std::fs::rename(scratch, target)?;
+        return Ok(());
+    }
+
+    pack(scratch, target, options)
+}
+
+/// Write the database file at `source` into a ZIP at `target`.
+fn pack(source: &Path, target: &Path, options: &BackupOptions) -> Result<(), AppError> {
+    let method = if options.compress {
+        CompressionMethod::Deflated
+    } else {
+        CompressionMethod::Stored
+    };
+    let file_options = SimpleFileOptions::default().compression_method(method);
+    let file_options = match &options.password {
+        Some(password) if password.is_empty() => {
+            return Err(AppError::Validation(
+                Message::new("backup.password_empty").with_field("password"),
+            ));
+        }
+        Some(password) => file_options.with_aes_encryption(AesMode::Aes256, password.expose()),
+        None => file_options,
+    };
+
+    let mut zip = ZipWriter::new(File::create(target)?);
+    zip.start_file(ENTRY_NAME, file_options)?;
+    std::io::copy(&mut File::open(source)?, &mut zip)?;
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// Take an automatic backup of `kind` into `dir` and drop the oldest ones of
+/// that kind beyond `KEEP_PER_KIND`.
+pub fn rotate(
+    conn: &Connection,
+    key: Option<&Password>,
+    dir: &Path,
+    kind: BackupKind,
+) -> Result<PathBuf, AppError> {
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let mut path = dir.join(format!("ppk_gen_{}_{}.zip", kind.as_str(), stamp));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("ppk_gen_{}_{}_{}.zip", kind.as_str(), stamp, n));
+    }
+
+    let options = BackupOptions {
+        compress: true,
+        password: None,
+    };
+    write(conn, key, &path, &options)?;
+    log::info!("Database backup written to {:?}", path);
+
+    let old: Vec<BackupFile> = list(dir)?
+        .into_iter()
+        .filter(|b| b.kind == kind)
+        .skip(KEEP_PER_KIND)
+        .collect();
+    for backup in old {
+        if let Err(e) = std::fs::remove_file(&backup.path) {
+            log::warn!("Failed to remove old backup {}: {}", backup.path, e);
+        }
+    }
+
+    Ok(path)
+}
+
+/// Automatic backups in `dir`, newest first.
+pub fn list(dir: &Path) -> Result<Vec<BackupFile>, AppError> {
+    let mut backups = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+        Err(e) => return Err(e.into()),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((kind, created_at)) = parse_name(&name) else {
+            continue;
+        };
+        backups.push(BackupFile {
+            path: entry.path().to_string_lossy().into_owned(),
+            kind,
+            created_at,
+            size: entry.metadata()?.len(),
+        });
+    }
+
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.path.cmp(&a.path)));
+    Ok(backups)
+}
+
+/// Re-write the automatic backups in `dir` that open with `from` (no key for
+/// plain ones) encrypted with `to`, once the live database has been encrypted
+/// or re-keyed. A plain backup that cannot be converted is removed rather
+/// than left readable; an encrypted one is kept as it is.
+pub fn rekey_all(dir: &Path, from: Option<&Password>, to: &Password) -> Result<(), AppError> {
+    for backup in list(dir)? {
+        let path = Path::new(&backup.path);
+        let Err(e) = rekey_backup(path, from, to) else {
+            continue;
+        };
+        if from.is_some() {
+            log::warn!("Failed to re-key backup {}: {}", backup.path, e);
+            continue;
+        }
+        log::warn!(
+            "Failed to encrypt backup {}, removing it: {}",
+            backup.path,
+            e
+        );
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove backup {}: {}", backup.path, e);
+        }
+    }
+    Ok(())
+}
+
+fn rekey_backup(path: &Path, from: Option<&Password>, to: &Password) -> Result<(), AppError> {
+    let extracted = scratch_path(path, ".tmp");
+    let encrypted = scratch_path(path, ".encrypted.tmp");
+    let packed = scratch_path(path, ".packed.tmp");
+    let result = rekey_backup_via(path, from, to, &extracted, &encrypted, &packed);
+    for scratch in [&extracted, &encrypted, &packed] {
+        let _ = std::fs::remove_file(scratch);
+    }
+    result
+}
+
+fn rekey_backup_via(
+    path: &Path,
+    from: Option<&Password>,
+    to: &Password,
+    extracted: &Path,
+    encrypted: &Path,
+    packed: &Path,
+) -> Result<(), AppError> {
+    extract(path, extracted, None)?;
+    {
+        let conn = open(extracted, from)?;
+        let opens = conn
+            .query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .is_ok();
+        if !opens {
+            // Taken after the database was encrypted or re-keyed
+            return Ok(());
+        }
+        database::export_encrypted(&conn, encrypted, to)?;
+    }
+
+    let options = BackupOptions {
+        compress: true,
+        password: None,
+    };
+    pack(encrypted, packed, &options)?;
+    std::fs::rename(packed, path)?;
+    Ok(())
+}
+
+/// Whether no daily backup has been taken today.
+pub fn daily_due(dir: &Path) -> Result<bool, AppError> {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    Ok(!list(dir)?
+        .iter()
+        .any(|b| b.kind == BackupKind::Daily && b.created_at.starts_with(&today)))
+}
+
+/// Replace the live database behind `conn` with the backup at `source`, after
+/// checking that the backup is intact and not from a newer version of the
+/// application. `password` opens an encrypted ZIP backup.
+pub fn restore(
+    conn: &mut Connection,
+    key: Option<&Password>,
+    source: &Path,
+    password: Option<&Password>,
+) -> Result<(), AppError> {
+    let scratch = scratch_path(source, ".tmp");
+    let result = restore_via(conn, key, source, &scratch, password);
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+fn restore_via(
+    conn: &mut Connection,
+    key: Option<&Password>,
+    source: &Path,
+    scratch: &Path,
+    password: Option<&Password>,
+) -> Result<(), AppError> {
+    let mut magic = [0u8; 4];
+    let is_zip = File::open(source)?.read(&mut magic)? == 4 && &magic == b"PK\x03\x04";
+    let db_path = if is_zip {
+        extract(source, scratch, password)?;
+        scratch
+    } else {
+        source
+    };
+
+    let backup = open(db_path, key)?;
+    check(&backup)?;
+
+    Backup::new(&backup, conn)?.run_to_completion(256, Duration::ZERO, None)?;
+    // A backup from an older version is brought up to the current schema
+    migrations::run(conn)?;
+    Ok(())
+}
+
+fn extract(source: &Path, target: &Path, password: Option<&Password>) -> Result<(), AppError> {
+    let mut zip = ZipArchive::new(File::open(source)?)?;
+    let index = zip
+        .index_for_name(ENTRY_NAME)
+        .ok_or_else(|| AppError::Validation("backup.not_backup".into()))?;
+
+    let mut entry = if zip.by_index_raw(index)?.encrypted() {
+        let password = password.ok_or_else(|| {
+            AppError::Validation(Message::new("backup.password_required").with_field("password"))
+        })?;
+        zip.by_index_decrypt(index, password.expose().as_bytes())
+            .map_err(|_| {
+                AppError::Validation(Message::new("backup.wrong_password").with_field("password"))
+            })?
+    } else {
+        zip.by_index(index)?
+    };
+    std::io::copy(&mut entry, &mut File::create(target)?)?;
+    Ok(())
+}
+
+/// Integrity and schema version checks run on a backup before it is restored.
+fn check(backup: &Connection) -> Result<(), AppError> {
+    let readable = backup.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    });
+    if readable.is_err() {
+        return Err(AppError::Validation("backup.unreadable".into()));
+    }
+
+    let integrity: String = backup.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(AppError::Validation(
+            Message::new("backup.corrupted").with_param("details", integrity),
+        ));
+    }
+
+    let unknown = migrations::unknown_versions(backup)?
+        .ok_or_else(|| AppError::Validation("backup.not_backup".into()))?;
+    if !unknown.is_empty() {
+        return Err(AppError::Validation(
+            Message::new("backup.newer_schema").with_param("versions", unknown.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+fn open(path: &Path, key: Option<&Password>) -> Result<Connection, AppError> {
+    let conn = Connection::open(path)?;
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key.expose())?;
+    }
+    Ok(conn)
+}
+
+/// Temporary file next to `path`, so plain data never leaves the directory.
+fn scratch_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `ppk_gen_<kind>_<YYYYmmdd>_<HHMMSS>[_<n>].zip` -> kind and creation time.
+fn parse_name(name: &str) -> Option<(BackupKind, String)> {
+    let rest = name.strip_prefix("ppk_gen_")?.strip_suffix(".zip")?;
+    let kind = BackupKind::ALL
+        .into_iter()
+        .find(|k| rest.starts_with(&format!("{}_", k.as_str())))?;
+    let stamp = rest.get(kind.as_str().len() + 1..kind.as_str().len() + 16)?;
+    let created = chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d_%H%M%S").ok()?;
+    Some((kind, created.format("%Y-%m-%d %H:%M:%S").to_string()))
+}
@@ -0,0 +1,280 @@
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::db::backup::{self, BackupFile, BackupKind, BackupOptions};
+use crate::db::pool::{self, DbConnection, DbPool};
+use crate::error::{AppError, Message};
+use crate::secret::Password;
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DatabaseStatus {
+    pub encrypted: bool,
+    pub locked: bool,
+}
+
+/// The application database file. An encrypted file stays locked, with no
+/// pool, until `unlock` is called with the master password.
+pub struct Database {
+    path: PathBuf,
+    inner: RwLock<Inner>,
+}
+
+struct Inner {
+    pool: Option<DbPool>,
+    encrypted: bool,
+    /// Master password of an unlocked encrypted database, needed to write
+    /// and read backups keyed the same way.
+    key: Option<Password>,
+}
+
+impl Database {
+    /// Opens a plain (or not yet existing) database right away; an encrypted
+    /// one is left locked.
+    pub fn open(path: &Path) -> Result<Database, AppError> {
+        let encrypted = path.exists() && !readable(path, None)?;
+        let pool = if encrypted {
+            None
+        } else {
+            Some(pool::create_pool(path, None).map_err(open_error)?)
+        };
+
+        Ok(Database {
+            path: path.to_path_buf(),
+            inner: RwLock::new(Inner {
+                pool,
+                encrypted,
+                key: None,
+            }),
+        })
+    }
+
+    pub fn status(&self) -> DatabaseStatus {
+        let inner = self.inner.read().unwrap();
+        DatabaseStatus {
+            encrypted: inner.encrypted,
+            locked: inner.pool.is_none(),
+        }
+    }
+
+    pub fn pool(&self) -> Result<DbPool, AppError> {
+        self.inner
+            .read()
+            .unwrap()
+            .pool
+            .clone()
+            .ok_or(AppError::Locked)
+    }
+
+    pub fn unlock(&self, password: &Password) -> Result<DatabaseStatus, AppError> {
+        let mut inner = self.inner.write().unwrap();
+        if inner.pool.is_none() {
+            if !readable(&self.path, Some(password))? {
+                return Err(wrong_password());
+            }
+            inner.pool = Some(pool::create_pool(&self.path, Some(password)).map_err(open_error)?);
+            inner.key = Some(password.clone());
+        }
+        drop(inner);
+        Ok(self.status())
+    }
+
+    /// One-off migration of a plain database into an encrypted one. The data
+    /// is exported into a new file which then replaces the original, and the
+    /// automatic backups taken so far are encrypted the same way.
+    pub fn encrypt(&self, password: &Password) -> Result<DatabaseStatus, AppError> {
+        validate_new_password(password)?;
+
+        let mut inner = self.inner.write().unwrap();
+        if inner.encrypted {
+            return Err(AppError::Validation("database.already_encrypted".into()));
+        }
+        let pool = inner.pool.take().ok_or(AppError::Locked)?;
+
+        if let Err(e) = self.replace_encrypted(pool, password) {
+            // Keep the plain database usable after a failed attempt
+            inner.pool = Some(pool::create_pool(&self.path, None).map_err(open_error)?);
+            return Err(e);
+        }
+        inner.encrypted = true;
+        inner.key = Some(password.clone());
+        inner.pool = Some(pool::create_pool(&self.path, Some(password)).map_err(open_error)?);
+        backup::rekey_all(&self.backup_dir(), None, password)?;
+
+        drop(inner);
+        Ok(self.status())
+    }
+
+    /// Re-keys an encrypted database, and its automatic backups, with a new
+    /// master password.
+    pub fn change_password(
+        &self,
+        current: &Password,
+        new: &Password,
+    ) -> Result<DatabaseStatus, AppError> {
+        validate_new_password(new)?;
+
+        let mut inner = self.inner.write().unwrap();
+        if !inner.encrypted {
+            return Err(AppError::Validation("database.not_encrypted".into()));
+        }
+        if !readable(&self.path, Some(current))? {
+            return Err(wrong_password());
+        }
+        let pool = match inner.pool.take() {
+            Some(pool) => pool,
+            None => pool::create_pool(&self.path, Some(current)).map_err(open_error)?,
+        };
+
+        let replaced = self.replace_encrypted(pool, new);
+        let key = if replaced.is_ok() { new } else { current };
+        inner.pool = Some(pool::create_pool(&self.path, Some(key)).map_err(open_error)?);
+        replaced?;
+        inner.key = Some(new.clone());
+        backup::rekey_all(&self.backup_dir(), Some(current), new)?;
+
+        drop(inner);
+        Ok(self.status())
+    }
+
+    /// Back up the live database to `target`.
+    pub fn backup(&self, target: &Path, options: &BackupOptions) -> Result<(), AppError> {
+        let (pool, key) = self.pool_and_key()?;
+        let conn = pool.get()?;
+        backup::write(&conn, key.as_ref(), target, options)
+    }
+
+    /// Take an automatic backup into the backup directory next to the database.
+    pub fn auto_backup(&self, kind: BackupKind) -> Result<PathBuf, AppError> {
+        let (pool, key) = self.pool_and_key()?;
+        let conn = pool.get()?;
+        backup::rotate(&conn, key.as_ref(), &self.backup_dir(), kind)
+    }
+
+    /// Take the daily backup unless there already is one from today.
+    pub fn daily_backup(&self) -> Result<Option<PathBuf>, AppError> {
+        if !backup::daily_due(&self.backup_dir())? {
+            return Ok(None);
+        }
+        self.auto_backup(BackupKind::Daily).map(Some)
+    }
+
+    pub fn list_backups(&self) -> Result<Vec<BackupFile>, AppError> {
+        backup::list(&self.backup_dir())
+    }
+
+    /// Replace the current data with a backup. The current state is backed up
+    /// first so a mistaken restore can be undone.
+    pub fn restore(&self, source: &Path, password: Option<&Password>) -> Result<(), AppError> {
+        let (pool, key) = self.pool_and_key()?;
+        let mut conn = pool.get()?;
+        backup::rotate(&conn, key.as_ref(), &self.backup_dir(), BackupKind::Restore)?;
+        backup::restore(&mut conn, key.as_ref(), source, password)
+    }
+
+    /// Export the data into a new file encrypted with `password`, which then
+    /// replaces the current one.
+    fn replace_encrypted(&self, pool: DbPool, password: &Password) -> Result<(), AppError> {
+        // Commands and the backup thread may still hold clones of the pool.
+        // Checking out every connection waits for their work to finish and
+        // keeps them from starting more while the file is replaced.
+        let held = drain(&pool)?;
+
+        let target = self.path.with_extension("db.encrypting");
+        let result = export_encrypted(&held[0], &target, password)
+            .and_then(|_| retire(&held))
+            .and_then(|_| std::fs::rename(&target, &self.path).map_err(AppError::from))
+            .map(|_| remove_wal_files(&self.path));
+        let _ = std::fs::remove_file(&target);
+        result
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        backup::dir(&self.path)
+    }
+
+    fn pool_and_key(&self) -> Result<(DbPool, Option<Password>), AppError> {
+        let inner = self.inner.read().unwrap();
+        let pool = inner.pool.clone().ok_or(AppError::Locked)?;
+        Ok((pool, inner.key.clone()))
+    }
+}
+
+/// Whether the file can be read with the given key (or without one).
+fn readable(path: &Path, key: Option<&Password>) -> Result<bool, AppError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key.expose())?;
+    }
+    let result = conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    });
+    match result {
+        Ok(_) => Ok(true),
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::NotADatabase => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Every connection of the pool, checked out as soon as each is free.
+fn drain(pool: &DbPool) -> Result<Vec<DbConnection>, AppError> {
+    (0..pool.max_size()).map(|_| Ok(pool.get()?)).collect()
+}
+
+/// Make drained connections read-only before their file is replaced, so a
+/// pool handle that outlives the swap cannot write to the old file.
+fn retire(connections: &[DbConnection]) -> Result<(), AppError> {
+    for conn in connections {
+        conn.pragma_update(None, "query_only", true)?;
+    }
+    Ok(())
+}
+
+/// Copy the database behind `conn` into a new file at `target`, encrypted
+/// with `password`.
+pub(crate) fn export_encrypted(
+    conn: &Connection,
+    target: &Path,
+    password: &Password,
+) -> Result<(), AppError> {
+    let _ = std::fs::remove_file(target);
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        (target.to_string_lossy(), password.expose()),
+    )?;
+    let exported = conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()));
+    conn.execute_batch("DETACH DATABASE encrypted;")?;
+    exported?;
+    Ok(())
+}
+
+fn remove_wal_files(path: &Path) {
+    for suffix in ["-wal", "-shm"] {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        let _ = std::fs::remove_file(name);
+    }
+}
+
+fn validate_new_password(password: &Password) -> Result<(), AppError> {
+    if password.expose().chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(
+            Message::new("database.password_too_short")
+                .with_field("password")
+                .with_param("min", MIN_PASSWORD_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+fn wrong_password() -> AppError {
+    AppError::Validation(Message::new("database.wrong_password").with_field("password"))
+}
+
+fn open_error(e: Box<dyn std::error::Error>) -> AppError {
+    AppError::Other(Message::new("database.open_failed").with_param("details", e))
+}
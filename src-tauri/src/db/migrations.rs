@@ -1,10 +1,71 @@
-use rusqlite::{Connection, params};
+use rusqlite::{params, Connection};
 
-const MIGRATIONS: &[(&str, &str)] = &[
-    ("001_initial", include_str!("../../migrations/001_initial.sql")),
+use crate::error::{AppError, Message};
+
+/// A schema change with the script that undoes it. Each one is applied or
+/// reverted inside its own transaction.
+pub struct Migration {
+    pub version: &'static str,
+    up: &'static str,
+    down: &'static str,
+}
+
+/// `migrations/<version>.sql` and its `migrations/<version>.down.sql`.
+macro_rules! migration {
+    ($version:literal) => {
+        Migration {
+            version: $version,
+            up: include_str!(concat!("../../migrations/", $version, ".sql")),
+            down: include_str!(concat!("../../migrations/", $version, ".down.sql")),
+        }
+    };
+}
+
+/// Every new migration also needs a fixture of the database it leaves behind
+/// in `tests/fixtures`, so upgrades from it stay covered by `migrations_test`.
+const MIGRATIONS: &[Migration] = &[
+    migration!("001_initial"),
+    migration!("002_settings"),
+    migration!("003_zip_password"),
+    migration!("004_retention"),
+    migration!("005_money_grosze"),
+    migration!("006_operation_journal"),
+    migration!("007_member_search"),
+    migration!("008_member_transfers"),
+    migration!("009_pesel_history"),
+    migration!("010_member_details_history"),
+    migration!("011_archive"),
+    migration!("012_employer_profile"),
+    migration!("013_contact_persons"),
+    migration!("014_employer_identification"),
 ];
 
-pub fn run(conn: &Connection) -> Result<(), rusqlite::Error> {
+/// Bring the schema up to date. Refuses a database that has migrations this
+/// build does not know, since it was written by a newer version of the app.
+pub fn run(conn: &Connection) -> Result<(), AppError> {
+    apply(conn, MIGRATIONS)
+}
+
+/// Revert applied migrations newest first until `version` is the latest one
+/// left, or all of them when `version` is `None`.
+pub fn revert_to(conn: &Connection, version: Option<&str>) -> Result<(), AppError> {
+    revert(conn, MIGRATIONS, version)
+}
+
+/// Version of the newest migration this build knows.
+pub fn latest_version() -> &'static str {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or_default()
+}
+
+/// Newest applied migration, if any.
+pub fn current_version(conn: &Connection) -> Result<Option<String>, rusqlite::Error> {
+    if !has_migrations_table(conn)? {
+        return Ok(None);
+    }
+    Ok(applied_versions(conn)?.pop())
+}
+
+fn apply(conn: &Connection, migrations: &[Migration]) -> Result<(), AppError> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS schema_migrations (
             version TEXT PRIMARY KEY,
@@ -13,22 +74,186 @@ pub fn run(conn: &Connection) -> Result<(), rusqlite::Error> {
         [],
     )?;
 
-    for (version, sql) in MIGRATIONS {
-        let already_applied: bool = conn.query_row(
-            "SELECT COUNT(*) > 0 FROM schema_migrations WHERE version = ?1",
-            params![version],
-            |row| row.get(0),
+    let applied = applied_versions(conn)?;
+    let unknown: Vec<&str> = applied
+        .iter()
+        .filter(|a| !migrations.iter().any(|m| m.version == a.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AppError::NewerSchema(unknown.join(", ")));
+    }
+
+    for migration in migrations {
+        if applied.iter().any(|a| a == migration.version) {
+            continue;
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration.up)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            params![migration.version],
         )?;
+        tx.commit()?;
+        log::info!("Applied migration: {}", migration.version);
+    }
 
-        if !already_applied {
-            conn.execute_batch(sql)?;
-            conn.execute(
-                "INSERT INTO schema_migrations (version) VALUES (?1)",
-                params![version],
-            )?;
-            log::info!("Applied migration: {}", version);
+    Ok(())
+}
+
+fn revert(
+    conn: &Connection,
+    migrations: &[Migration],
+    version: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(version) = version {
+        if !migrations.iter().any(|m| m.version == version) {
+            return Err(AppError::Validation(
+                Message::new("migrations.unknown_version").with_param("version", version),
+            ));
+        }
+    }
+
+    let applied = applied_versions(conn)?;
+    for migration in migrations.iter().rev() {
+        if Some(migration.version) == version {
+            break;
+        }
+        if !applied.iter().any(|a| a == migration.version) {
+            continue;
         }
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration.down)?;
+        tx.execute(
+            "DELETE FROM schema_migrations WHERE version = ?1",
+            params![migration.version],
+        )?;
+        tx.commit()?;
+        log::info!("Reverted migration: {}", migration.version);
     }
 
     Ok(())
 }
+
+/// Whether an existing database has migrations left to apply. A brand new
+/// database (without `schema_migrations`) has nothing worth backing up first.
+pub fn has_pending(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    if !has_migrations_table(conn)? {
+        return Ok(false);
+    }
+    let applied = applied_versions(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .any(|m| !applied.iter().any(|a| a == m.version)))
+}
+
+/// Applied migrations this build does not know about, i.e. the database was
+/// written by a newer version of the application. `None` when the database
+/// has no migration table at all.
+pub fn unknown_versions(conn: &Connection) -> Result<Option<Vec<String>>, rusqlite::Error> {
+    if !has_migrations_table(conn)? {
+        return Ok(None);
+    }
+    let mut applied = applied_versions(conn)?;
+    applied.retain(|a| !MIGRATIONS.iter().any(|m| m.version == a));
+    Ok(Some(applied))
+}
+
+fn has_migrations_table(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+        [],
+        |row| row.get(0),
+    )
+}
+
+fn applied_versions(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT version FROM schema_migrations ORDER BY version")?;
+    let versions = stmt.query_map([], |row| row.get(0))?;
+    versions.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_no_trace() {
+        let conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            Migration {
+                version: "001_a",
+                up: "CREATE TABLE a (id INTEGER);",
+                down: "DROP TABLE a;",
+            },
+            Migration {
+                version: "002_b",
+                up: "CREATE TABLE b (id INTEGER); INSERT INTO missing VALUES (1);",
+                down: "DROP TABLE b;",
+            },
+        ];
+
+        assert!(apply(&conn, &migrations).is_err());
+        assert!(table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+        assert_eq!(applied_versions(&conn).unwrap(), vec!["001_a"]);
+    }
+
+    #[test]
+    fn test_revert_runs_down_scripts_newest_first() {
+        let conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            Migration {
+                version: "001_a",
+                up: "CREATE TABLE a (id INTEGER);",
+                down: "DROP TABLE a;",
+            },
+            Migration {
+                version: "002_b",
+                up: "CREATE TABLE b (id INTEGER REFERENCES a(id));",
+                down: "DROP TABLE b;",
+            },
+        ];
+        apply(&conn, &migrations).unwrap();
+
+        revert(&conn, &migrations, Some("001_a")).unwrap();
+        assert!(table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+
+        revert(&conn, &migrations, None).unwrap();
+        assert!(!table_exists(&conn, "a"));
+        assert!(applied_versions(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_applied_version_is_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        let migrations = [Migration {
+            version: "001_a",
+            up: "CREATE TABLE a (id INTEGER);",
+            down: "DROP TABLE a;",
+        }];
+        apply(&conn, &migrations).unwrap();
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES ('002_future')",
+            [],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            apply(&conn, &migrations),
+            Err(AppError::NewerSchema(_))
+        ));
+    }
+}
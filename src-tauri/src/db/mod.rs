@@ -1,2 +1,4 @@
+pub mod backup;
+pub mod database;
 pub mod migrations;
 pub mod pool;
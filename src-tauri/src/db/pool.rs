@@ -1,18 +1,72 @@
-use r2d2::Pool;
+use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
 use std::path::Path;
 
+use crate::db::backup::{self, BackupKind};
 use crate::db::migrations;
-use crate::state::DbPool;
+use crate::secret::Password;
 
-pub fn create_pool(db_path: &Path) -> Result<DbPool, Box<dyn std::error::Error>> {
+pub type DbPool = Pool<SqliteConnectionManager>;
+pub type DbConnection = PooledConnection<SqliteConnectionManager>;
+
+/// Opens the database file, keying every connection with `key` when the file
+/// is encrypted with SQLCipher.
+pub fn create_pool(
+    db_path: &Path,
+    key: Option<&Password>,
+) -> Result<DbPool, Box<dyn std::error::Error>> {
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
-    let manager = SqliteConnectionManager::file(db_path);
+    let connection_key = key.cloned();
+    let manager = SqliteConnectionManager::file(db_path).with_init(move |conn| {
+        // `key` has to be the first statement run on a new connection
+        if let Some(key) = &connection_key {
+            conn.pragma_update(None, "key", key.expose())?;
+        }
+        register_functions(conn)
+    });
     let pool = Pool::builder().max_size(4).build(manager)?;
 
+    init(&pool, Some(&backup::dir(db_path)), key)?;
+    Ok(pool)
+}
+
+/// Fresh in-memory database with the full schema. Each in-memory connection is
+/// its own database, so the pool is limited to a single connection.
+pub fn create_memory_pool() -> Result<DbPool, Box<dyn std::error::Error>> {
+    let manager = SqliteConnectionManager::memory().with_init(register_functions);
+    let pool = Pool::builder().max_size(1).build(manager)?;
+
+    init(&pool, None, None)?;
+    Ok(pool)
+}
+
+/// SQL functions the queries rely on. SQLite's own `lower()` and `LIKE` only
+/// fold ASCII letters, so `unicode_lower()` is there for Polish names.
+fn register_functions(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "unicode_lower",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            Ok(ctx
+                .get::<Option<String>>(0)?
+                .map(|text| text.to_lowercase()))
+        },
+    )
+}
+
+/// Configure the connection and migrate, backing up into `backup_dir` first
+/// when there are migrations to apply.
+fn init(
+    pool: &DbPool,
+    backup_dir: Option<&Path>,
+    key: Option<&Password>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Configure SQLite
     {
         let conn = pool.get()?;
@@ -27,8 +81,13 @@ pub fn create_pool(db_path: &Path) -> Result<DbPool, Box<dyn std::error::Error>>
     // Run migrations
     {
         let conn = pool.get()?;
+        if let Some(dir) = backup_dir {
+            if migrations::has_pending(&conn)? {
+                backup::rotate(&conn, key, dir, BackupKind::Migration)?;
+            }
+        }
         migrations::run(&conn)?;
     }
 
-    Ok(pool)
+    Ok(())
 }
@@ -0,0 +1,123 @@
+//! Fixed sets of codes stored as TEXT and exchanged with the frontend as the
+//! same strings.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{AppError, Message};
+
+macro_rules! code {
+    ($(#[$doc:meta])* $name:ident ($field:literal) { $($variant:ident => $code:literal),+ $(,)? }) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum $name {
+            $(#[serde(rename = $code)] $variant),+
+        }
+
+        impl $name {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $code),+
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = AppError;
+
+            fn from_str(value: &str) -> Result<$name, AppError> {
+                match value {
+                    $($code => Ok($name::$variant),)+
+                    _ => Err(AppError::Validation(
+                        Message::new("code.invalid")
+                            .with_field($field)
+                            .with_param("value", value),
+                    )),
+                }
+            }
+        }
+
+        impl ToSql for $name {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(ToSqlOutput::from(self.as_str()))
+            }
+        }
+
+        impl FromSql for $name {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<$name> {
+                value
+                    .as_str()?
+                    .parse()
+                    .map_err(|e: AppError| FromSqlError::Other(e.to_string().into()))
+            }
+        }
+    };
+}
+
+code!(
+    /// `<PLEC>`: derived from the PESEL.
+    Gender ("gender") { M => "M", K => "K" }
+);
+
+code!(
+    MemberStatus ("status") {
+        Active => "active",
+        Resigned => "resigned",
+        Terminated => "terminated",
+    }
+);
+
+code!(
+    /// A T/N flag, as used for `<UCZ_OBNIZ_SKL_POD>`.
+    YesNo ("reduced_basic_flag") { Yes => "T", No => "N" }
+);
+
+code!(
+    /// Whether a contribution was typed in or copied by prefill.
+    ContributionSource ("source") {
+        Manual => "manual",
+        Prefilled => "prefilled",
+    }
+);
+
+code!(
+    /// How an employer is identified in `<PRACODAWCA>`. Local units use their
+    /// 14-digit REGON, some sole proprietors their PESEL.
+    EmployerIdType ("id_type") {
+        Nip => "NIP",
+        Regon => "REGON",
+        Pesel => "PESEL",
+    }
+);
+
+/// Every employer was identified by NIP before the type could be chosen.
+impl Default for EmployerIdType {
+    fn default() -> EmployerIdType {
+        EmployerIdType::Nip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip() {
+        assert_eq!("K".parse::<Gender>().unwrap(), Gender::K);
+        assert_eq!(MemberStatus::Resigned.as_str(), "resigned");
+        assert_eq!(serde_json::to_string(&YesNo::Yes).unwrap(), "\"T\"");
+        assert_eq!(
+            serde_json::from_str::<ContributionSource>("\"prefilled\"").unwrap(),
+            ContributionSource::Prefilled
+        );
+        assert!("X".parse::<YesNo>().is_err());
+    }
+}
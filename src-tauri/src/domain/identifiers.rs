@@ -0,0 +1,119 @@
+//! Polish identification numbers.
+//!
+//! `parse` checks the number. Reading from the database or from a generation
+//! snapshot does not, so stored values round-trip unchanged, including the
+//! placeholders left by anonymization.
+
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::AppError;
+use crate::validation::pesel::PeselInfo;
+use crate::validation::{nip, pesel, regon};
+
+macro_rules! identifier {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl ToSql for $name {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                self.0.to_sql()
+            }
+        }
+
+        impl FromSql for $name {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<$name> {
+                String::column_result(value).map($name)
+            }
+        }
+    };
+}
+
+identifier!(
+    /// PESEL of a participant.
+    Pesel
+);
+identifier!(
+    /// NIP of an employer, as entered (dashes allowed).
+    Nip
+);
+identifier!(
+    /// REGON of an employer.
+    Regon
+);
+
+impl Pesel {
+    pub fn parse(value: &str) -> Result<Pesel, AppError> {
+        pesel::validate(value)?;
+        Ok(Pesel(value.to_string()))
+    }
+
+    /// Date of birth and gender encoded in the number.
+    pub fn info(&self) -> Result<PeselInfo, AppError> {
+        pesel::validate(&self.0)
+    }
+
+    /// Stand-in for a PESEL removed by anonymization; not a valid number.
+    pub fn anonymized(member_id: i64) -> Pesel {
+        Pesel(format!("ANON{}", member_id))
+    }
+}
+
+impl Nip {
+    pub fn parse(value: &str) -> Result<Nip, AppError> {
+        nip::validate(value)?;
+        Ok(Nip(value.to_string()))
+    }
+
+    pub fn digits(&self) -> String {
+        self.0.chars().filter(|c| c.is_ascii_digit()).collect()
+    }
+}
+
+impl Regon {
+    pub fn parse(value: &str) -> Result<Regon, AppError> {
+        regon::validate(value)?;
+        Ok(Regon(value.to_string()))
+    }
+
+    pub fn digits(&self) -> String {
+        self.0.chars().filter(|c| c.is_ascii_digit()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_validates() {
+        assert!(Pesel::parse("85032212342").is_ok());
+        assert!(Pesel::parse("85032212343").is_err());
+        assert!(Nip::parse("526-104-08-28").is_ok());
+        assert!(Nip::parse("1234567890").is_err());
+        assert_eq!(Nip::parse("526-104-08-28").unwrap().digits(), "5261040828");
+    }
+
+    #[test]
+    fn test_deserialize_keeps_stored_value() {
+        let pesel: Pesel = serde_json::from_str("\"ANON7\"").unwrap();
+        assert_eq!(pesel, Pesel::anonymized(7));
+        assert_eq!(serde_json::to_string(&pesel).unwrap(), "\"ANON7\"");
+    }
+}
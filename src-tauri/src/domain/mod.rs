@@ -0,0 +1,4 @@
+pub mod codes;
+pub mod identifiers;
+pub mod money;
+pub mod period;
@@ -0,0 +1,191 @@
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{AppError, Message};
+
+/// A non-negative amount in PLN, held as integer grosze.
+///
+/// Stored in the database as INTEGER grosze and exchanged with the frontend
+/// and in generation snapshots as a dot-decimal string ("94.38").
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_grosze(grosze: i64) -> Money {
+        Money(grosze)
+    }
+
+    pub fn grosze(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Parse a dot-decimal amount entered in `field`.
+    pub fn parse(value: &str, field: &'static str) -> Result<Money, AppError> {
+        let invalid = |key| {
+            AppError::Validation(
+                Message::new(key)
+                    .with_field(field)
+                    .with_param("value", value),
+            )
+        };
+
+        let dec = Decimal::from_str(value.trim()).map_err(|_| invalid("money.invalid"))?;
+        if dec < Decimal::ZERO {
+            return Err(invalid("money.negative"));
+        }
+        if dec.scale() > 2 {
+            return Err(invalid("money.scale"));
+        }
+
+        let grosze = dec.mantissa() * 10i128.pow(2 - dec.scale());
+        i64::try_from(grosze)
+            .map(Money)
+            .map_err(|_| invalid("money.too_large"))
+    }
+
+    /// `rate` percent of the amount, rounded half up to whole grosze. Fails
+    /// with `money.too_large` for `field` when the result does not fit.
+    pub fn percent(self, rate: Decimal, field: &'static str) -> Result<Money, AppError> {
+        let too_large = || AppError::Validation(Message::new("money.too_large").with_field(field));
+
+        let mut value = Decimal::new(self.0, 2)
+            .checked_mul(rate)
+            .ok_or_else(too_large)?
+            / Decimal::ONE_HUNDRED;
+        value = value.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+        value.rescale(2);
+        i64::try_from(value.mantissa())
+            .map(Money)
+            .map_err(|_| too_large())
+    }
+
+    /// The sum of two amounts. Fails with `money.too_large` for `field` when
+    /// it does not fit.
+    pub fn checked_add(self, other: Money, field: &'static str) -> Result<Money, AppError> {
+        self.0
+            .checked_add(other.0)
+            .map(Money)
+            .ok_or_else(|| AppError::Validation(Message::new("money.too_large").with_field(field)))
+    }
+
+    /// Comma-decimal form used in the CSV file ("94,38").
+    pub fn to_comma_string(self) -> String {
+        self.to_string().replace('.', ",")
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, abs / 100, abs % 100)
+    }
+}
+
+impl FromStr for Money {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Money, AppError> {
+        Money::parse(value, "amount")
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ToSql for Money {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.0))
+    }
+}
+
+impl FromSql for Money {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Money> {
+        i64::column_result(value).map(Money)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_to_grosze() {
+        assert_eq!(Money::parse("94.38", "x").unwrap().grosze(), 9438);
+        assert_eq!(Money::parse("94.3", "x").unwrap().grosze(), 9430);
+        assert_eq!(Money::parse("94", "x").unwrap().grosze(), 9400);
+        assert_eq!(Money::parse("0.00", "x").unwrap(), Money::ZERO);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        assert!(Money::parse("abc", "x").is_err());
+        assert!(Money::parse("-1.00", "x").is_err());
+        assert!(Money::parse("1.001", "x").is_err());
+        assert!(Money::parse("99999999999999999999", "x").is_err());
+    }
+
+    #[test]
+    fn test_percent_rounds_half_up() {
+        let salary = Money::from_grosze(471_900);
+        let percent = |money: Money, rate| money.percent(rate, "x").unwrap().grosze();
+        assert_eq!(percent(salary, Decimal::new(2, 0)), 9438);
+        assert_eq!(percent(salary, Decimal::new(15, 1)), 7079);
+        assert_eq!(percent(Money::from_grosze(1), Decimal::new(50, 0)), 1);
+        assert_eq!(
+            percent(Money::from_grosze(10000), Decimal::new(1035, 1)),
+            10350
+        );
+    }
+
+    #[test]
+    fn test_percent_rejects_overflow() {
+        let max = Money::from_grosze(i64::MAX);
+        assert!(max.percent(Decimal::new(200, 0), "x").is_err());
+        assert!(max.percent(Decimal::MAX, "x").is_err());
+        assert!(max.percent(Decimal::new(50, 0), "x").is_ok());
+    }
+
+    #[test]
+    fn test_checked_add_rejects_overflow() {
+        let amount = Money::from_grosze(9438);
+        assert_eq!(amount.checked_add(amount, "x").unwrap().grosze(), 18876);
+        assert!(Money::from_grosze(i64::MAX)
+            .checked_add(amount, "x")
+            .is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Money::from_grosze(9438).to_string(), "94.38");
+        assert_eq!(Money::from_grosze(5).to_string(), "0.05");
+        assert_eq!(Money::from_grosze(9438).to_comma_string(), "94,38");
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let money: Money = serde_json::from_str("\"188.92\"").unwrap();
+        assert_eq!(money.grosze(), 18892);
+        assert_eq!(serde_json::to_string(&money).unwrap(), "\"188.92\"");
+        assert!(serde_json::from_str::<Money>("\"1,5\"").is_err());
+    }
+}
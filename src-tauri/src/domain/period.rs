@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::{AppError, Message};
+
+/// A contribution month. Stored as separate `period_year` and `period_month`
+/// columns and exchanged as `{ "year": 2025, "month": 12 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "PeriodParts")]
+pub struct Period {
+    pub year: i32,
+    pub month: i32,
+}
+
+#[derive(Deserialize)]
+struct PeriodParts {
+    year: i32,
+    month: i32,
+}
+
+impl TryFrom<PeriodParts> for Period {
+    type Error = AppError;
+
+    fn try_from(parts: PeriodParts) -> Result<Period, AppError> {
+        Period::new(parts.year, parts.month)
+    }
+}
+
+impl Period {
+    pub fn new(year: i32, month: i32) -> Result<Period, AppError> {
+        if !(1..=12).contains(&month) {
+            return Err(AppError::Validation(
+                Message::new("period.month")
+                    .with_field("period_month")
+                    .with_param("month", month),
+            ));
+        }
+        if !(2000..=2100).contains(&year) {
+            return Err(AppError::Validation(
+                Message::new("period.year")
+                    .with_field("period_year")
+                    .with_param("year", year),
+            ));
+        }
+        Ok(Period { year, month })
+    }
+}
+
+/// "YYYY-MM", as in `<SKL_ZA_OKRES>`.
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_validates_month() {
+        assert!(Period::new(2025, 0).is_err());
+        assert!(Period::new(2025, 13).is_err());
+        assert_eq!(Period::new(2025, 3).unwrap().to_string(), "2025-03");
+    }
+
+    #[test]
+    fn test_deserialize_validates() {
+        assert!(serde_json::from_str::<Period>(r#"{"year":2025,"month":12}"#).is_ok());
+        assert!(serde_json::from_str::<Period>(r#"{"year":2025,"month":13}"#).is_err());
+    }
+}
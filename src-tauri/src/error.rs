@@ -1,30 +1,191 @@
+use serde::ser::SerializeStruct;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Debug, thiserror::Error)]
+use crate::i18n::{self, Locale};
+
+/// A message for the user: a key into the translation catalogue, the form
+/// field it concerns and the values substituted into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: &'static str,
+    pub field: Option<&'static str>,
+    pub params: BTreeMap<&'static str, String>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Message {
+        Message {
+            key,
+            field: None,
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_field(mut self, field: &'static str) -> Message {
+        self.field = Some(field);
+        self
+    }
+
+    pub fn with_param(mut self, name: &'static str, value: impl ToString) -> Message {
+        self.params.insert(name, value.to_string());
+        self
+    }
+
+    /// `{field}` in the template becomes the field's label.
+    pub fn render(&self, locale: Locale) -> String {
+        let mut params = self.params.clone();
+        if let Some(field) = self.field {
+            params
+                .entry("field")
+                .or_insert_with(|| i18n::template(locale, &format!("field.{}", field)).to_string());
+        }
+        i18n::translate(locale, self.key, &params)
+    }
+}
+
+impl From<&'static str> for Message {
+    fn from(key: &'static str) -> Message {
+        Message::new(key)
+    }
+}
+
+/// Serialized like an error, as `{ field, message_key, params, message }`.
+impl Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_struct("Message", 4)?;
+        s.serialize_field("field", &self.field)?;
+        s.serialize_field("message_key", self.key)?;
+        s.serialize_field("params", &self.params)?;
+        s.serialize_field("message", &self.to_string())?;
+        s.end()
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(i18n::locale()))
+    }
+}
+
+/// Serialized for the frontend as
+/// `{ code, field, message_key, params, message }`, with `message` rendered
+/// in the configured language.
+#[derive(Debug)]
 pub enum AppError {
-    #[error("Błąd bazy danych: {0}")]
-    Database(#[from] rusqlite::Error),
+    Database(rusqlite::Error),
+    Pool(r2d2::Error),
+    Locked,
+    /// Versions of the unknown migrations.
+    NewerSchema(String),
+    Validation(Message),
+    NotFound(Message),
+    Generation(Message),
+    Io(std::io::Error),
+    Zip(zip::result::ZipError),
+    Other(Message),
+}
 
-    #[error("Błąd puli połączeń: {0}")]
-    Pool(#[from] r2d2::Error),
+impl AppError {
+    /// Stable identifier of the kind of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "database",
+            AppError::Pool(_) => "pool",
+            AppError::Locked => "locked",
+            AppError::NewerSchema(_) => "newer_schema",
+            AppError::Validation(_) => "validation",
+            AppError::NotFound(_) => "not_found",
+            AppError::Generation(_) => "generation",
+            AppError::Io(_) => "io",
+            AppError::Zip(_) => "zip",
+            AppError::Other(_) => "other",
+        }
+    }
 
-    #[error("Błąd walidacji: {0}")]
-    Validation(String),
+    /// The specific message, without the prefix naming the kind of error.
+    pub fn message(&self) -> Message {
+        match self {
+            AppError::Database(e) => Message::new("error.database").with_param("details", e),
+            AppError::Pool(e) => Message::new("error.pool").with_param("details", e),
+            AppError::Locked => Message::new("error.locked"),
+            AppError::NewerSchema(versions) => {
+                Message::new("error.newer_schema").with_param("versions", versions)
+            }
+            AppError::Validation(m)
+            | AppError::NotFound(m)
+            | AppError::Generation(m)
+            | AppError::Other(m) => m.clone(),
+            AppError::Io(e) => Message::new("error.io").with_param("details", e),
+            AppError::Zip(e) => Message::new("error.zip").with_param("details", e),
+        }
+    }
 
-    #[error("Nie znaleziono: {0}")]
-    NotFound(String),
+    /// Attach the form field an error concerns.
+    pub fn with_field(self, field: &'static str) -> AppError {
+        match self {
+            AppError::Validation(m) => AppError::Validation(m.with_field(field)),
+            other => other,
+        }
+    }
 
-    #[error("Błąd generowania: {0}")]
-    Generation(String),
+    pub fn render(&self, locale: Locale) -> String {
+        let prefix = match self {
+            AppError::Validation(_) => "error.validation",
+            AppError::NotFound(_) => "error.not_found",
+            AppError::Generation(_) => "error.generation",
+            _ => return self.message().render(locale),
+        };
+        Message::new(prefix)
+            .with_param("message", self.message().render(locale))
+            .render(locale)
+    }
+}
 
-    #[error("Błąd IO: {0}")]
-    Io(#[from] std::io::Error),
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(i18n::locale()))
+    }
+}
 
-    #[error("Błąd ZIP: {0}")]
-    Zip(#[from] zip::result::ZipError),
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Database(e) => Some(e),
+            AppError::Pool(e) => Some(e),
+            AppError::Io(e) => Some(e),
+            AppError::Zip(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> AppError {
+        AppError::Database(e)
+    }
+}
 
-    #[error("{0}")]
-    Other(String),
+impl From<r2d2::Error> for AppError {
+    fn from(e: r2d2::Error) -> AppError {
+        AppError::Pool(e)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> AppError {
+        AppError::Io(e)
+    }
+}
+
+impl From<zip::result::ZipError> for AppError {
+    fn from(e: zip::result::ZipError) -> AppError {
+        AppError::Zip(e)
+    }
 }
 
 impl Serialize for AppError {
@@ -32,6 +193,50 @@ impl Serialize for AppError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let message = self.message();
+        let mut s = serializer.serialize_struct("AppError", 5)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("field", &message.field)?;
+        s.serialize_field("message_key", message.key)?;
+        s.serialize_field("params", &message.params)?;
+        s.serialize_field("message", &self.to_string())?;
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_structured_error() {
+        let error = AppError::Validation(
+            Message::new("money.scale")
+                .with_field("employee_basic")
+                .with_param("value", "1.001"),
+        );
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "validation");
+        assert_eq!(json["field"], "employee_basic");
+        assert_eq!(json["message_key"], "money.scale");
+        assert_eq!(json["params"]["value"], "1.001");
+        assert_eq!(
+            json["message"],
+            "Błąd walidacji: Składka podstawowa pracownika: kwota może mieć maksymalnie 2 miejsca po przecinku"
+        );
+    }
+
+    #[test]
+    fn test_renders_in_english() {
+        let error =
+            AppError::Validation(Message::new("money.negative").with_field("employer_basic"));
+        assert_eq!(
+            error.render(Locale::En),
+            "Validation error: Employer basic contribution: amount cannot be negative"
+        );
+        assert_eq!(
+            AppError::Locked.render(Locale::En),
+            "The database is locked"
+        );
     }
 }
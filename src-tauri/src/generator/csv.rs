@@ -1,3 +1,4 @@
+use crate::domain::period::Period;
 use crate::models::contribution::ContributionWithMember;
 
 /// Build CSV matching exact PZU format:
@@ -7,7 +8,7 @@ use crate::models::contribution::ContributionWithMember;
 /// - Data rows all-quoted
 /// - Comma decimals ("94,38")
 /// - Month unpadded
-pub fn build(contributions: &[ContributionWithMember], period_year: i32, period_month: i32) -> String {
+pub fn build(contributions: &[ContributionWithMember], period: Period) -> String {
     let mut csv = String::new();
 
     // Header (unquoted)
@@ -24,13 +25,13 @@ pub fn build(contributions: &[ContributionWithMember], period_year: i32, period_
             "", // UCZESTNIK_IDENTYFIKATOR_INFORMATYCZNY - empty
             c.last_name.to_uppercase(),
             c.first_name.to_uppercase(),
-            format_comma_decimal(&c.employee_basic),
-            format_comma_decimal(&c.employee_additional),
-            format_comma_decimal(&c.employer_basic),
-            format_comma_decimal(&c.employer_additional),
+            c.employee_basic.to_comma_string(),
+            c.employee_additional.to_comma_string(),
+            c.employer_basic.to_comma_string(),
+            c.employer_additional.to_comma_string(),
             c.reduced_basic_flag,
-            period_month, // unpadded
-            period_year,
+            period.month, // unpadded
+            period.year,
             "", // PZIF_RACH_PPK - empty
             "", // ID_EPPK_UCZESTNIKA - empty
         ));
@@ -39,14 +40,3 @@ pub fn build(contributions: &[ContributionWithMember], period_year: i32, period_
 
     csv
 }
-
-/// Convert dot decimal to comma decimal ("94.38" -> "94,38")
-fn format_comma_decimal(value: &str) -> String {
-    use rust_decimal::Decimal;
-    use std::str::FromStr;
-
-    match Decimal::from_str(value) {
-        Ok(d) => format!("{:.2}", d).replace('.', ","),
-        Err(_) => value.replace('.', ","),
-    }
-}
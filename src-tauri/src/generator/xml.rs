@@ -1,3 +1,5 @@
+use crate::domain::codes::EmployerIdType;
+use crate::domain::period::Period;
 use crate::models::contribution::ContributionWithMember;
 use crate::models::organization::Organization;
 
@@ -7,45 +9,100 @@ use crate::models::organization::Organization;
 /// - Dot decimals ("94.38")
 /// - Empty elements as `<TAG></TAG>`
 /// - Period as "YYYY-MM"
+/// - Free text escaped, as contacts and names may hold `&` or `<`
+/// - `<PRACODAWCA>` identified by NIP, REGON or PESEL, as set on the organization
+///
+/// `contact` is the rendered `<KONTAKT>` value.
 pub fn build(
     org: &Organization,
+    contact: &str,
     contributions: &[ContributionWithMember],
-    period_year: i32,
-    period_month: i32,
+    period: Period,
 ) -> String {
     let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    let period = format!("{:04}-{:02}", period_year, period_month);
 
     let mut xml = String::new();
     xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\r\n");
     xml.push_str("<PPK>\r\n");
-    xml.push_str(&format!("    <WERSJA>GRUPA_PPK 1.00</WERSJA>\r\n"));
+    xml.push_str("    <WERSJA>GRUPA_PPK 1.00</WERSJA>\r\n");
     xml.push_str(&format!("    <GENERACJA>{}</GENERACJA>\r\n", now));
     xml.push_str("    <PRACODAWCA>\r\n");
-    xml.push_str(&format!("        <NIP>{}</NIP>\r\n", org.nip));
-    xml.push_str(&format!("        <REGON>{}</REGON>\r\n", org.regon));
-    xml.push_str(&format!("        <KONTAKT>{}</KONTAKT>\r\n", org.contact_person));
+    // The identifier of the employer's type is always written, the others
+    // only when known. A PESEL is never written unless it is the identifier.
+    if org.id_type == EmployerIdType::Nip || !org.nip.as_str().is_empty() {
+        xml.push_str(&format!("        <NIP>{}</NIP>\r\n", org.nip));
+    }
+    if org.id_type == EmployerIdType::Regon || !org.regon.as_str().is_empty() {
+        xml.push_str(&format!("        <REGON>{}</REGON>\r\n", org.regon));
+    }
+    if org.id_type == EmployerIdType::Pesel {
+        let pesel = org.pesel.as_ref().map(|p| p.as_str()).unwrap_or_default();
+        xml.push_str(&format!("        <PESEL>{}</PESEL>\r\n", pesel));
+    }
+    xml.push_str(&format!(
+        "        <KONTAKT>{}</KONTAKT>\r\n",
+        escape(contact)
+    ));
     xml.push_str("    </PRACODAWCA>\r\n");
     xml.push_str("    <DANE_UCZESTNIKA>\r\n");
 
     for c in contributions {
         xml.push_str("        <UCZESTNIK>\r\n");
         xml.push_str(&format!("            <NR_PESEL>{}</NR_PESEL>\r\n", c.pesel));
-        xml.push_str(&format!("            <DOK_TOZ_TYP>{}</DOK_TOZ_TYP>\r\n", c.doc_type));
-        xml.push_str(&format!("            <DOK_TOZ_SYM>{}</DOK_TOZ_SYM>\r\n", c.doc_number));
-        xml.push_str(&format!("            <NAZWISKO>{}</NAZWISKO>\r\n", c.last_name.to_uppercase()));
-        xml.push_str(&format!("            <IMIE>{}</IMIE>\r\n", c.first_name.to_uppercase()));
+        xml.push_str(&format!(
+            "            <DOK_TOZ_TYP>{}</DOK_TOZ_TYP>\r\n",
+            escape(&c.doc_type)
+        ));
+        xml.push_str(&format!(
+            "            <DOK_TOZ_SYM>{}</DOK_TOZ_SYM>\r\n",
+            escape(&c.doc_number)
+        ));
+        xml.push_str(&format!(
+            "            <NAZWISKO>{}</NAZWISKO>\r\n",
+            escape(&c.last_name.to_uppercase())
+        ));
+        xml.push_str(&format!(
+            "            <IMIE>{}</IMIE>\r\n",
+            escape(&c.first_name.to_uppercase())
+        ));
         xml.push_str(&format!("            <PLEC>{}</PLEC>\r\n", c.gender));
-        xml.push_str(&format!("            <IMIE_2>{}</IMIE_2>\r\n", c.second_name.to_uppercase()));
-        xml.push_str(&format!("            <OBYW>{}</OBYW>\r\n", c.citizenship));
-        xml.push_str(&format!("            <DATA_UR>{}</DATA_UR>\r\n", c.date_of_birth));
+        xml.push_str(&format!(
+            "            <IMIE_2>{}</IMIE_2>\r\n",
+            escape(&c.second_name.to_uppercase())
+        ));
+        xml.push_str(&format!(
+            "            <OBYW>{}</OBYW>\r\n",
+            escape(&c.citizenship)
+        ));
+        xml.push_str(&format!(
+            "            <DATA_UR>{}</DATA_UR>\r\n",
+            c.date_of_birth
+        ));
         xml.push_str("            <SKLADKA>\r\n");
-        xml.push_str(&format!("                <UCZ_WAR_POD>{}</UCZ_WAR_POD>\r\n", format_dot_decimal(&c.employee_basic)));
-        xml.push_str(&format!("                <UCZ_WAR_DOD>{}</UCZ_WAR_DOD>\r\n", format_dot_decimal(&c.employee_additional)));
-        xml.push_str(&format!("                <FIR_WAR_POD>{}</FIR_WAR_POD>\r\n", format_dot_decimal(&c.employer_basic)));
-        xml.push_str(&format!("                <FIR_WAR_DOD>{}</FIR_WAR_DOD>\r\n", format_dot_decimal(&c.employer_additional)));
-        xml.push_str(&format!("                <UCZ_OBNIZ_SKL_POD>{}</UCZ_OBNIZ_SKL_POD>\r\n", c.reduced_basic_flag));
-        xml.push_str(&format!("                <SKL_ZA_OKRES>{}</SKL_ZA_OKRES>\r\n", period));
+        xml.push_str(&format!(
+            "                <UCZ_WAR_POD>{}</UCZ_WAR_POD>\r\n",
+            c.employee_basic
+        ));
+        xml.push_str(&format!(
+            "                <UCZ_WAR_DOD>{}</UCZ_WAR_DOD>\r\n",
+            c.employee_additional
+        ));
+        xml.push_str(&format!(
+            "                <FIR_WAR_POD>{}</FIR_WAR_POD>\r\n",
+            c.employer_basic
+        ));
+        xml.push_str(&format!(
+            "                <FIR_WAR_DOD>{}</FIR_WAR_DOD>\r\n",
+            c.employer_additional
+        ));
+        xml.push_str(&format!(
+            "                <UCZ_OBNIZ_SKL_POD>{}</UCZ_OBNIZ_SKL_POD>\r\n",
+            c.reduced_basic_flag
+        ));
+        xml.push_str(&format!(
+            "                <SKL_ZA_OKRES>{}</SKL_ZA_OKRES>\r\n",
+            period
+        ));
         xml.push_str("            </SKLADKA>\r\n");
         xml.push_str("        </UCZESTNIK>\r\n");
     }
@@ -56,13 +113,12 @@ pub fn build(
     xml
 }
 
-/// Ensure decimal uses dot and has 2 decimal places
-fn format_dot_decimal(value: &str) -> String {
-    use rust_decimal::Decimal;
-    use std::str::FromStr;
-
-    match Decimal::from_str(value) {
-        Ok(d) => format!("{:.2}", d),
-        Err(_) => value.to_string(),
-    }
+/// Escape text for use as element content or an attribute value, in XML or
+/// HTML.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
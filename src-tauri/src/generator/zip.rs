@@ -1,8 +1,9 @@
 use std::io::Write;
 use zip::write::SimpleFileOptions;
-use zip::ZipWriter;
+use zip::{AesMode, ZipWriter};
 
-use crate::error::AppError;
+use crate::error::{AppError, Message};
+use crate::secret::Password;
 
 pub struct GeneratedFiles {
     pub zip_bytes: Vec<u8>,
@@ -11,6 +12,28 @@ pub struct GeneratedFiles {
 }
 
 pub fn build(xml_content: &str, csv_content: &str) -> Result<GeneratedFiles, AppError> {
+    build_archive(xml_content, csv_content, None)
+}
+
+/// Same as `build`, with both entries encrypted using AES-256.
+pub fn build_encrypted(
+    xml_content: &str,
+    csv_content: &str,
+    password: &Password,
+) -> Result<GeneratedFiles, AppError> {
+    if password.is_empty() {
+        return Err(AppError::Validation(
+            Message::new("zip.password_empty").with_field("password"),
+        ));
+    }
+    build_archive(xml_content, csv_content, Some(password))
+}
+
+fn build_archive(
+    xml_content: &str,
+    csv_content: &str,
+    password: Option<&Password>,
+) -> Result<GeneratedFiles, AppError> {
     let now = chrono::Local::now();
     let timestamp = now.format("%Y%m%d_%H%M%S").to_string();
 
@@ -20,8 +43,12 @@ pub fn build(xml_content: &str, csv_content: &str) -> Result<GeneratedFiles, App
     let mut buf = Vec::new();
     {
         let mut zip = ZipWriter::new(std::io::Cursor::new(&mut buf));
-        let options = SimpleFileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let options = match password {
+            Some(password) => options.with_aes_encryption(AesMode::Aes256, password.expose()),
+            None => options,
+        };
 
         zip.start_file(&xml_filename, options)?;
         zip.write_all(xml_content.as_bytes())?;
@@ -0,0 +1,123 @@
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Error kinds; `{message}` is the nested message
+    ("error.database", "Database error: {details}"),
+    ("error.pool", "Connection pool error: {details}"),
+    ("error.locked", "The database is locked"),
+    ("error.newer_schema", "The database comes from a newer version of the program (migrations: {versions})"),
+    ("error.validation", "Validation error: {message}"),
+    ("error.not_found", "Not found: {message}"),
+    ("error.generation", "Generation error: {message}"),
+    ("error.io", "I/O error: {details}"),
+    ("error.zip", "ZIP error: {details}"),
+    // Field labels, substituted for `{field}`
+    ("field.amount", "Amount"),
+    ("field.employee_basic", "Employee basic contribution"),
+    ("field.employee_additional", "Employee additional contribution"),
+    ("field.employer_basic", "Employer basic contribution"),
+    ("field.employer_additional", "Employer additional contribution"),
+    ("field.reduced_basic_flag", "Reduced basic contribution"),
+    ("field.gender", "Gender"),
+    ("field.status", "Status"),
+    ("field.source", "Source"),
+    ("field.percent", "Percentage change"),
+    ("field.employee_additional_rate", "Employee additional rate"),
+    ("field.employer_additional_rate", "Employer additional rate"),
+    // Validation modules
+    ("pesel.length", "PESEL must have 11 digits"),
+    ("pesel.digits", "PESEL may contain digits only"),
+    ("pesel.checksum", "Invalid PESEL checksum"),
+    ("pesel.month", "Invalid month in PESEL"),
+    ("nip.length", "NIP must have 10 digits"),
+    ("nip.checksum", "Invalid NIP checksum"),
+    ("regon.length", "REGON must have 9 or 14 digits"),
+    ("regon.checksum", "Invalid REGON checksum"),
+    ("krs.length", "KRS number must have 10 digits"),
+    ("postal_code.format", "Postal code must have the form 00-000"),
+    ("bank_account.length", "Account number must have 26 digits (NRB) or be a valid IBAN"),
+    ("bank_account.checksum", "Invalid account number checksum"),
+    ("phone.format", "Phone number must have 9 digits or start with + and a country code"),
+    ("email.format", "Invalid e-mail address"),
+    ("money.invalid", "{field}: invalid amount '{value}'"),
+    ("money.negative", "{field}: amount cannot be negative"),
+    ("money.scale", "{field}: amount can have at most 2 decimal places"),
+    ("money.too_large", "{field}: amount is too large"),
+    ("code.invalid", "{field}: invalid value '{value}'"),
+    ("period.month", "Invalid month: {month}"),
+    ("period.year", "Invalid year: {year}"),
+    // Organizations and members
+    ("organization.name_required", "Organization name is required"),
+    ("organization.agreement_date", "Agreement date must be a valid date (YYYY-MM-DD)"),
+    ("organization.identifier_required", "{identifier} is required for the chosen employer identification"),
+    ("organization.not_found", "Organization not found"),
+    ("contact.name_required", "Contact person's name is required"),
+    ("contact.not_found", "Contact person not found"),
+    ("member.first_name_required", "First name is required"),
+    ("member.last_name_required", "Last name is required"),
+    ("member.not_found", "Participant not found"),
+    ("person.not_found", "Person not found"),
+    ("member.age_range", "Invalid age range"),
+    ("member.transfer_inactive", "Only an active participant can be transferred"),
+    ("member.transfer_same_organization", "The participant already belongs to this organization"),
+    ("member.transfer_exists", "The participant is already active in the target organization"),
+    ("member.details_effective_from", "The change of details must apply from a period later than {period}"),
+    ("member.pesel_anonymized", "The PESEL of an anonymized participant cannot be changed"),
+    ("member.pesel_unchanged", "The new PESEL is the same as the current one"),
+    ("member.pesel_exists", "A participant with this PESEL already exists in this organization"),
+    ("member.archived_exists", "A participant with this PESEL is in the trash; restore them instead of adding them again"),
+    ("member.pesel_effective_from", "The change must apply from a period later than {period}"),
+    ("merge.same_member", "A participant cannot be merged with itself"),
+    ("merge.other_organization", "Only participants of the same organization can be merged"),
+    ("merge.pesel_history", "Periods of one participant fall before the PESEL correction from {period} of the other, so they cannot be merged"),
+    ("merge.details_history", "Periods of one participant fall before the change of details from {period} of the other, so they cannot be merged"),
+    // Generations
+    ("generation.not_found", "Generation not found"),
+    ("generation.empty", "No contributions to generate for the selected period"),
+    ("generation.password_required", "A ZIP password is required to rebuild the file"),
+    ("snapshot.invalid", "Cannot parse snapshot: {details}"),
+    ("snapshot.organization_invalid", "Cannot parse organization: {details}"),
+    ("snapshot.contributions_invalid", "Cannot parse contributions: {details}"),
+    ("zip.password_empty", "ZIP password cannot be empty"),
+    ("batch.no_output_dir", "No output directory configured"),
+    ("batch.prefilled", "Prefilled {count} contributions automatically"),
+    ("batch.member_inactive", "{member}: member is not active"),
+    ("batch.all_zero", "{member}: all contributions are zero"),
+    ("prefill.same_period", "The source period must differ from the one being filled"),
+    ("prefill.percent", "{field}: value must be between -100 and {max}%"),
+    ("prefill.rate", "{field}: rate must be between 0 and {max}%"),
+    ("journal.conflict", "Cannot undo or redo the operation, the data has changed since"),
+    ("journal.invalid", "Cannot write the operation journal: {details}"),
+    // Settings
+    ("settings.archive_dir_required", "Archive directory is required"),
+    ("settings.archive_dir_missing", "Archive directory is not configured"),
+    ("settings.template_path", "File name template cannot contain a path"),
+    ("settings.template_extension", "File name template must end with .zip"),
+    ("settings.template_unclosed", "Unclosed placeholder in template"),
+    ("settings.template_placeholder", "Unknown placeholder in template: {placeholder}"),
+    ("settings.template_identifier_missing", "The employer has no number for the {placeholder} placeholder; use {id}"),
+    ("settings.retention_years_min", "Data retention period must be at least 1 year"),
+    // Retention and subject access
+    ("retention.no_policy", "The organization has no data retention period set"),
+    ("retention.invalid_period", "Invalid data retention period"),
+    ("audit.not_found", "Audit log entry not found"),
+    ("subject.not_found", "No data for the given PESEL"),
+    ("subject.report_invalid", "Cannot serialize report: {details}"),
+    ("trash.not_archived", "Only data moved to the trash can be deleted permanently"),
+    ("trash.purge_blocked", "The data appears in saved generations; it can be deleted only after the retention period"),
+    // Database and backups
+    ("database.open_failed", "Cannot open database: {details}"),
+    ("database.wrong_password", "Wrong database password"),
+    ("database.password_too_short", "Database password must have at least {min} characters"),
+    ("database.already_encrypted", "The database is already encrypted"),
+    ("database.not_encrypted", "The database is not encrypted"),
+    ("migrations.unknown_version", "Unknown schema version: {version}"),
+    ("backup.password_empty", "Backup password cannot be empty"),
+    ("backup.password_required", "The backup is encrypted, enter its password"),
+    ("backup.wrong_password", "Wrong backup password"),
+    ("backup.not_backup", "The file is not a PPK database backup"),
+    (
+        "backup.unreadable",
+        "Cannot read the backup. It was made with a different database password or is not a database file",
+    ),
+    ("backup.corrupted", "The backup is corrupted: {details}"),
+    ("backup.newer_schema", "The backup comes from a newer version of the program (migrations: {versions})"),
+];
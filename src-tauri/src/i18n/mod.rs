@@ -0,0 +1,115 @@
+//! Translation catalogue for messages shown to the user. Messages are looked
+//! up by key and `{name}` placeholders are replaced with parameters.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+mod en;
+mod pl;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Pl,
+    En,
+}
+
+impl Locale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::Pl => "pl",
+            Locale::En => "en",
+        }
+    }
+
+    /// Unknown codes fall back to Polish.
+    pub fn from_code(code: &str) -> Locale {
+        match code {
+            "en" => Locale::En,
+            _ => Locale::Pl,
+        }
+    }
+
+    fn messages(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::Pl => pl::MESSAGES,
+            Locale::En => en::MESSAGES,
+        }
+    }
+}
+
+/// Language errors are rendered in, from the `language` app setting.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::En,
+        _ => Locale::Pl,
+    }
+}
+
+pub fn set_locale(locale: Locale) {
+    CURRENT.store(locale as u8, Ordering::Relaxed);
+}
+
+/// The template for `key`, falling back to Polish and then to the key itself.
+pub fn template(locale: Locale, key: &str) -> &str {
+    let find = |messages: &'static [(&'static str, &'static str)]| {
+        messages.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    };
+    find(locale.messages())
+        .or_else(|| find(Locale::Pl.messages()))
+        .unwrap_or(key)
+}
+
+pub fn translate(locale: Locale, key: &str, params: &BTreeMap<&str, String>) -> String {
+    let mut text = template(locale, key).to_string();
+    for (name, value) in params {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = template
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_catalogues_have_same_keys_and_placeholders() {
+        assert_eq!(pl::MESSAGES.len(), en::MESSAGES.len());
+        for (key, pl_text) in pl::MESSAGES {
+            let en_text = en::MESSAGES
+                .iter()
+                .find(|(k, _)| k == key)
+                .unwrap_or_else(|| panic!("missing English message {}", key))
+                .1;
+            assert_eq!(placeholders(pl_text), placeholders(en_text), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_translate_substitutes_params() {
+        let params = BTreeMap::from([("month", "13".to_string())]);
+        assert_eq!(
+            translate(Locale::Pl, "period.month", &params),
+            "Nieprawidłowy miesiąc: 13"
+        );
+        assert_eq!(
+            translate(Locale::En, "period.month", &params),
+            "Invalid month: 13"
+        );
+        assert_eq!(translate(Locale::En, "no.such.key", &params), "no.such.key");
+    }
+}
@@ -0,0 +1,123 @@
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Error kinds; `{message}` is the nested message
+    ("error.database", "Błąd bazy danych: {details}"),
+    ("error.pool", "Błąd puli połączeń: {details}"),
+    ("error.locked", "Baza danych jest zablokowana"),
+    ("error.newer_schema", "Baza danych pochodzi z nowszej wersji programu (migracje: {versions})"),
+    ("error.validation", "Błąd walidacji: {message}"),
+    ("error.not_found", "Nie znaleziono: {message}"),
+    ("error.generation", "Błąd generowania: {message}"),
+    ("error.io", "Błąd IO: {details}"),
+    ("error.zip", "Błąd ZIP: {details}"),
+    // Field labels, substituted for `{field}`
+    ("field.amount", "Kwota"),
+    ("field.employee_basic", "Składka podstawowa pracownika"),
+    ("field.employee_additional", "Składka dodatkowa pracownika"),
+    ("field.employer_basic", "Składka podstawowa pracodawcy"),
+    ("field.employer_additional", "Składka dodatkowa pracodawcy"),
+    ("field.reduced_basic_flag", "Obniżenie składki podstawowej"),
+    ("field.gender", "Płeć"),
+    ("field.status", "Status"),
+    ("field.source", "Źródło"),
+    ("field.percent", "Zmiana procentowa"),
+    ("field.employee_additional_rate", "Stawka dodatkowa pracownika"),
+    ("field.employer_additional_rate", "Stawka dodatkowa pracodawcy"),
+    // Validation modules
+    ("pesel.length", "PESEL musi mieć 11 cyfr"),
+    ("pesel.digits", "PESEL może zawierać tylko cyfry"),
+    ("pesel.checksum", "Nieprawidłowa suma kontrolna PESEL"),
+    ("pesel.month", "Nieprawidłowy miesiąc w PESEL"),
+    ("nip.length", "NIP musi mieć 10 cyfr"),
+    ("nip.checksum", "Nieprawidłowa suma kontrolna NIP"),
+    ("regon.length", "REGON musi mieć 9 lub 14 cyfr"),
+    ("regon.checksum", "Nieprawidłowa suma kontrolna REGON"),
+    ("krs.length", "Numer KRS musi mieć 10 cyfr"),
+    ("postal_code.format", "Kod pocztowy musi mieć postać 00-000"),
+    ("bank_account.length", "Numer rachunku musi mieć 26 cyfr (NRB) lub być poprawnym numerem IBAN"),
+    ("bank_account.checksum", "Nieprawidłowa suma kontrolna numeru rachunku"),
+    ("phone.format", "Numer telefonu musi mieć 9 cyfr lub zaczynać się od + i numeru kierunkowego kraju"),
+    ("email.format", "Nieprawidłowy adres e-mail"),
+    ("money.invalid", "{field}: nieprawidłowa kwota '{value}'"),
+    ("money.negative", "{field}: kwota nie może być ujemna"),
+    ("money.scale", "{field}: kwota może mieć maksymalnie 2 miejsca po przecinku"),
+    ("money.too_large", "{field}: kwota jest zbyt duża"),
+    ("code.invalid", "{field}: nieprawidłowa wartość '{value}'"),
+    ("period.month", "Nieprawidłowy miesiąc: {month}"),
+    ("period.year", "Nieprawidłowy rok: {year}"),
+    // Organizations and members
+    ("organization.name_required", "Nazwa organizacji jest wymagana"),
+    ("organization.agreement_date", "Data umowy musi być poprawną datą (RRRR-MM-DD)"),
+    ("organization.identifier_required", "{identifier} jest wymagany przy wybranym sposobie identyfikacji pracodawcy"),
+    ("organization.not_found", "Organizacja nie znaleziona"),
+    ("contact.name_required", "Imię i nazwisko osoby kontaktowej jest wymagane"),
+    ("contact.not_found", "Osoba kontaktowa nie znaleziona"),
+    ("member.first_name_required", "Imię jest wymagane"),
+    ("member.last_name_required", "Nazwisko jest wymagane"),
+    ("member.not_found", "Uczestnik nie znaleziony"),
+    ("person.not_found", "Nie znaleziono osoby"),
+    ("member.age_range", "Nieprawidłowy przedział wieku"),
+    ("member.transfer_inactive", "Przenieść można tylko aktywnego uczestnika"),
+    ("member.transfer_same_organization", "Uczestnik należy już do tej organizacji"),
+    ("member.transfer_exists", "Uczestnik jest już aktywny w organizacji docelowej"),
+    ("member.details_effective_from", "Zmiana danych musi obowiązywać od okresu późniejszego niż {period}"),
+    ("member.pesel_anonymized", "Nie można zmienić numeru PESEL zanonimizowanego uczestnika"),
+    ("member.pesel_unchanged", "Nowy numer PESEL jest taki sam jak obecny"),
+    ("member.pesel_exists", "Uczestnik z tym numerem PESEL już istnieje w tej organizacji"),
+    ("member.archived_exists", "Uczestnik z tym numerem PESEL jest w koszu; przywróć go zamiast dodawać ponownie"),
+    ("member.pesel_effective_from", "Zmiana musi obowiązywać od okresu późniejszego niż {period}"),
+    ("merge.same_member", "Nie można scalić uczestnika z nim samym"),
+    ("merge.other_organization", "Scalać można tylko uczestników tej samej organizacji"),
+    ("merge.pesel_history", "Okresy jednego uczestnika przypadają przed korektą numeru PESEL od {period} drugiego, więc nie można ich scalić"),
+    ("merge.details_history", "Okresy jednego uczestnika przypadają przed zmianą danych od {period} drugiego, więc nie można ich scalić"),
+    // Generations
+    ("generation.not_found", "Generacja nie znaleziona"),
+    ("generation.empty", "Brak składek do wygenerowania dla wybranego okresu"),
+    ("generation.password_required", "Hasło ZIP jest wymagane do odtworzenia pliku"),
+    ("snapshot.invalid", "Błąd parsowania snapshot: {details}"),
+    ("snapshot.organization_invalid", "Błąd parsowania organizacji: {details}"),
+    ("snapshot.contributions_invalid", "Błąd parsowania składek: {details}"),
+    ("zip.password_empty", "Hasło ZIP nie może być puste"),
+    ("batch.no_output_dir", "Brak skonfigurowanego katalogu wyjściowego"),
+    ("batch.prefilled", "Uzupełniono automatycznie {count} składek"),
+    ("batch.member_inactive", "{member}: uczestnik nie jest aktywny"),
+    ("batch.all_zero", "{member}: wszystkie składki są zerowe"),
+    ("prefill.same_period", "Okres źródłowy musi być inny niż uzupełniany"),
+    ("prefill.percent", "{field}: wartość musi wynosić od -100 do {max}%"),
+    ("prefill.rate", "{field}: stawka musi wynosić od 0 do {max}%"),
+    ("journal.conflict", "Nie można cofnąć ani ponowić operacji, dane zmieniły się w międzyczasie"),
+    ("journal.invalid", "Błąd zapisu dziennika operacji: {details}"),
+    // Settings
+    ("settings.archive_dir_required", "Katalog archiwum jest wymagany"),
+    ("settings.archive_dir_missing", "Nie skonfigurowano katalogu archiwum"),
+    ("settings.template_path", "Szablon nazwy pliku nie może zawierać ścieżki"),
+    ("settings.template_extension", "Szablon nazwy pliku musi kończyć się na .zip"),
+    ("settings.template_unclosed", "Niezamknięty znacznik w szablonie"),
+    ("settings.template_placeholder", "Nieznany znacznik w szablonie: {placeholder}"),
+    ("settings.template_identifier_missing", "Pracodawca nie ma numeru dla znacznika {placeholder}; użyj {id}"),
+    ("settings.retention_years_min", "Okres przechowywania danych musi wynosić co najmniej 1 rok"),
+    // Retention and subject access
+    ("retention.no_policy", "Organizacja nie ma ustawionego okresu przechowywania danych"),
+    ("retention.invalid_period", "Nieprawidłowy okres przechowywania danych"),
+    ("audit.not_found", "Wpis dziennika nie znaleziony"),
+    ("subject.not_found", "Brak danych dla podanego numeru PESEL"),
+    ("subject.report_invalid", "Błąd serializacji raportu: {details}"),
+    ("trash.not_archived", "Trwale usunąć można tylko dane przeniesione do kosza"),
+    ("trash.purge_blocked", "Dane występują w zapisanych generacjach; można je usunąć dopiero po upływie okresu przechowywania"),
+    // Database and backups
+    ("database.open_failed", "Nie można otworzyć bazy danych: {details}"),
+    ("database.wrong_password", "Nieprawidłowe hasło do bazy danych"),
+    ("database.password_too_short", "Hasło do bazy danych musi mieć co najmniej {min} znaków"),
+    ("database.already_encrypted", "Baza danych jest już zaszyfrowana"),
+    ("database.not_encrypted", "Baza danych nie jest zaszyfrowana"),
+    ("migrations.unknown_version", "Nieznana wersja schematu: {version}"),
+    ("backup.password_empty", "Hasło kopii zapasowej nie może być puste"),
+    ("backup.password_required", "Kopia zapasowa jest zaszyfrowana, podaj hasło"),
+    ("backup.wrong_password", "Nieprawidłowe hasło kopii zapasowej"),
+    ("backup.not_backup", "Plik nie jest kopią zapasową bazy PPK"),
+    (
+        "backup.unreadable",
+        "Nie można odczytać kopii zapasowej. Została zapisana z innym hasłem bazy danych lub nie jest plikiem bazy",
+    ),
+    ("backup.corrupted", "Kopia zapasowa jest uszkodzona: {details}"),
+    ("backup.newer_schema", "Kopia zapasowa pochodzi z nowszej wersji programu (migracje: {versions})"),
+];
@@ -1,11 +1,16 @@
 mod commands;
-mod db;
+pub mod db;
+pub mod domain;
 pub mod error;
 pub mod generator;
+pub mod i18n;
 pub mod models;
+pub mod secret;
+pub mod services;
 mod state;
 pub mod validation;
 
+use error::AppError;
 use state::AppState;
 use tauri::Manager;
 
@@ -32,10 +37,32 @@ pub fn run() {
 
             log::info!("Database path: {:?}", db_path);
 
-            let pool = db::pool::create_pool(&db_path)
-                .expect("Failed to create database pool");
+            let db = db::database::Database::open(&db_path).expect("Failed to open database");
 
-            app.manage(AppState { db: pool });
+            // An encrypted database waits for the master password; it has been
+            // opened in plain form before, so its defaults are already in place
+            if let Ok(pool) = db.pool() {
+                services::settings::init_defaults(&pool, &app_dir.join("archive"))
+                    .expect("Failed to initialize settings");
+                if let Err(e) = services::settings::load_language(&pool) {
+                    log::warn!("Failed to load language: {}", e);
+                }
+                if let Err(e) = db.auto_backup(db::backup::BackupKind::Startup) {
+                    log::warn!("Startup backup failed: {}", e);
+                }
+            }
+
+            app.manage(AppState { db });
+
+            // Daily backup, checked hourly for as long as the app runs
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                match handle.state::<AppState>().db.daily_backup() {
+                    Ok(_) | Err(AppError::Locked) => {}
+                    Err(e) => log::warn!("Daily backup failed: {}", e),
+                }
+                std::thread::sleep(std::time::Duration::from_secs(60 * 60));
+            });
 
             Ok(())
         })
@@ -45,25 +72,77 @@ pub fn run() {
             commands::organizations::get_organization,
             commands::organizations::create_organization,
             commands::organizations::update_organization,
-            commands::organizations::delete_organization,
+            commands::organizations::archive_organization,
+            commands::organizations::restore_organization,
+            // Contact persons
+            commands::contacts::list_contacts,
+            commands::contacts::create_contact,
+            commands::contacts::update_contact,
+            commands::contacts::set_default_contact,
+            commands::contacts::delete_contact,
             // Members
             commands::members::list_members,
+            commands::members::query_members,
             commands::members::get_member,
             commands::members::create_member,
             commands::members::update_member,
-            commands::members::delete_member,
+            commands::members::list_details_history,
+            commands::members::change_member_pesel,
+            commands::members::list_pesel_history,
+            commands::members::transfer_member,
+            commands::members::list_member_transfers,
+            commands::members::preview_merge,
+            commands::members::merge_members,
+            commands::members::archive_member,
+            commands::members::restore_member,
             commands::members::validate_pesel,
             // Contributions
             commands::contributions::list_contributions,
             commands::contributions::upsert_contribution,
+            commands::contributions::upsert_contributions,
+            commands::contributions::preview_prefill,
             commands::contributions::prefill_contributions,
             commands::contributions::get_available_periods,
             // Generations
             commands::generations::generate_ppk,
+            commands::generations::generate_batch,
             commands::generations::list_generations,
             commands::generations::get_generation,
             commands::generations::export_generation,
-            commands::generations::save_zip_file,
+            commands::generations::verify_archive,
+            // Undo/redo
+            commands::journal::list_journal,
+            commands::journal::undo,
+            commands::journal::redo,
+            // Settings
+            commands::settings::get_app_settings,
+            commands::settings::update_app_settings,
+            commands::settings::get_organization_settings,
+            commands::settings::update_organization_settings,
+            // Retention
+            commands::retention::preview_retention,
+            commands::retention::apply_retention,
+            // Persons
+            commands::persons::list_persons,
+            commands::persons::get_person,
+            commands::persons::get_person_report,
+            // Subject access
+            commands::subject_access::get_subject_data,
+            commands::subject_access::export_subject_data,
+            // Audit
+            commands::audit::list_audit_log,
+            // Trash
+            commands::trash::list_trash,
+            commands::trash::purge_member,
+            commands::trash::purge_organization,
+            // Database
+            commands::database::get_database_status,
+            commands::database::unlock_database,
+            commands::database::encrypt_database,
+            commands::database::change_database_password,
+            commands::database::create_backup,
+            commands::database::list_backups,
+            commands::database::restore_backup,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,65 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+/// A record of an operation on personal data. `details` describes what was
+/// done, never the personal data itself.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub organization_id: Option<i64>,
+    pub action: String,
+    pub details: serde_json::Value,
+    pub created_at: String,
+}
+
+impl AuditEntry {
+    pub fn insert(
+        conn: &Connection,
+        organization_id: Option<i64>,
+        action: &str,
+        details: &serde_json::Value,
+    ) -> Result<AuditEntry, AppError> {
+        conn.execute(
+            "INSERT INTO audit_log (organization_id, action, details_json) VALUES (?1, ?2, ?3)",
+            params![organization_id, action, details.to_string()],
+        )?;
+        Self::get(conn, conn.last_insert_rowid())
+    }
+
+    pub fn get(conn: &Connection, id: i64) -> Result<AuditEntry, AppError> {
+        conn.query_row(
+            "SELECT id, organization_id, action, details_json, created_at FROM audit_log WHERE id = ?1",
+            params![id],
+            row_to_entry,
+        )
+        .map_err(|_| AppError::NotFound("audit.not_found".into()))
+    }
+
+    pub fn list(conn: &Connection, organization_id: i64) -> Result<Vec<AuditEntry>, AppError> {
+        let mut stmt = conn.prepare(
+            "SELECT id, organization_id, action, details_json, created_at FROM audit_log
+             WHERE organization_id = ?1
+             ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map(params![organization_id], row_to_entry)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+    let details: String = row.get(3)?;
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        organization_id: row.get(1)?,
+        action: row.get(2)?,
+        details: serde_json::from_str(&details).unwrap_or(serde_json::Value::Null),
+        created_at: row.get(4)?,
+    })
+}
@@ -0,0 +1,134 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// A person the institution can contact about an employer's files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactPerson {
+    pub id: i64,
+    pub organization_id: i64,
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    /// The contact written into generated files. At most one per organization.
+    pub is_default: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveContactPerson {
+    pub name: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub email: String,
+}
+
+const SELECT_COLS: &str =
+    "id, organization_id, name, phone, email, is_default, created_at, updated_at";
+
+fn row_to_contact(row: &rusqlite::Row) -> rusqlite::Result<ContactPerson> {
+    Ok(ContactPerson {
+        id: row.get(0)?,
+        organization_id: row.get(1)?,
+        name: row.get(2)?,
+        phone: row.get(3)?,
+        email: row.get(4)?,
+        is_default: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+impl ContactPerson {
+    /// An organization's contacts, the default one first.
+    pub fn list(conn: &Connection, organization_id: i64) -> Result<Vec<ContactPerson>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM contact_persons WHERE organization_id = ?1
+             ORDER BY is_default DESC, name, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![organization_id], row_to_contact)?;
+
+        let mut contacts = Vec::new();
+        for row in rows {
+            contacts.push(row?);
+        }
+        Ok(contacts)
+    }
+
+    pub fn get(conn: &Connection, id: i64) -> Result<ContactPerson, AppError> {
+        let sql = format!("SELECT {} FROM contact_persons WHERE id = ?1", SELECT_COLS);
+        conn.query_row(&sql, params![id], row_to_contact)
+            .map_err(|_| AppError::NotFound("contact.not_found".into()))
+    }
+
+    pub fn get_default(
+        conn: &Connection,
+        organization_id: i64,
+    ) -> Result<Option<ContactPerson>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM contact_persons WHERE organization_id = ?1 AND is_default",
+            SELECT_COLS
+        );
+        Ok(conn
+            .query_row(&sql, params![organization_id], row_to_contact)
+            .optional()?)
+    }
+
+    pub fn create(
+        conn: &Connection,
+        organization_id: i64,
+        data: &SaveContactPerson,
+    ) -> Result<ContactPerson, AppError> {
+        conn.execute(
+            "INSERT INTO contact_persons (organization_id, name, phone, email)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![organization_id, data.name, data.phone, data.email],
+        )?;
+        Self::get(conn, conn.last_insert_rowid())
+    }
+
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        data: &SaveContactPerson,
+    ) -> Result<ContactPerson, AppError> {
+        let affected = conn.execute(
+            "UPDATE contact_persons SET name = ?1, phone = ?2, email = ?3,
+             updated_at = datetime('now') WHERE id = ?4",
+            params![data.name, data.phone, data.email, id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("contact.not_found".into()));
+        }
+        Self::get(conn, id)
+    }
+
+    /// Make the contact its organization's default, in place of any other.
+    pub fn set_default(conn: &Connection, id: i64) -> Result<ContactPerson, AppError> {
+        let contact = Self::get(conn, id)?;
+        conn.execute(
+            "UPDATE contact_persons SET is_default = 0
+             WHERE organization_id = ?1 AND is_default AND id != ?2",
+            params![contact.organization_id, id],
+        )?;
+        conn.execute(
+            "UPDATE contact_persons SET is_default = 1, updated_at = datetime('now')
+             WHERE id = ?1 AND NOT is_default",
+            params![id],
+        )?;
+        Self::get(conn, id)
+    }
+
+    pub fn delete(conn: &Connection, id: i64) -> Result<(), AppError> {
+        let affected = conn.execute("DELETE FROM contact_persons WHERE id = ?1", params![id])?;
+        if affected == 0 {
+            return Err(AppError::NotFound("contact.not_found".into()));
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,12 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, params_from_iter, Connection};
 use serde::{Deserialize, Serialize};
 
+use crate::domain::codes::{ContributionSource, Gender, MemberStatus, YesNo};
+use crate::domain::identifiers::Pesel;
+use crate::domain::money::Money;
+use crate::domain::period::Period;
 use crate::error::AppError;
+use crate::models::member::MemberFilter;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContributionWithMember {
@@ -9,26 +14,62 @@ pub struct ContributionWithMember {
     pub member_id: i64,
     pub period_year: i32,
     pub period_month: i32,
-    pub employee_basic: String,
-    pub employee_additional: String,
-    pub employer_basic: String,
-    pub employer_additional: String,
-    pub reduced_basic_flag: String,
-    pub source: String,
+    pub employee_basic: Money,
+    pub employee_additional: Money,
+    pub employer_basic: Money,
+    pub employer_additional: Money,
+    pub reduced_basic_flag: YesNo,
+    pub source: ContributionSource,
     pub updated_at: String,
     // Member fields
-    pub pesel: String,
+    pub pesel: Pesel,
     pub first_name: String,
     pub last_name: String,
-    pub gender: String,
+    pub gender: Gender,
     pub date_of_birth: String,
     pub citizenship: String,
     pub second_name: String,
     pub doc_type: String,
     pub doc_number: String,
-    pub member_status: String,
+    pub member_status: MemberStatus,
 }
 
+/// The amounts and flag of one contribution row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContributionAmounts {
+    pub employee_basic: Money,
+    pub employee_additional: Money,
+    pub employer_basic: Money,
+    pub employer_additional: Money,
+    pub reduced_basic_flag: YesNo,
+}
+
+impl Default for ContributionAmounts {
+    fn default() -> ContributionAmounts {
+        ContributionAmounts {
+            employee_basic: Money::ZERO,
+            employee_additional: Money::ZERO,
+            employer_basic: Money::ZERO,
+            employer_additional: Money::ZERO,
+            reduced_basic_flag: YesNo::No,
+        }
+    }
+}
+
+impl From<&ContributionWithMember> for ContributionAmounts {
+    fn from(c: &ContributionWithMember) -> ContributionAmounts {
+        ContributionAmounts {
+            employee_basic: c.employee_basic,
+            employee_additional: c.employee_additional,
+            employer_basic: c.employer_basic,
+            employer_additional: c.employer_additional,
+            reduced_basic_flag: c.reduced_basic_flag,
+        }
+    }
+}
+
+/// A contribution row as entered in the form. Amounts and the flag stay text
+/// here so that a bad value comes back as a validation error naming its field.
 #[derive(Debug, Deserialize)]
 pub struct UpsertContribution {
     pub member_id: i64,
@@ -41,8 +82,97 @@ pub struct UpsertContribution {
     pub reduced_basic_flag: Option<String>,
 }
 
+/// A validated [`UpsertContribution`]; `None` leaves the stored value as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionChange {
+    pub member_id: i64,
+    pub period: Period,
+    pub employee_basic: Option<Money>,
+    pub employee_additional: Option<Money>,
+    pub employer_basic: Option<Money>,
+    pub employer_additional: Option<Money>,
+    pub reduced_basic_flag: Option<YesNo>,
+}
+
+impl UpsertContribution {
+    pub fn validate(&self) -> Result<ContributionChange, AppError> {
+        let amount = |value: &Option<String>, field| {
+            value.as_deref().map(|v| Money::parse(v, field)).transpose()
+        };
+
+        Ok(ContributionChange {
+            member_id: self.member_id,
+            period: Period::new(self.period_year, self.period_month)?,
+            employee_basic: amount(&self.employee_basic, "employee_basic")?,
+            employee_additional: amount(&self.employee_additional, "employee_additional")?,
+            employer_basic: amount(&self.employer_basic, "employer_basic")?,
+            employer_additional: amount(&self.employer_additional, "employer_additional")?,
+            reduced_basic_flag: self
+                .reduced_basic_flag
+                .as_deref()
+                .map(str::parse)
+                .transpose()?,
+        })
+    }
+}
+
 pub struct Contribution;
 
+const SELECT_WITH_MEMBER: &str = "c.id, c.member_id, c.period_year, c.period_month,
+    c.employee_basic, c.employee_additional, c.employer_basic, c.employer_additional,
+    c.reduced_basic_flag, c.source, c.updated_at,
+    COALESCE(h.pesel, m.pesel), COALESCE(d.first_name, m.first_name),
+    COALESCE(d.last_name, m.last_name),
+    COALESCE(h.gender, m.gender), COALESCE(h.date_of_birth, m.date_of_birth),
+    COALESCE(d.citizenship, m.citizenship), COALESCE(d.second_name, m.second_name),
+    COALESCE(d.doc_type, m.doc_type), COALESCE(d.doc_number, m.doc_number), m.status";
+
+/// Contributions with their member and, for periods before a PESEL
+/// correction or a dated change of details, the values that applied then
+/// (aliased `h` and `d`).
+const FROM_WITH_MEMBER: &str = "contributions c
+    JOIN members m ON c.member_id = m.id
+    LEFT JOIN member_pesel_history h ON h.id = (
+        SELECT h2.id FROM member_pesel_history h2
+        WHERE h2.member_id = c.member_id
+          AND (c.period_year, c.period_month) < (h2.replaced_from_year, h2.replaced_from_month)
+        ORDER BY h2.replaced_from_year, h2.replaced_from_month, h2.id
+        LIMIT 1
+    )
+    LEFT JOIN member_details_history d ON d.id = (
+        SELECT d2.id FROM member_details_history d2
+        WHERE d2.member_id = c.member_id
+          AND (c.period_year, c.period_month) < (d2.replaced_from_year, d2.replaced_from_month)
+        ORDER BY d2.replaced_from_year, d2.replaced_from_month, d2.id
+        LIMIT 1
+    )";
+
+fn row_to_contribution(row: &rusqlite::Row) -> rusqlite::Result<ContributionWithMember> {
+    Ok(ContributionWithMember {
+        id: row.get(0)?,
+        member_id: row.get(1)?,
+        period_year: row.get(2)?,
+        period_month: row.get(3)?,
+        employee_basic: row.get(4)?,
+        employee_additional: row.get(5)?,
+        employer_basic: row.get(6)?,
+        employer_additional: row.get(7)?,
+        reduced_basic_flag: row.get(8)?,
+        source: row.get(9)?,
+        updated_at: row.get(10)?,
+        pesel: row.get(11)?,
+        first_name: row.get(12)?,
+        last_name: row.get(13)?,
+        gender: row.get(14)?,
+        date_of_birth: row.get(15)?,
+        citizenship: row.get(16)?,
+        second_name: row.get(17)?,
+        doc_type: row.get(18)?,
+        doc_number: row.get(19)?,
+        member_status: row.get(20)?,
+    })
+}
+
 impl Contribution {
     pub fn list_for_period(
         conn: &Connection,
@@ -50,43 +180,34 @@ impl Contribution {
         year: i32,
         month: i32,
     ) -> Result<Vec<ContributionWithMember>, AppError> {
-        let mut stmt = conn.prepare(
-            "SELECT c.id, c.member_id, c.period_year, c.period_month,
-                    c.employee_basic, c.employee_additional, c.employer_basic, c.employer_additional,
-                    c.reduced_basic_flag, c.source, c.updated_at,
-                    m.pesel, m.first_name, m.last_name, m.gender, m.date_of_birth,
-                    m.citizenship, m.second_name, m.doc_type, m.doc_number, m.status
-             FROM contributions c
-             JOIN members m ON c.member_id = m.id
-             WHERE m.organization_id = ?1 AND c.period_year = ?2 AND c.period_month = ?3
-             ORDER BY m.last_name, m.first_name",
-        )?;
+        let filter = MemberFilter::default();
+        Self::list_for_period_filtered(conn, organization_id, year, month, &filter)
+    }
 
-        let rows = stmt.query_map(params![organization_id, year, month], |row| {
-            Ok(ContributionWithMember {
-                id: row.get(0)?,
-                member_id: row.get(1)?,
-                period_year: row.get(2)?,
-                period_month: row.get(3)?,
-                employee_basic: row.get(4)?,
-                employee_additional: row.get(5)?,
-                employer_basic: row.get(6)?,
-                employer_additional: row.get(7)?,
-                reduced_basic_flag: row.get(8)?,
-                source: row.get(9)?,
-                updated_at: row.get(10)?,
-                pesel: row.get(11)?,
-                first_name: row.get(12)?,
-                last_name: row.get(13)?,
-                gender: row.get(14)?,
-                date_of_birth: row.get(15)?,
-                citizenship: row.get(16)?,
-                second_name: row.get(17)?,
-                doc_type: row.get(18)?,
-                doc_number: row.get(19)?,
-                member_status: row.get(20)?,
-            })
-        })?;
+    /// Contributions of a period, only for members matching `filter`.
+    pub fn list_for_period_filtered(
+        conn: &Connection,
+        organization_id: i64,
+        year: i32,
+        month: i32,
+        filter: &MemberFilter,
+    ) -> Result<Vec<ContributionWithMember>, AppError> {
+        let (clauses, mut values) = filter.conditions()?;
+        let mut sql = format!(
+            "SELECT {} FROM {}
+             WHERE m.organization_id = ? AND m.archived_at IS NULL
+               AND c.period_year = ? AND c.period_month = ?",
+            SELECT_WITH_MEMBER, FROM_WITH_MEMBER
+        );
+        for clause in &clauses {
+            sql.push_str(" AND ");
+            sql.push_str(clause);
+        }
+        sql.push_str(" ORDER BY m.last_name, m.first_name");
+        values.splice(0..0, [organization_id.into(), year.into(), month.into()]);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), row_to_contribution)?;
 
         let mut result = Vec::new();
         for row in rows {
@@ -95,25 +216,34 @@ impl Contribution {
         Ok(result)
     }
 
-    pub fn upsert(conn: &Connection, data: &UpsertContribution) -> Result<(), AppError> {
-        // Validate money amounts
-        for (field, name) in [
-            (&data.employee_basic, "Składka podstawowa pracownika"),
-            (&data.employee_additional, "Składka dodatkowa pracownika"),
-            (&data.employer_basic, "Składka podstawowa pracodawcy"),
-            (&data.employer_additional, "Składka dodatkowa pracodawcy"),
-        ] {
-            if let Some(val) = field {
-                validate_money(val, name)?;
-            }
+    /// Every contribution of one member, oldest period first.
+    pub fn list_for_member(
+        conn: &Connection,
+        member_id: i64,
+    ) -> Result<Vec<ContributionWithMember>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM {}
+             WHERE c.member_id = ?1
+             ORDER BY c.period_year, c.period_month",
+            SELECT_WITH_MEMBER, FROM_WITH_MEMBER
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![member_id], row_to_contribution)?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
         }
+        Ok(result)
+    }
 
+    pub fn upsert(conn: &Connection, data: &ContributionChange) -> Result<(), AppError> {
         conn.execute(
             "INSERT INTO contributions (member_id, period_year, period_month,
                 employee_basic, employee_additional, employer_basic, employer_additional,
                 reduced_basic_flag, source)
              VALUES (?1, ?2, ?3,
-                COALESCE(?4, '0.00'), COALESCE(?5, '0.00'), COALESCE(?6, '0.00'), COALESCE(?7, '0.00'),
+                COALESCE(?4, 0), COALESCE(?5, 0), COALESCE(?6, 0), COALESCE(?7, 0),
                 COALESCE(?8, 'N'), 'manual')
              ON CONFLICT(member_id, period_year, period_month)
              DO UPDATE SET
@@ -126,8 +256,8 @@ impl Contribution {
                 updated_at = datetime('now')",
             params![
                 data.member_id,
-                data.period_year,
-                data.period_month,
+                data.period.year,
+                data.period.month,
                 data.employee_basic,
                 data.employee_additional,
                 data.employer_basic,
@@ -139,67 +269,104 @@ impl Contribution {
         Ok(())
     }
 
-    pub fn prefill(
+    /// Each member's most recent contribution before `period`.
+    pub fn list_latest_before(
         conn: &Connection,
         organization_id: i64,
-        target_year: i32,
-        target_month: i32,
-    ) -> Result<i64, AppError> {
-        // For each active member without a contribution for the target period,
-        // copy from their most recent existing contribution.
-        let copied = conn.execute(
-            "INSERT INTO contributions (member_id, period_year, period_month,
-                employee_basic, employee_additional, employer_basic, employer_additional,
-                reduced_basic_flag, source)
-             SELECT m.id, ?2, ?3,
-                    prev.employee_basic, prev.employee_additional,
-                    prev.employer_basic, prev.employer_additional,
-                    prev.reduced_basic_flag, 'prefilled'
-             FROM members m
-             INNER JOIN (
-                 SELECT c.*, ROW_NUMBER() OVER (
-                     PARTITION BY c.member_id
-                     ORDER BY c.period_year DESC, c.period_month DESC
-                 ) AS rn
-                 FROM contributions c
-                 INNER JOIN members m2 ON c.member_id = m2.id
-                 WHERE m2.organization_id = ?1
-             ) prev ON prev.member_id = m.id AND prev.rn = 1
-             WHERE m.organization_id = ?1
-               AND m.status = 'active'
-               AND NOT EXISTS (
-                   SELECT 1 FROM contributions c2
-                   WHERE c2.member_id = m.id
-                     AND c2.period_year = ?2
-                     AND c2.period_month = ?3
-               )",
-            params![organization_id, target_year, target_month],
+        period: Period,
+    ) -> Result<Vec<ContributionWithMember>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM {}
+             WHERE c.id IN (
+                 SELECT id FROM (
+                     SELECT c2.id, ROW_NUMBER() OVER (
+                         PARTITION BY c2.member_id
+                         ORDER BY c2.period_year DESC, c2.period_month DESC
+                     ) AS rn
+                     FROM contributions c2
+                     JOIN members m2 ON c2.member_id = m2.id
+                     WHERE m2.organization_id = ?1 AND m2.archived_at IS NULL
+                       AND (c2.period_year < ?2 OR (c2.period_year = ?2 AND c2.period_month < ?3))
+                 ) WHERE rn = 1
+             )
+             ORDER BY m.last_name, m.first_name",
+            SELECT_WITH_MEMBER, FROM_WITH_MEMBER
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            params![organization_id, period.year, period.month],
+            row_to_contribution,
         )?;
 
-        // Create zero-value contributions for active members with no history at all
-        let zeroed = conn.execute(
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Write a prefilled row. An existing row is replaced only if it was
+    /// prefilled too, never one entered by hand. Returns whether anything
+    /// was written.
+    pub fn write_prefilled(
+        conn: &Connection,
+        member_id: i64,
+        period: Period,
+        amounts: &ContributionAmounts,
+    ) -> Result<bool, AppError> {
+        let written = conn.execute(
             "INSERT INTO contributions (member_id, period_year, period_month,
                 employee_basic, employee_additional, employer_basic, employer_additional,
                 reduced_basic_flag, source)
-             SELECT m.id, ?2, ?3,
-                    '0.00', '0.00', '0.00', '0.00', 'N', 'prefilled'
-             FROM members m
-             WHERE m.organization_id = ?1
-               AND m.status = 'active'
-               AND NOT EXISTS (
-                   SELECT 1 FROM contributions c
-                   WHERE c.member_id = m.id
-               )",
-            params![organization_id, target_year, target_month],
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'prefilled')
+             ON CONFLICT(member_id, period_year, period_month)
+             DO UPDATE SET
+                employee_basic = excluded.employee_basic,
+                employee_additional = excluded.employee_additional,
+                employer_basic = excluded.employer_basic,
+                employer_additional = excluded.employer_additional,
+                reduced_basic_flag = excluded.reduced_basic_flag,
+                updated_at = datetime('now')
+             WHERE contributions.source = 'prefilled'",
+            params![
+                member_id,
+                period.year,
+                period.month,
+                amounts.employee_basic,
+                amounts.employee_additional,
+                amounts.employer_basic,
+                amounts.employer_additional,
+                amounts.reduced_basic_flag,
+            ],
+        )?;
+        Ok(written > 0)
+    }
+
+    pub fn delete(conn: &Connection, member_id: i64, period: Period) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM contributions WHERE member_id = ?1 AND period_year = ?2 AND period_month = ?3",
+            params![member_id, period.year, period.month],
         )?;
+        Ok(())
+    }
 
-        Ok((copied + zeroed) as i64)
+    /// Move every contribution of `from_member_id` to `to_member_id`, which
+    /// must have none for the same periods. Returns the number moved.
+    pub fn reassign(
+        conn: &Connection,
+        from_member_id: i64,
+        to_member_id: i64,
+    ) -> Result<usize, AppError> {
+        Ok(conn.execute(
+            "UPDATE contributions SET member_id = ?2, updated_at = datetime('now') WHERE member_id = ?1",
+            params![from_member_id, to_member_id],
+        )?)
     }
 
     pub fn get_available_periods(
         conn: &Connection,
         organization_id: i64,
-    ) -> Result<Vec<(i32, i32)>, AppError> {
+    ) -> Result<Vec<Period>, AppError> {
         let mut stmt = conn.prepare(
             "SELECT DISTINCT c.period_year, c.period_month
              FROM contributions c
@@ -209,7 +376,10 @@ impl Contribution {
         )?;
 
         let rows = stmt.query_map(params![organization_id], |row| {
-            Ok((row.get::<_, i32>(0)?, row.get::<_, i32>(1)?))
+            Ok(Period {
+                year: row.get(0)?,
+                month: row.get(1)?,
+            })
         })?;
 
         let mut periods = Vec::new();
@@ -219,28 +389,3 @@ impl Contribution {
         Ok(periods)
     }
 }
-
-fn validate_money(value: &str, field_name: &str) -> Result<(), AppError> {
-    use rust_decimal::Decimal;
-    use std::str::FromStr;
-
-    let dec = Decimal::from_str(value).map_err(|_| {
-        AppError::Validation(format!("{}: nieprawidłowa kwota '{}'", field_name, value))
-    })?;
-
-    if dec < Decimal::ZERO {
-        return Err(AppError::Validation(format!(
-            "{}: kwota nie może być ujemna",
-            field_name
-        )));
-    }
-
-    if dec.scale() > 2 {
-        return Err(AppError::Validation(format!(
-            "{}: kwota może mieć maksymalnie 2 miejsca po przecinku",
-            field_name
-        )));
-    }
-
-    Ok(())
-}
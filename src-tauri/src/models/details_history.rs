@@ -0,0 +1,120 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::period::Period;
+use crate::error::AppError;
+use crate::models::member::Member;
+
+/// Names, citizenship and document a member had before a dated change. They
+/// still apply to the periods before `replaced_from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailsChange {
+    pub id: i64,
+    pub member_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub second_name: String,
+    pub citizenship: String,
+    pub doc_type: String,
+    pub doc_number: String,
+    pub replaced_from_year: i32,
+    pub replaced_from_month: i32,
+    pub changed_at: String,
+}
+
+const SELECT_COLS: &str = "id, member_id, first_name, last_name, second_name, citizenship,
+    doc_type, doc_number, replaced_from_year, replaced_from_month, changed_at";
+
+fn row_to_change(row: &rusqlite::Row) -> rusqlite::Result<DetailsChange> {
+    Ok(DetailsChange {
+        id: row.get(0)?,
+        member_id: row.get(1)?,
+        first_name: row.get(2)?,
+        last_name: row.get(3)?,
+        second_name: row.get(4)?,
+        citizenship: row.get(5)?,
+        doc_type: row.get(6)?,
+        doc_number: row.get(7)?,
+        replaced_from_year: row.get(8)?,
+        replaced_from_month: row.get(9)?,
+        changed_at: row.get(10)?,
+    })
+}
+
+impl DetailsChange {
+    /// Record the member's current details as valid until `replaced_from`.
+    /// Returns the new row's id.
+    pub fn insert(
+        conn: &Connection,
+        member: &Member,
+        replaced_from: Period,
+    ) -> Result<i64, AppError> {
+        conn.execute(
+            "INSERT INTO member_details_history (member_id, first_name, last_name, second_name,
+                citizenship, doc_type, doc_number, replaced_from_year, replaced_from_month)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                member.id,
+                member.first_name,
+                member.last_name,
+                member.second_name,
+                member.citizenship,
+                member.doc_type,
+                member.doc_number,
+                replaced_from.year,
+                replaced_from.month
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// A member's earlier details, oldest first.
+    pub fn list_for_member(
+        conn: &Connection,
+        member_id: i64,
+    ) -> Result<Vec<DetailsChange>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM member_details_history WHERE member_id = ?1
+             ORDER BY replaced_from_year, replaced_from_month, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![member_id], row_to_change)?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            changes.push(row?);
+        }
+        Ok(changes)
+    }
+
+    /// Move the earlier details of `from_member_id` to `to_member_id`, as when
+    /// merging a duplicate, so the periods moved with them keep their names.
+    pub fn reassign(
+        conn: &Connection,
+        from_member_id: i64,
+        to_member_id: i64,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE member_details_history SET member_id = ?2 WHERE member_id = ?1",
+            params![from_member_id, to_member_id],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a member's earlier details, as when their data is anonymized.
+    pub fn delete_for_member(conn: &Connection, member_id: i64) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM member_details_history WHERE member_id = ?1",
+            params![member_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn replaced_from(&self) -> Period {
+        Period {
+            year: self.replaced_from_year,
+            month: self.replaced_from_month,
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
-use crate::error::AppError;
+use crate::domain::money::Money;
+use crate::error::{AppError, Message};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Generation {
@@ -11,8 +12,10 @@ pub struct Generation {
     pub period_month: i32,
     pub generated_at: String,
     pub file_path: String,
-    pub total_employee_basic: String,
-    pub total_employer_basic: String,
+    pub checksum: String,
+    pub encrypted: bool,
+    pub total_employee_basic: Money,
+    pub total_employer_basic: Money,
     pub member_count: i32,
 }
 
@@ -24,17 +27,34 @@ pub struct GenerationWithSnapshot {
     pub period_month: i32,
     pub generated_at: String,
     pub file_path: String,
-    pub total_employee_basic: String,
-    pub total_employer_basic: String,
+    pub checksum: String,
+    pub encrypted: bool,
+    pub total_employee_basic: Money,
+    pub total_employer_basic: Money,
     pub member_count: i32,
     pub snapshot_json: String,
 }
 
+#[derive(Debug)]
+pub struct NewGeneration {
+    pub organization_id: i64,
+    pub period_year: i32,
+    pub period_month: i32,
+    pub snapshot_json: String,
+    pub file_path: String,
+    pub checksum: String,
+    pub encrypted: bool,
+    pub total_employee_basic: Money,
+    pub total_employer_basic: Money,
+    pub member_count: i32,
+}
+
 impl Generation {
     pub fn list(conn: &Connection, organization_id: i64) -> Result<Vec<Generation>, AppError> {
         let mut stmt = conn.prepare(
             "SELECT id, organization_id, period_year, period_month, generated_at,
-                    file_path, total_employee_basic, total_employer_basic, member_count
+                    file_path, checksum, encrypted, total_employee_basic, total_employer_basic,
+                    member_count
              FROM generations
              WHERE organization_id = ?1
              ORDER BY generated_at DESC",
@@ -48,9 +68,11 @@ impl Generation {
                 period_month: row.get(3)?,
                 generated_at: row.get(4)?,
                 file_path: row.get(5)?,
-                total_employee_basic: row.get(6)?,
-                total_employer_basic: row.get(7)?,
-                member_count: row.get(8)?,
+                checksum: row.get(6)?,
+                encrypted: row.get(7)?,
+                total_employee_basic: row.get(8)?,
+                total_employer_basic: row.get(9)?,
+                member_count: row.get(10)?,
             })
         })?;
 
@@ -61,11 +83,14 @@ impl Generation {
         Ok(result)
     }
 
-    pub fn get_with_snapshot(conn: &Connection, id: i64) -> Result<GenerationWithSnapshot, AppError> {
+    pub fn get_with_snapshot(
+        conn: &Connection,
+        id: i64,
+    ) -> Result<GenerationWithSnapshot, AppError> {
         conn.query_row(
             "SELECT id, organization_id, period_year, period_month, generated_at,
-                    file_path, total_employee_basic, total_employer_basic, member_count,
-                    snapshot_json
+                    file_path, checksum, encrypted, total_employee_basic, total_employer_basic,
+                    member_count, snapshot_json
              FROM generations WHERE id = ?1",
             params![id],
             |row| {
@@ -76,54 +101,131 @@ impl Generation {
                     period_month: row.get(3)?,
                     generated_at: row.get(4)?,
                     file_path: row.get(5)?,
-                    total_employee_basic: row.get(6)?,
-                    total_employer_basic: row.get(7)?,
-                    member_count: row.get(8)?,
-                    snapshot_json: row.get(9)?,
+                    checksum: row.get(6)?,
+                    encrypted: row.get(7)?,
+                    total_employee_basic: row.get(8)?,
+                    total_employer_basic: row.get(9)?,
+                    member_count: row.get(10)?,
+                    snapshot_json: row.get(11)?,
                 })
             },
         )
-        .map_err(|_| AppError::NotFound("Generacja nie znaleziona".into()))
+        .map_err(|_| AppError::NotFound("generation.not_found".into()))
     }
 
-    pub fn insert(
-        conn: &Connection,
-        organization_id: i64,
-        period_year: i32,
-        period_month: i32,
-        snapshot_json: &str,
-        file_path: &str,
-        total_employee_basic: &str,
-        total_employer_basic: &str,
-        member_count: i32,
-    ) -> Result<Generation, AppError> {
+    pub fn insert(conn: &Connection, data: &NewGeneration) -> Result<Generation, AppError> {
         conn.execute(
             "INSERT INTO generations (organization_id, period_year, period_month,
-                snapshot_json, file_path, total_employee_basic, total_employer_basic, member_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                snapshot_json, file_path, checksum, encrypted, total_employee_basic,
+                total_employer_basic, member_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
-                organization_id,
-                period_year,
-                period_month,
-                snapshot_json,
-                file_path,
-                total_employee_basic,
-                total_employer_basic,
-                member_count,
+                data.organization_id,
+                data.period_year,
+                data.period_month,
+                data.snapshot_json,
+                data.file_path,
+                data.checksum,
+                data.encrypted,
+                data.total_employee_basic,
+                data.total_employer_basic,
+                data.member_count,
             ],
         )?;
 
         let id = conn.last_insert_rowid();
         Ok(Generation {
             id,
-            organization_id,
-            period_year,
-            period_month,
+            organization_id: data.organization_id,
+            period_year: data.period_year,
+            period_month: data.period_month,
             generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            file_path: file_path.to_string(),
-            total_employee_basic: total_employee_basic.to_string(),
-            total_employer_basic: total_employer_basic.to_string(),
-            member_count,
+            file_path: data.file_path.clone(),
+            checksum: data.checksum.clone(),
+            encrypted: data.encrypted,
+            total_employee_basic: data.total_employee_basic,
+            total_employer_basic: data.total_employer_basic,
+            member_count: data.member_count,
         })
     }
+
+    /// Ids and snapshots of all generations of an organization.
+    pub fn list_snapshots(
+        conn: &Connection,
+        organization_id: i64,
+    ) -> Result<Vec<(i64, String)>, AppError> {
+        let mut stmt = conn.prepare(
+            "SELECT id, snapshot_json FROM generations WHERE organization_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![organization_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Snapshots of the organization's generations that mention any of `member_ids`.
+    pub fn snapshots_mentioning(
+        conn: &Connection,
+        organization_id: i64,
+        member_ids: &[i64],
+    ) -> Result<Vec<(i64, serde_json::Value)>, AppError> {
+        if member_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut affected = Vec::new();
+        for (id, json) in Self::list_snapshots(conn, organization_id)? {
+            let snapshot: serde_json::Value = serde_json::from_str(&json).map_err(|e| {
+                AppError::Other(Message::new("snapshot.invalid").with_param("details", e))
+            })?;
+            let mentions = snapshot["contributions"].as_array().is_some_and(|rows| {
+                rows.iter().any(|row| {
+                    row["member_id"]
+                        .as_i64()
+                        .is_some_and(|id| member_ids.contains(&id))
+                })
+            });
+            if mentions {
+                affected.push((id, snapshot));
+            }
+        }
+        Ok(affected)
+    }
+
+    /// Forget a generation's archived file, so exports rebuild it from the
+    /// snapshot. Returns the path the file was stored at.
+    pub fn clear_file(conn: &Connection, id: i64) -> Result<String, AppError> {
+        let file_path: String = conn
+            .query_row(
+                "SELECT file_path FROM generations WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|_| AppError::NotFound("generation.not_found".into()))?;
+        conn.execute(
+            "UPDATE generations SET file_path = '', checksum = '' WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(file_path)
+    }
+
+    pub fn update_snapshot(
+        conn: &Connection,
+        id: i64,
+        snapshot_json: &str,
+    ) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE generations SET snapshot_json = ?1 WHERE id = ?2",
+            params![snapshot_json, id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("generation.not_found".into()));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,283 @@
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
+
+/// A table row as column name to value.
+pub type Row = Map<String, Value>;
+
+/// Identifies a journaled row. Contributions are keyed by member and period
+/// because an upsert may create the row it changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum RowKey {
+    Members {
+        id: i64,
+    },
+    Contributions {
+        member_id: i64,
+        period_year: i32,
+        period_month: i32,
+    },
+    MemberDetailsHistory {
+        id: i64,
+    },
+}
+
+impl RowKey {
+    pub fn contribution(member_id: i64, period: Period) -> RowKey {
+        RowKey::Contributions {
+            member_id,
+            period_year: period.year,
+            period_month: period.month,
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            RowKey::Members { .. } => "members",
+            RowKey::Contributions { .. } => "contributions",
+            RowKey::MemberDetailsHistory { .. } => "member_details_history",
+        }
+    }
+
+    fn filter(&self) -> (&'static str, Vec<SqlValue>) {
+        match *self {
+            RowKey::Members { id } | RowKey::MemberDetailsHistory { id } => {
+                ("id = ?1", vec![id.into()])
+            }
+            RowKey::Contributions {
+                member_id,
+                period_year,
+                period_month,
+            } => (
+                "member_id = ?1 AND period_year = ?2 AND period_month = ?3",
+                vec![member_id.into(), period_year.into(), period_month.into()],
+            ),
+        }
+    }
+
+    /// The row as currently stored, if any.
+    pub fn read(&self, conn: &Connection) -> Result<Option<Row>, AppError> {
+        let (filter, values) = self.filter();
+        let sql = format!("SELECT * FROM {} WHERE {}", self.table(), filter);
+        let mut stmt = conn.prepare(&sql)?;
+        let names: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+        let row = stmt
+            .query_row(params_from_iter(values), |row| {
+                let mut map = Row::new();
+                for (i, name) in names.iter().enumerate() {
+                    map.insert(name.clone(), to_json(row.get_ref(i)?));
+                }
+                Ok(map)
+            })
+            .optional()?;
+        Ok(row)
+    }
+
+    /// Make the stored row equal `row`, deleting it for `None`.
+    pub fn write(&self, conn: &Connection, row: Option<&Row>) -> Result<(), AppError> {
+        let (filter, mut values) = self.filter();
+        let Some(row) = row else {
+            conn.execute(
+                &format!("DELETE FROM {} WHERE {}", self.table(), filter),
+                params_from_iter(values),
+            )?;
+            return Ok(());
+        };
+
+        // UPDATE rather than REPLACE, which would cascade to child rows
+        let columns: Vec<&String> = row.keys().collect();
+        let sql = if self.read(conn)?.is_some() {
+            let assignments: Vec<String> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("{} = ?{}", c, values.len() + i + 1))
+                .collect();
+            format!(
+                "UPDATE {} SET {} WHERE {}",
+                self.table(),
+                assignments.join(", "),
+                filter
+            )
+        } else {
+            values.clear();
+            let placeholders: Vec<String> =
+                (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                self.table(),
+                columns
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                placeholders.join(", ")
+            )
+        };
+        values.extend(row.values().map(to_sql));
+        conn.execute(&sql, params_from_iter(values))?;
+        Ok(())
+    }
+}
+
+/// One row as it was before and after an operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowChange {
+    pub key: RowKey,
+    pub before: Option<Row>,
+    pub after: Option<Row>,
+}
+
+/// A journaled operation. Undone entries can be redone until a new operation
+/// is recorded.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub organization_id: i64,
+    pub action: String,
+    pub undone: bool,
+    pub created_at: String,
+    #[serde(skip)]
+    pub changes: Vec<RowChange>,
+}
+
+pub struct Journal;
+
+const SELECT_COLS: &str = "id, organization_id, action, undone, created_at, changes_json";
+
+impl Journal {
+    /// Append an entry and drop everything that could still be redone.
+    pub fn record(
+        conn: &Connection,
+        organization_id: i64,
+        action: &str,
+        changes: &[RowChange],
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM operation_journal WHERE organization_id = ?1 AND undone = 1",
+            params![organization_id],
+        )?;
+        let changes = serde_json::to_string(changes).map_err(|e| {
+            AppError::Other(Message::new("journal.invalid").with_param("details", e))
+        })?;
+        conn.execute(
+            "INSERT INTO operation_journal (organization_id, action, changes_json)
+             VALUES (?1, ?2, ?3)",
+            params![organization_id, action, changes],
+        )?;
+        Ok(())
+    }
+
+    pub fn list(conn: &Connection, organization_id: i64) -> Result<Vec<JournalEntry>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM operation_journal WHERE organization_id = ?1 ORDER BY id DESC",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![organization_id], row_to_entry)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// The most recent entry that has not been undone.
+    pub fn last_done(
+        conn: &Connection,
+        organization_id: i64,
+    ) -> Result<Option<JournalEntry>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM operation_journal
+             WHERE organization_id = ?1 AND undone = 0
+             ORDER BY id DESC LIMIT 1",
+            SELECT_COLS
+        );
+        Ok(conn
+            .query_row(&sql, params![organization_id], row_to_entry)
+            .optional()?)
+    }
+
+    /// The earliest undone entry, next in line for redo.
+    pub fn first_undone(
+        conn: &Connection,
+        organization_id: i64,
+    ) -> Result<Option<JournalEntry>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM operation_journal
+             WHERE organization_id = ?1 AND undone = 1
+             ORDER BY id ASC LIMIT 1",
+            SELECT_COLS
+        );
+        Ok(conn
+            .query_row(&sql, params![organization_id], row_to_entry)
+            .optional()?)
+    }
+
+    pub fn set_undone(conn: &Connection, id: i64, undone: bool) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE operation_journal SET undone = ?2 WHERE id = ?1",
+            params![id, undone],
+        )?;
+        Ok(())
+    }
+
+    /// Forget the organization's journal, so nothing before this point can
+    /// be undone.
+    pub fn cut_off(conn: &Connection, organization_id: i64) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM operation_journal WHERE organization_id = ?1",
+            params![organization_id],
+        )?;
+        Ok(())
+    }
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+    let changes: String = row.get(5)?;
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        organization_id: row.get(1)?,
+        action: row.get(2)?,
+        undone: row.get(3)?,
+        created_at: row.get(4)?,
+        changes: serde_json::from_str(&changes).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+    })
+}
+
+fn to_json(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => b.to_vec().into(),
+    }
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(s) => s.clone().into(),
+        Value::Array(items) => SqlValue::Blob(
+            items
+                .iter()
+                .filter_map(|i| i.as_u64())
+                .map(|b| b as u8)
+                .collect(),
+        ),
+        Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
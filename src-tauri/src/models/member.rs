@@ -1,24 +1,34 @@
-use rusqlite::{params, Connection};
+use chrono::{Months, NaiveDate};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
-use crate::error::AppError;
+use crate::domain::codes::{Gender, MemberStatus};
+use crate::domain::identifiers::Pesel;
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Member {
     pub id: i64,
     pub organization_id: i64,
-    pub pesel: String,
+    pub pesel: Pesel,
     pub first_name: String,
     pub last_name: String,
-    pub gender: String,
+    pub gender: Gender,
     pub date_of_birth: String,
     pub citizenship: String,
     pub second_name: String,
     pub doc_type: String,
     pub doc_number: String,
-    pub status: String,
+    pub status: MemberStatus,
+    /// Day the member stopped being active; drives the retention policy.
+    pub terminated_at: Option<String>,
+    pub anonymized_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Set while the member is in the trash.
+    pub archived_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,16 +49,188 @@ pub struct CreateMember {
 pub struct UpdateMember {
     pub first_name: String,
     pub last_name: String,
-    pub gender: String,
+    pub gender: Gender,
     pub date_of_birth: String,
     pub citizenship: Option<String>,
     pub second_name: Option<String>,
     pub doc_type: Option<String>,
     pub doc_number: Option<String>,
-    pub status: Option<String>,
+    pub status: Option<MemberStatus>,
+    /// First period the new names, citizenship and document apply to.
+    /// Earlier periods keep the current ones. Without it the edit is a
+    /// correction and applies to every period.
+    #[serde(default)]
+    pub effective_from: Option<Period>,
+}
+
+/// Values left in place of a member's personal data once anonymized.
+#[derive(Debug, Clone)]
+pub struct AnonymizedPerson {
+    pub pesel: Pesel,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl AnonymizedPerson {
+    pub fn for_member(id: i64) -> AnonymizedPerson {
+        AnonymizedPerson {
+            // Unique per member, since (organization_id, pesel) is unique
+            pesel: Pesel::anonymized(id),
+            first_name: String::new(),
+            last_name: "Zanonimizowany".to_string(),
+        }
+    }
+}
+
+/// Narrows a list of members; unset fields match everyone.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MemberFilter {
+    /// Words matched against names, PESEL and document number. A member
+    /// matches when every word is found in one of them.
+    pub search: Option<String>,
+    pub status: Option<MemberStatus>,
+    pub citizenship: Option<String>,
+    /// Age in full years on `age_on`, today when absent. Members without a
+    /// date of birth never match an age filter.
+    pub min_age: Option<u32>,
+    pub max_age: Option<u32>,
+    pub age_on: Option<NaiveDate>,
+    /// Only members with no document type or number recorded.
+    pub missing_documents: bool,
+}
+
+impl MemberFilter {
+    /// SQL conditions on the members table aliased `m`, with their values in
+    /// the order of their `?` placeholders.
+    pub(crate) fn conditions(&self) -> Result<(Vec<String>, Vec<SqlValue>), AppError> {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        for word in self.search.as_deref().unwrap_or("").split_whitespace() {
+            // Both sides are lowercased, as LIKE ignores case for ASCII only
+            let pattern = format!("%{}%", escape_like(&word.to_lowercase()));
+            clauses.push(
+                "(unicode_lower(m.first_name) LIKE ? ESCAPE '\\'
+                  OR unicode_lower(m.last_name) LIKE ? ESCAPE '\\'
+                  OR unicode_lower(m.second_name) LIKE ? ESCAPE '\\' OR m.pesel LIKE ? ESCAPE '\\'
+                  OR unicode_lower(m.doc_number) LIKE ? ESCAPE '\\')"
+                    .to_string(),
+            );
+            values.extend(std::iter::repeat(SqlValue::from(pattern)).take(5));
+        }
+        if let Some(status) = self.status {
+            clauses.push("m.status = ?".to_string());
+            values.push(status.as_str().to_string().into());
+        }
+        if let Some(citizenship) = &self.citizenship {
+            clauses.push("m.citizenship = ?".to_string());
+            values.push(citizenship.trim().to_uppercase().into());
+        }
+        if self.min_age.is_some() || self.max_age.is_some() {
+            if let (Some(min), Some(max)) = (self.min_age, self.max_age) {
+                if min > max {
+                    return Err(AppError::Validation(
+                        Message::new("member.age_range").with_field("min_age"),
+                    ));
+                }
+            }
+            let age_on = self
+                .age_on
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+            clauses.push("m.date_of_birth != ''".to_string());
+            if let Some(min) = self.min_age {
+                // Born on or before the day they turned `min`
+                clauses.push("m.date_of_birth <= ?".to_string());
+                values.push(years_before(age_on, min)?.to_string().into());
+            }
+            if let Some(max) = self.max_age {
+                // Born after the day they would have turned `max + 1`
+                clauses.push("m.date_of_birth > ?".to_string());
+                values.push(years_before(age_on, max + 1)?.to_string().into());
+            }
+        }
+        if self.missing_documents {
+            clauses.push("(m.doc_type = '' OR m.doc_number = '')".to_string());
+        }
+
+        Ok((clauses, values))
+    }
+}
+
+fn escape_like(word: &str) -> String {
+    word.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn years_before(day: NaiveDate, years: u32) -> Result<NaiveDate, AppError> {
+    years
+        .checked_mul(12)
+        .and_then(|months| day.checked_sub_months(Months::new(months)))
+        .ok_or_else(|| AppError::Validation(Message::new("member.age_range").with_field("max_age")))
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberSort {
+    #[default]
+    LastName,
+    FirstName,
+    Pesel,
+    DateOfBirth,
+    Status,
+    CreatedAt,
+}
+
+impl MemberSort {
+    /// Columns to order by; `id` is appended so the order is total.
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            MemberSort::LastName => &["last_name", "first_name"],
+            MemberSort::FirstName => &["first_name", "last_name"],
+            MemberSort::Pesel => &["pesel"],
+            MemberSort::DateOfBirth => &["date_of_birth"],
+            MemberSort::Status => &["status", "last_name", "first_name"],
+            MemberSort::CreatedAt => &["created_at"],
+        }
+    }
+}
+
+/// Default and largest page size of [`Member::query`].
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MemberQuery {
+    #[serde(flatten)]
+    pub filter: MemberFilter,
+    pub sort: MemberSort,
+    pub descending: bool,
+    /// Cursor: the `next` of the previous page.
+    pub after: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberPage {
+    pub members: Vec<Member>,
+    /// Members matching the filter, across all pages.
+    pub total: i64,
+    /// Cursor of the next page, absent on the last one.
+    pub next: Option<i64>,
 }
 
-const SELECT_COLS: &str = "id, organization_id, pesel, first_name, last_name, gender, date_of_birth, citizenship, second_name, doc_type, doc_number, status, created_at, updated_at";
+const SELECT_COLS: &str = "id, organization_id, pesel, first_name, last_name, gender, date_of_birth, citizenship, second_name, doc_type, doc_number, status, terminated_at, anonymized_at, created_at, updated_at, archived_at";
+
+fn prefixed_cols(alias: &str) -> String {
+    SELECT_COLS
+        .split(", ")
+        .map(|c| format!("{}.{}", alias, c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
 fn row_to_member(row: &rusqlite::Row) -> rusqlite::Result<Member> {
     Ok(Member {
@@ -64,19 +246,24 @@ fn row_to_member(row: &rusqlite::Row) -> rusqlite::Result<Member> {
         doc_type: row.get(9)?,
         doc_number: row.get(10)?,
         status: row.get(11)?,
-        created_at: row.get(12)?,
-        updated_at: row.get(13)?,
+        terminated_at: row.get(12)?,
+        anonymized_at: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+        archived_at: row.get(16)?,
     })
 }
 
 impl Member {
+    /// The organization's members, leaving out the archived ones.
     pub fn list(conn: &Connection, organization_id: i64) -> Result<Vec<Member>, AppError> {
         let sql = format!(
-            "SELECT {} FROM members WHERE organization_id = ?1 ORDER BY last_name, first_name",
+            "SELECT {} FROM members WHERE organization_id = ?1 AND archived_at IS NULL
+             ORDER BY last_name, first_name",
             SELECT_COLS
         );
         let mut stmt = conn.prepare(&sql)?;
-        let rows = stmt.query_map(params![organization_id], |row| row_to_member(row))?;
+        let rows = stmt.query_map(params![organization_id], row_to_member)?;
 
         let mut members = Vec::new();
         for row in rows {
@@ -85,13 +272,97 @@ impl Member {
         Ok(members)
     }
 
+    /// One page of the organization's members matching `query`, continuing
+    /// after the member given as cursor.
+    pub fn query(
+        conn: &Connection,
+        organization_id: i64,
+        query: &MemberQuery,
+    ) -> Result<MemberPage, AppError> {
+        let (mut clauses, mut values) = query.filter.conditions()?;
+        clauses.insert(
+            0,
+            "m.organization_id = ? AND m.archived_at IS NULL".to_string(),
+        );
+        values.insert(0, organization_id.into());
+        let filter = clauses.join(" AND ");
+
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM members m WHERE {}", filter),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        let mut columns: Vec<String> = query
+            .sort
+            .columns()
+            .iter()
+            .map(|c| format!("m.{}", c))
+            .collect();
+        columns.push("m.id".to_string());
+        let direction = if query.descending { "DESC" } else { "ASC" };
+
+        let mut sql = format!(
+            "SELECT {} FROM members m WHERE {}",
+            prefixed_cols("m"),
+            filter
+        );
+        if let Some(after) = query.after {
+            let cursor = Self::get(conn, after)?;
+            if cursor.organization_id != organization_id {
+                return Err(AppError::NotFound("member.not_found".into()));
+            }
+            // Row values compare column by column, as ORDER BY does
+            sql.push_str(&format!(
+                " AND ({}) {} (SELECT {} FROM members m WHERE m.id = ?)",
+                columns.join(", "),
+                if query.descending { "<" } else { ">" },
+                columns.join(", ")
+            ));
+            values.push(after.into());
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        sql.push_str(&format!(
+            " ORDER BY {} LIMIT {}",
+            columns
+                .iter()
+                .map(|c| format!("{} {}", c, direction))
+                .collect::<Vec<_>>()
+                .join(", "),
+            limit + 1
+        ));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), row_to_member)?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            members.push(row?);
+        }
+        let next = if members.len() > limit as usize {
+            members.truncate(limit as usize);
+            members.last().map(|m| m.id)
+        } else {
+            None
+        };
+        Ok(MemberPage {
+            members,
+            total,
+            next,
+        })
+    }
+
     pub fn list_active(conn: &Connection, organization_id: i64) -> Result<Vec<Member>, AppError> {
         let sql = format!(
-            "SELECT {} FROM members WHERE organization_id = ?1 AND status = 'active' ORDER BY last_name, first_name",
+            "SELECT {} FROM members WHERE organization_id = ?1 AND status = 'active' AND archived_at IS NULL
+             ORDER BY last_name, first_name",
             SELECT_COLS
         );
         let mut stmt = conn.prepare(&sql)?;
-        let rows = stmt.query_map(params![organization_id], |row| row_to_member(row))?;
+        let rows = stmt.query_map(params![organization_id], row_to_member)?;
 
         let mut members = Vec::new();
         for row in rows {
@@ -100,10 +371,61 @@ impl Member {
         Ok(members)
     }
 
+    /// Memberships of one person across all organizations.
+    pub fn list_by_pesel(conn: &Connection, pesel: &str) -> Result<Vec<Member>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM members WHERE pesel = ?1 ORDER BY organization_id, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![pesel], row_to_member)?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            members.push(row?);
+        }
+        Ok(members)
+    }
+
+    /// Members of all organizations in use whose personal data was not
+    /// anonymized. Archived members and organizations are left out.
+    pub fn list_identified(conn: &Connection) -> Result<Vec<Member>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM members
+             WHERE anonymized_at IS NULL AND archived_at IS NULL
+               AND organization_id IN (SELECT id FROM organizations WHERE archived_at IS NULL)
+             ORDER BY organization_id, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], row_to_member)?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            members.push(row?);
+        }
+        Ok(members)
+    }
+
+    /// The organization's membership of the person with `pesel`, if any.
+    pub fn find_by_pesel(
+        conn: &Connection,
+        organization_id: i64,
+        pesel: &str,
+    ) -> Result<Option<Member>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM members WHERE organization_id = ?1 AND pesel = ?2",
+            SELECT_COLS
+        );
+        Ok(conn
+            .query_row(&sql, params![organization_id, pesel], row_to_member)
+            .optional()?)
+    }
+
     pub fn get(conn: &Connection, id: i64) -> Result<Member, AppError> {
         let sql = format!("SELECT {} FROM members WHERE id = ?1", SELECT_COLS);
-        conn.query_row(&sql, params![id], |row| row_to_member(row))
-            .map_err(|_| AppError::NotFound("Uczestnik nie znaleziony".into()))
+        conn.query_row(&sql, params![id], row_to_member)
+            .map_err(|_| AppError::NotFound("member.not_found".into()))
     }
 
     pub fn create(conn: &Connection, data: &CreateMember) -> Result<Member, AppError> {
@@ -132,6 +454,11 @@ impl Member {
         let affected = conn.execute(
             "UPDATE members SET first_name = ?1, last_name = ?2, gender = ?3, date_of_birth = ?4,
              citizenship = ?5, second_name = ?6, doc_type = ?7, doc_number = ?8, status = ?9,
+             terminated_at = CASE
+                WHEN ?9 = 'active' THEN NULL
+                WHEN status = 'active' OR terminated_at IS NULL THEN date('now')
+                ELSE terminated_at
+             END,
              updated_at = datetime('now') WHERE id = ?10",
             params![
                 data.first_name,
@@ -142,22 +469,161 @@ impl Member {
                 data.second_name.as_deref().unwrap_or(""),
                 data.doc_type.as_deref().unwrap_or(""),
                 data.doc_number.as_deref().unwrap_or(""),
-                data.status.as_deref().unwrap_or("active"),
+                data.status.unwrap_or(MemberStatus::Active),
                 id,
             ],
         )?;
 
         if affected == 0 {
-            return Err(AppError::NotFound("Uczestnik nie znaleziony".into()));
+            return Err(AppError::NotFound("member.not_found".into()));
         }
 
         Self::get(conn, id)
     }
 
+    /// Replace the member's PESEL and the data derived from it.
+    pub fn set_pesel(
+        conn: &Connection,
+        id: i64,
+        pesel: &Pesel,
+        gender: Gender,
+        date_of_birth: &str,
+    ) -> Result<Member, AppError> {
+        let affected = conn.execute(
+            "UPDATE members SET pesel = ?1, gender = ?2, date_of_birth = ?3,
+             updated_at = datetime('now') WHERE id = ?4",
+            params![pesel, gender, date_of_birth, id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("member.not_found".into()));
+        }
+        Self::get(conn, id)
+    }
+
+    /// End an active membership on `on`.
+    pub fn close(conn: &Connection, id: i64, on: NaiveDate) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE members SET status = 'terminated', terminated_at = ?1,
+             updated_at = datetime('now') WHERE id = ?2",
+            params![on.to_string(), id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("member.not_found".into()));
+        }
+        Ok(())
+    }
+
+    /// Make a former membership active again, with the identity data of
+    /// `person`.
+    pub fn reopen(conn: &Connection, id: i64, person: &Member) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE members SET first_name = ?1, last_name = ?2, gender = ?3, date_of_birth = ?4,
+             citizenship = ?5, second_name = ?6, doc_type = ?7, doc_number = ?8,
+             status = 'active', terminated_at = NULL, archived_at = NULL,
+             updated_at = datetime('now') WHERE id = ?9",
+            params![
+                person.first_name,
+                person.last_name,
+                person.gender,
+                person.date_of_birth,
+                person.citizenship,
+                person.second_name,
+                person.doc_type,
+                person.doc_number,
+                id,
+            ],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("member.not_found".into()));
+        }
+        Ok(())
+    }
+
+    /// Former members of an organization who left on or before `cutoff`
+    /// (`YYYY-MM-DD`) and still have their personal data.
+    pub fn list_retention_due(
+        conn: &Connection,
+        organization_id: i64,
+        cutoff: &str,
+    ) -> Result<Vec<Member>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM members
+             WHERE organization_id = ?1 AND status != 'active' AND anonymized_at IS NULL
+               AND terminated_at IS NOT NULL AND terminated_at <= ?2
+             ORDER BY terminated_at, last_name, first_name",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![organization_id, cutoff], row_to_member)?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            members.push(row?);
+        }
+        Ok(members)
+    }
+
+    /// Replace the personal data of a member with `anonymized`, keeping the
+    /// row so its contributions still count towards totals.
+    pub fn anonymize(
+        conn: &Connection,
+        id: i64,
+        anonymized: &AnonymizedPerson,
+    ) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE members SET pesel = ?1, first_name = ?2, last_name = ?3, second_name = '',
+             date_of_birth = '', doc_type = '', doc_number = '',
+             anonymized_at = datetime('now'), updated_at = datetime('now')
+             WHERE id = ?4",
+            params![
+                anonymized.pesel,
+                anonymized.first_name,
+                anonymized.last_name,
+                id
+            ],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("member.not_found".into()));
+        }
+        Ok(())
+    }
+
+    /// Archived members of all organizations, most recently archived first.
+    pub fn list_archived(conn: &Connection) -> Result<Vec<Member>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM members WHERE archived_at IS NOT NULL ORDER BY archived_at DESC, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], row_to_member)?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            members.push(row?);
+        }
+        Ok(members)
+    }
+
+    /// Move the member to the trash, or back out of it.
+    pub fn set_archived(conn: &Connection, id: i64, archived: bool) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE members SET
+             archived_at = CASE WHEN ?1 THEN COALESCE(archived_at, datetime('now')) END,
+             updated_at = datetime('now') WHERE id = ?2",
+            params![archived, id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("member.not_found".into()));
+        }
+        Ok(())
+    }
+
+    /// Delete the member with their contributions. Only for merging and for
+    /// purging from the trash.
     pub fn delete(conn: &Connection, id: i64) -> Result<(), AppError> {
         let affected = conn.execute("DELETE FROM members WHERE id = ?1", params![id])?;
         if affected == 0 {
-            return Err(AppError::NotFound("Uczestnik nie znaleziony".into()));
+            return Err(AppError::NotFound("member.not_found".into()));
         }
         Ok(())
     }
@@ -1,4 +1,11 @@
+pub mod audit;
+pub mod contact;
 pub mod contribution;
+pub mod details_history;
 pub mod generation;
+pub mod journal;
 pub mod member;
 pub mod organization;
+pub mod pesel_history;
+pub mod settings;
+pub mod transfer;
@@ -1,17 +1,53 @@
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
+use crate::domain::codes::EmployerIdType;
+use crate::domain::identifiers::{Nip, Pesel, Regon};
 use crate::error::AppError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Organization {
     pub id: i64,
     pub name: String,
-    pub nip: String,
-    pub regon: String,
-    pub contact_person: String,
+    /// Empty when the employer is identified otherwise.
+    pub nip: Nip,
+    /// 9 digits, or 14 for a local unit. Empty when the employer has none.
+    pub regon: Regon,
+    /// Which of NIP, REGON and PESEL identifies the employer.
+    #[serde(default)]
+    pub id_type: EmployerIdType,
+    /// Only for sole proprietors identified by PESEL.
+    #[serde(default)]
+    pub pesel: Option<Pesel>,
+    #[serde(flatten)]
+    pub profile: EmployerProfile,
     pub created_at: String,
     pub updated_at: String,
+    /// Set while the organization is in the trash.
+    pub archived_at: Option<String>,
+}
+
+/// Registered address, registry numbers, accounts and PPK agreements of an
+/// employer. Every field is optional; empty strings mean not filled in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmployerProfile {
+    pub street: String,
+    pub building_number: String,
+    pub apartment_number: String,
+    pub postal_code: String,
+    pub city: String,
+    pub krs: String,
+    /// The employer's own account, as NRB or IBAN.
+    pub bank_account: String,
+    /// Agreement with the financial institution to manage the PPK.
+    pub management_agreement_number: String,
+    pub management_agreement_date: Option<String>,
+    /// Agreement with the financial institution to run the PPK.
+    pub operation_agreement_number: String,
+    pub operation_agreement_date: Option<String>,
+    /// The institution's account contributions are paid to.
+    pub collection_account: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,7 +55,13 @@ pub struct CreateOrganization {
     pub name: String,
     pub nip: String,
     pub regon: String,
-    pub contact_person: String,
+    #[serde(default)]
+    pub id_type: EmployerIdType,
+    /// Ignored unless the employer is identified by PESEL.
+    #[serde(default)]
+    pub pesel: String,
+    #[serde(flatten)]
+    pub profile: EmployerProfile,
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,27 +69,89 @@ pub struct UpdateOrganization {
     pub name: String,
     pub nip: String,
     pub regon: String,
-    pub contact_person: String,
+    #[serde(default)]
+    pub id_type: EmployerIdType,
+    /// Ignored unless the employer is identified by PESEL.
+    #[serde(default)]
+    pub pesel: String,
+    #[serde(flatten)]
+    pub profile: EmployerProfile,
+}
+
+const SELECT_COLS: &str = "id, name, nip, regon, created_at, updated_at, archived_at,
+    street, building_number, apartment_number, postal_code, city, krs, bank_account,
+    management_agreement_number, management_agreement_date,
+    operation_agreement_number, operation_agreement_date, collection_account, id_type, pesel";
+
+fn row_to_organization(row: &rusqlite::Row) -> rusqlite::Result<Organization> {
+    Ok(Organization {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        nip: row.get(2)?,
+        regon: row.get(3)?,
+        id_type: row.get(19)?,
+        pesel: row.get(20)?,
+        profile: EmployerProfile {
+            street: row.get(7)?,
+            building_number: row.get(8)?,
+            apartment_number: row.get(9)?,
+            postal_code: row.get(10)?,
+            city: row.get(11)?,
+            krs: row.get(12)?,
+            bank_account: row.get(13)?,
+            management_agreement_number: row.get(14)?,
+            management_agreement_date: row.get(15)?,
+            operation_agreement_number: row.get(16)?,
+            operation_agreement_date: row.get(17)?,
+            collection_account: row.get(18)?,
+        },
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        archived_at: row.get(6)?,
+    })
+}
+
+/// The PESEL of a sole proprietor is personal data, so it is kept only while
+/// it identifies the employer.
+fn employer_pesel(id_type: EmployerIdType, pesel: &str) -> Option<&str> {
+    (id_type == EmployerIdType::Pesel && !pesel.is_empty()).then_some(pesel)
 }
 
 impl Organization {
+    /// Digits of the identifier the employer goes by.
+    pub fn identifier(&self) -> String {
+        match self.id_type {
+            EmployerIdType::Nip => self.nip.digits(),
+            EmployerIdType::Regon => self.regon.digits(),
+            EmployerIdType::Pesel => self
+                .pesel
+                .as_ref()
+                .map(|p| p.as_str().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Organizations in use, leaving out the archived ones.
     pub fn list(conn: &Connection) -> Result<Vec<Organization>, AppError> {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, nip, regon, contact_person, created_at, updated_at
-             FROM organizations ORDER BY name",
-        )?;
+        let sql = format!(
+            "SELECT {} FROM organizations WHERE archived_at IS NULL ORDER BY name",
+            SELECT_COLS
+        );
+        Self::query_all(conn, &sql)
+    }
 
-        let rows = stmt.query_map([], |row| {
-            Ok(Organization {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                nip: row.get(2)?,
-                regon: row.get(3)?,
-                contact_person: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
-            })
-        })?;
+    /// Archived organizations, most recently archived first.
+    pub fn list_archived(conn: &Connection) -> Result<Vec<Organization>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM organizations WHERE archived_at IS NOT NULL ORDER BY archived_at DESC, name",
+            SELECT_COLS
+        );
+        Self::query_all(conn, &sql)
+    }
+
+    fn query_all(conn: &Connection, sql: &str) -> Result<Vec<Organization>, AppError> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], row_to_organization)?;
 
         let mut orgs = Vec::new();
         for row in rows {
@@ -56,55 +160,103 @@ impl Organization {
         Ok(orgs)
     }
 
+    /// Any organization, archived or not.
     pub fn get(conn: &Connection, id: i64) -> Result<Organization, AppError> {
-        conn.query_row(
-            "SELECT id, name, nip, regon, contact_person, created_at, updated_at
-             FROM organizations WHERE id = ?1",
-            params![id],
-            |row| {
-                Ok(Organization {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    nip: row.get(2)?,
-                    regon: row.get(3)?,
-                    contact_person: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                })
-            },
-        )
-        .map_err(|_| AppError::NotFound("Organizacja nie znaleziona".into()))
+        let sql = format!("SELECT {} FROM organizations WHERE id = ?1", SELECT_COLS);
+        conn.query_row(&sql, params![id], row_to_organization)
+            .map_err(|_| AppError::NotFound("organization.not_found".into()))
     }
 
     pub fn create(conn: &Connection, data: &CreateOrganization) -> Result<Organization, AppError> {
         conn.execute(
-            "INSERT INTO organizations (name, nip, regon, contact_person)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![data.name, data.nip, data.regon, data.contact_person],
+            "INSERT INTO organizations (name, nip, regon, id_type, pesel)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                data.name,
+                data.nip,
+                data.regon,
+                data.id_type,
+                employer_pesel(data.id_type, &data.pesel)
+            ],
         )?;
 
         let id = conn.last_insert_rowid();
+        Self::set_profile(conn, id, &data.profile)?;
         Self::get(conn, id)
     }
 
-    pub fn update(conn: &Connection, id: i64, data: &UpdateOrganization) -> Result<Organization, AppError> {
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        data: &UpdateOrganization,
+    ) -> Result<Organization, AppError> {
         let affected = conn.execute(
-            "UPDATE organizations SET name = ?1, nip = ?2, regon = ?3, contact_person = ?4,
-             updated_at = datetime('now') WHERE id = ?5",
-            params![data.name, data.nip, data.regon, data.contact_person, id],
+            "UPDATE organizations SET name = ?1, nip = ?2, regon = ?3, id_type = ?4, pesel = ?5,
+             updated_at = datetime('now') WHERE id = ?6",
+            params![
+                data.name,
+                data.nip,
+                data.regon,
+                data.id_type,
+                employer_pesel(data.id_type, &data.pesel),
+                id
+            ],
         )?;
 
         if affected == 0 {
-            return Err(AppError::NotFound("Organizacja nie znaleziona".into()));
+            return Err(AppError::NotFound("organization.not_found".into()));
         }
 
+        Self::set_profile(conn, id, &data.profile)?;
         Self::get(conn, id)
     }
 
+    fn set_profile(conn: &Connection, id: i64, profile: &EmployerProfile) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE organizations SET street = ?1, building_number = ?2, apartment_number = ?3,
+             postal_code = ?4, city = ?5, krs = ?6, bank_account = ?7,
+             management_agreement_number = ?8, management_agreement_date = ?9,
+             operation_agreement_number = ?10, operation_agreement_date = ?11,
+             collection_account = ?12 WHERE id = ?13",
+            params![
+                profile.street,
+                profile.building_number,
+                profile.apartment_number,
+                profile.postal_code,
+                profile.city,
+                profile.krs,
+                profile.bank_account,
+                profile.management_agreement_number,
+                profile.management_agreement_date,
+                profile.operation_agreement_number,
+                profile.operation_agreement_date,
+                profile.collection_account,
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Move the organization to the trash, or back out of it.
+    pub fn set_archived(conn: &Connection, id: i64, archived: bool) -> Result<(), AppError> {
+        let affected = conn.execute(
+            "UPDATE organizations SET
+             archived_at = CASE WHEN ?1 THEN COALESCE(archived_at, datetime('now')) END,
+             updated_at = datetime('now') WHERE id = ?2",
+            params![archived, id],
+        )?;
+        if affected == 0 {
+            return Err(AppError::NotFound("organization.not_found".into()));
+        }
+        Ok(())
+    }
+
+    /// Delete the organization with everything belonging to it. Only for
+    /// purging it from the trash.
     pub fn delete(conn: &Connection, id: i64) -> Result<(), AppError> {
         let affected = conn.execute("DELETE FROM organizations WHERE id = ?1", params![id])?;
         if affected == 0 {
-            return Err(AppError::NotFound("Organizacja nie znaleziona".into()));
+            return Err(AppError::NotFound("organization.not_found".into()));
         }
         Ok(())
     }
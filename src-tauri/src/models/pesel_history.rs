@@ -0,0 +1,142 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::codes::Gender;
+use crate::domain::identifiers::Pesel;
+use crate::domain::period::Period;
+use crate::error::AppError;
+
+/// A PESEL a member had before it was corrected. It still applies to the
+/// periods before `replaced_from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeselChange {
+    pub id: i64,
+    pub member_id: i64,
+    pub pesel: Pesel,
+    pub gender: Gender,
+    pub date_of_birth: String,
+    pub replaced_from_year: i32,
+    pub replaced_from_month: i32,
+    pub changed_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePesel {
+    pub pesel: String,
+    /// First period the new PESEL applies to. Earlier periods keep the
+    /// current one; the earliest period on record replaces it everywhere.
+    pub effective_from: Period,
+}
+
+const SELECT_COLS: &str = "id, member_id, pesel, gender, date_of_birth,
+    replaced_from_year, replaced_from_month, changed_at";
+
+fn row_to_change(row: &rusqlite::Row) -> rusqlite::Result<PeselChange> {
+    Ok(PeselChange {
+        id: row.get(0)?,
+        member_id: row.get(1)?,
+        pesel: row.get(2)?,
+        gender: row.get(3)?,
+        date_of_birth: row.get(4)?,
+        replaced_from_year: row.get(5)?,
+        replaced_from_month: row.get(6)?,
+        changed_at: row.get(7)?,
+    })
+}
+
+impl PeselChange {
+    /// Record the PESEL, gender and date of birth a member had until
+    /// `replaced_from`.
+    pub fn insert(
+        conn: &Connection,
+        member_id: i64,
+        pesel: &Pesel,
+        gender: Gender,
+        date_of_birth: &str,
+        replaced_from: Period,
+    ) -> Result<PeselChange, AppError> {
+        conn.execute(
+            "INSERT INTO member_pesel_history (member_id, pesel, gender, date_of_birth,
+                replaced_from_year, replaced_from_month)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                member_id,
+                pesel,
+                gender,
+                date_of_birth,
+                replaced_from.year,
+                replaced_from.month
+            ],
+        )?;
+        let id = conn.last_insert_rowid();
+        let sql = format!(
+            "SELECT {} FROM member_pesel_history WHERE id = ?1",
+            SELECT_COLS
+        );
+        Ok(conn.query_row(&sql, params![id], row_to_change)?)
+    }
+
+    /// A member's earlier PESELs, oldest first.
+    pub fn list_for_member(
+        conn: &Connection,
+        member_id: i64,
+    ) -> Result<Vec<PeselChange>, AppError> {
+        let sql = format!(
+            "SELECT {} FROM member_pesel_history WHERE member_id = ?1
+             ORDER BY replaced_from_year, replaced_from_month, id",
+            SELECT_COLS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![member_id], row_to_change)?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            changes.push(row?);
+        }
+        Ok(changes)
+    }
+
+    /// Members with `pesel` among their earlier PESELs.
+    pub fn member_ids_with(conn: &Connection, pesel: &str) -> Result<Vec<i64>, AppError> {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT member_id FROM member_pesel_history WHERE pesel = ?1 ORDER BY member_id",
+        )?;
+        let rows = stmt.query_map(params![pesel], |row| row.get(0))?;
+
+        let mut ids = Vec::new();
+        for row in rows {
+            ids.push(row?);
+        }
+        Ok(ids)
+    }
+
+    /// Move the earlier PESELs of `from_member_id` to `to_member_id`, as when
+    /// merging a duplicate, so the periods moved with them keep their PESEL.
+    pub fn reassign(
+        conn: &Connection,
+        from_member_id: i64,
+        to_member_id: i64,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE member_pesel_history SET member_id = ?2 WHERE member_id = ?1",
+            params![from_member_id, to_member_id],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a member's earlier PESELs, as when their data is anonymized.
+    pub fn delete_for_member(conn: &Connection, member_id: i64) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM member_pesel_history WHERE member_id = ?1",
+            params![member_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn replaced_from(&self) -> Period {
+        Period {
+            year: self.replaced_from_year,
+            month: self.replaced_from_month,
+        }
+    }
+}
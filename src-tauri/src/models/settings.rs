@@ -0,0 +1,171 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::i18n::Locale;
+use crate::secret::Password;
+
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "{id}_SKLADKA_{year}_{month}.zip";
+pub const DEFAULT_CONTACT_TEMPLATE: &str = "{name} {phone} {email}";
+
+/// Application-wide settings, stored as key/value rows in `app_settings`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Directory holding a copy of every generated ZIP.
+    pub archive_dir: String,
+    /// Output directory used when an organization has none of its own.
+    pub output_dir: String,
+    pub file_name_template: String,
+    /// Format of `<KONTAKT>` when an organization has none of its own.
+    #[serde(default)]
+    pub contact_template: String,
+    /// Language of messages, errors included.
+    #[serde(default)]
+    pub language: Locale,
+}
+
+/// Per-organization overrides. Empty values fall back to `AppSettings`.
+/// The ZIP password itself is never sent back, only whether one is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationSettings {
+    pub organization_id: i64,
+    pub output_dir: String,
+    pub file_name_template: String,
+    /// Format of `<KONTAKT>` expected by the organization's institution.
+    pub contact_template: String,
+    pub has_zip_password: bool,
+    /// Years after termination before a member's personal data is anonymized.
+    pub retention_years: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrganizationSettings {
+    pub output_dir: String,
+    pub file_name_template: String,
+    #[serde(default)]
+    pub contact_template: String,
+    /// `None` keeps the current password, an empty one removes it.
+    #[serde(default)]
+    pub zip_password: Option<Password>,
+    #[serde(default)]
+    pub retention_years: Option<i32>,
+}
+
+impl AppSettings {
+    pub fn get(conn: &Connection) -> Result<AppSettings, AppError> {
+        let mut settings = AppSettings::default();
+
+        let mut stmt = conn.prepare("SELECT key, value FROM app_settings")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        for row in rows {
+            let (key, value) = row?;
+            match key.as_str() {
+                "archive_dir" => settings.archive_dir = value,
+                "output_dir" => settings.output_dir = value,
+                "file_name_template" => settings.file_name_template = value,
+                "contact_template" => settings.contact_template = value,
+                "language" => settings.language = Locale::from_code(&value),
+                _ => {}
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn save(conn: &Connection, settings: &AppSettings) -> Result<(), AppError> {
+        for (key, value) in [
+            ("archive_dir", settings.archive_dir.as_str()),
+            ("output_dir", settings.output_dir.as_str()),
+            ("file_name_template", settings.file_name_template.as_str()),
+            ("contact_template", settings.contact_template.as_str()),
+            ("language", settings.language.as_str()),
+        ] {
+            conn.execute(
+                "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = datetime('now')",
+                params![key, value],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl OrganizationSettings {
+    pub fn get(conn: &Connection, organization_id: i64) -> Result<OrganizationSettings, AppError> {
+        let row = conn
+            .query_row(
+                "SELECT output_dir, file_name_template, contact_template, zip_password != '',
+                    retention_years
+                 FROM organization_settings WHERE organization_id = ?1",
+                params![organization_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, bool>(3)?,
+                        row.get::<_, Option<i32>>(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let (output_dir, file_name_template, contact_template, has_zip_password, retention_years) =
+            row.unwrap_or_default();
+        Ok(OrganizationSettings {
+            organization_id,
+            output_dir,
+            file_name_template,
+            contact_template,
+            has_zip_password,
+            retention_years,
+        })
+    }
+
+    pub fn zip_password(
+        conn: &Connection,
+        organization_id: i64,
+    ) -> Result<Option<Password>, AppError> {
+        let password: Option<String> = conn
+            .query_row(
+                "SELECT zip_password FROM organization_settings WHERE organization_id = ?1",
+                params![organization_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(password.filter(|p| !p.is_empty()).map(Password::new))
+    }
+
+    pub fn save(
+        conn: &Connection,
+        organization_id: i64,
+        data: &UpdateOrganizationSettings,
+    ) -> Result<OrganizationSettings, AppError> {
+        conn.execute(
+            "INSERT INTO organization_settings
+                (organization_id, output_dir, file_name_template, zip_password, retention_years,
+                 contact_template)
+             VALUES (?1, ?2, ?3, COALESCE(?4, ''), ?5, ?6)
+             ON CONFLICT(organization_id) DO UPDATE SET
+                output_dir = ?2,
+                file_name_template = ?3,
+                zip_password = COALESCE(?4, zip_password),
+                retention_years = ?5,
+                contact_template = ?6,
+                updated_at = datetime('now')",
+            params![
+                organization_id,
+                data.output_dir,
+                data.file_name_template,
+                data.zip_password.as_ref().map(|p| p.expose()),
+                data.retention_years,
+                data.contact_template,
+            ],
+        )?;
+
+        Self::get(conn, organization_id)
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// A participant moving from one organization to another on a given day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberTransfer {
+    pub id: i64,
+    pub from_member_id: i64,
+    pub from_organization_id: i64,
+    pub from_organization_name: String,
+    pub to_member_id: i64,
+    pub to_organization_id: i64,
+    pub to_organization_name: String,
+    pub transferred_on: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferMember {
+    pub member_id: i64,
+    /// The organization the member moves to.
+    pub organization_id: i64,
+    /// Last day of the membership being closed.
+    pub transferred_on: NaiveDate,
+}
+
+const SELECT_SQL: &str = "SELECT t.id, t.from_member_id, fm.organization_id, fo.name,
+    t.to_member_id, tm.organization_id, tor.name, t.transferred_on, t.created_at
+    FROM member_transfers t
+    JOIN members fm ON fm.id = t.from_member_id
+    JOIN organizations fo ON fo.id = fm.organization_id
+    JOIN members tm ON tm.id = t.to_member_id
+    JOIN organizations tor ON tor.id = tm.organization_id";
+
+fn row_to_transfer(row: &rusqlite::Row) -> rusqlite::Result<MemberTransfer> {
+    Ok(MemberTransfer {
+        id: row.get(0)?,
+        from_member_id: row.get(1)?,
+        from_organization_id: row.get(2)?,
+        from_organization_name: row.get(3)?,
+        to_member_id: row.get(4)?,
+        to_organization_id: row.get(5)?,
+        to_organization_name: row.get(6)?,
+        transferred_on: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+impl MemberTransfer {
+    pub fn insert(
+        conn: &Connection,
+        from_member_id: i64,
+        to_member_id: i64,
+        transferred_on: NaiveDate,
+    ) -> Result<MemberTransfer, AppError> {
+        conn.execute(
+            "INSERT INTO member_transfers (from_member_id, to_member_id, transferred_on)
+             VALUES (?1, ?2, ?3)",
+            params![from_member_id, to_member_id, transferred_on.to_string()],
+        )?;
+        let id = conn.last_insert_rowid();
+        let sql = format!("{} WHERE t.id = ?1", SELECT_SQL);
+        Ok(conn.query_row(&sql, params![id], row_to_transfer)?)
+    }
+
+    /// Point the transfers of `from_member_id` at `to_member_id`, dropping
+    /// any that would then link the member with itself.
+    pub fn reassign(
+        conn: &Connection,
+        from_member_id: i64,
+        to_member_id: i64,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE member_transfers SET from_member_id = ?2 WHERE from_member_id = ?1",
+            params![from_member_id, to_member_id],
+        )?;
+        conn.execute(
+            "UPDATE member_transfers SET to_member_id = ?2 WHERE to_member_id = ?1",
+            params![from_member_id, to_member_id],
+        )?;
+        conn.execute(
+            "DELETE FROM member_transfers WHERE from_member_id = to_member_id",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Transfers into or out of a membership, oldest first.
+    pub fn list_for_member(
+        conn: &Connection,
+        member_id: i64,
+    ) -> Result<Vec<MemberTransfer>, AppError> {
+        let sql = format!(
+            "{} WHERE t.from_member_id = ?1 OR t.to_member_id = ?1
+             ORDER BY t.transferred_on, t.id",
+            SELECT_SQL
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![member_id], row_to_transfer)?;
+
+        let mut transfers = Vec::new();
+        for row in rows {
+            transfers.push(row?);
+        }
+        Ok(transfers)
+    }
+}
@@ -0,0 +1,103 @@
+//! Local archive of every generated ZIP, with checksums to detect files that
+//! were moved, deleted or altered afterwards.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::db::pool::DbPool;
+use crate::error::AppError;
+use crate::models::generation::Generation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveStatus {
+    Ok,
+    Missing,
+    Modified,
+    /// Generated before checksums were recorded.
+    Unchecked,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveCheck {
+    pub generation_id: i64,
+    pub period_year: i32,
+    pub period_month: i32,
+    pub file_path: String,
+    pub status: ArchiveStatus,
+}
+
+/// Hex-encoded SHA-256 of the archive contents.
+pub fn checksum(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Store a copy under `<archive_dir>/<organization_id>/`, never overwriting an
+/// earlier file. Returns the absolute path of the stored copy.
+pub(crate) fn store(
+    archive_dir: &Path,
+    organization_id: i64,
+    file_name: &str,
+    bytes: &[u8],
+) -> Result<PathBuf, AppError> {
+    let dir = archive_dir.join(organization_id.to_string());
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+
+    let mut path = dir.join(format!("{}_{}", stamp, file_name));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}_{}_{}", stamp, n, file_name));
+    }
+
+    write_file(&path, bytes)
+}
+
+/// Write `bytes` to `path`, creating parent directories. Returns the absolute path.
+pub(crate) fn write_file(path: &Path, bytes: &[u8]) -> Result<PathBuf, AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, bytes)?;
+
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// Archived bytes of a generation, if the file is still there and unchanged.
+pub(crate) fn read_verified(file_path: &str, expected: &str) -> Option<Vec<u8>> {
+    if file_path.is_empty() || expected.is_empty() {
+        return None;
+    }
+    let bytes = std::fs::read(file_path).ok()?;
+    (checksum(&bytes) == expected).then_some(bytes)
+}
+
+/// Check every archived file of an organization against its stored checksum.
+pub fn verify(pool: &DbPool, organization_id: i64) -> Result<Vec<ArchiveCheck>, AppError> {
+    let conn = pool.get()?;
+    let generations = Generation::list(&conn, organization_id)?;
+
+    Ok(generations
+        .into_iter()
+        .map(|g| {
+            let status = match std::fs::read(&g.file_path) {
+                Err(_) => ArchiveStatus::Missing,
+                Ok(_) if g.checksum.is_empty() => ArchiveStatus::Unchecked,
+                Ok(bytes) if checksum(&bytes) == g.checksum => ArchiveStatus::Ok,
+                Ok(_) => ArchiveStatus::Modified,
+            };
+            ArchiveCheck {
+                generation_id: g.id,
+                period_year: g.period_year,
+                period_month: g.period_month,
+                file_path: g.file_path,
+                status,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,11 @@
+use crate::db::pool::DbPool;
+use crate::error::AppError;
+use crate::models::audit::AuditEntry;
+use crate::models::organization::Organization;
+
+/// Audit log of an organization, newest entry first.
+pub fn list(pool: &DbPool, organization_id: i64) -> Result<Vec<AuditEntry>, AppError> {
+    let conn = pool.get()?;
+    Organization::get(&conn, organization_id)?;
+    AuditEntry::list(&conn, organization_id)
+}
@@ -0,0 +1,214 @@
+//! Month-end run over many organizations: prefill, review, generate and write
+//! each ZIP into one output directory.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::db::pool::DbPool;
+use crate::domain::codes::MemberStatus;
+use crate::domain::money::Money;
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
+use crate::models::contribution::{Contribution, ContributionWithMember};
+use crate::models::organization::Organization;
+use crate::services::prefill::{self, PrefillRequest};
+use crate::services::{generations, settings};
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    /// Organizations to process; `None` means all of them.
+    pub organization_ids: Option<Vec<i64>>,
+    pub year: i32,
+    pub month: i32,
+    /// Common directory for all files; `None` uses each organization's
+    /// configured output directory.
+    pub output_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchStatus {
+    Success,
+    Warning,
+    Failure,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub current: usize,
+    pub total: usize,
+    pub organization_id: i64,
+    pub organization_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItem {
+    pub organization_id: i64,
+    pub organization_name: String,
+    pub status: BatchStatus,
+    pub prefilled: i64,
+    pub generation_id: Option<i64>,
+    pub file_path: Option<String>,
+    pub member_count: i32,
+    pub total_employee_basic: Money,
+    pub total_employer_basic: Money,
+    pub messages: Vec<Message>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub items: Vec<BatchItem>,
+    pub succeeded: usize,
+    pub warnings: usize,
+    pub failed: usize,
+}
+
+/// Run the batch. A failure for one organization is recorded in its summary
+/// row and does not stop the others. `on_progress` is called before each
+/// organization is processed.
+pub fn generate_all(
+    pool: &DbPool,
+    request: &BatchRequest,
+    mut on_progress: impl FnMut(&BatchProgress),
+) -> Result<BatchSummary, AppError> {
+    let output_dir = request.output_dir.as_deref().map(Path::new);
+    if let Some(dir) = output_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let orgs = select_organizations(pool, request.organization_ids.as_deref())?;
+    let total = orgs.len();
+
+    let mut items = Vec::with_capacity(total);
+    for (i, org) in orgs.into_iter().enumerate() {
+        on_progress(&BatchProgress {
+            current: i + 1,
+            total,
+            organization_id: org.id,
+            organization_name: org.name.clone(),
+        });
+
+        let item = match process(pool, &org, request.year, request.month, output_dir) {
+            Ok(item) => item,
+            Err(e) => {
+                log::warn!("Batch generation failed for organization {}: {}", org.id, e);
+                BatchItem {
+                    organization_id: org.id,
+                    organization_name: org.name,
+                    status: BatchStatus::Failure,
+                    prefilled: 0,
+                    generation_id: None,
+                    file_path: None,
+                    member_count: 0,
+                    total_employee_basic: Money::ZERO,
+                    total_employer_basic: Money::ZERO,
+                    messages: vec![e.message()],
+                }
+            }
+        };
+        items.push(item);
+    }
+
+    let count = |status| items.iter().filter(|i| i.status == status).count();
+    Ok(BatchSummary {
+        succeeded: count(BatchStatus::Success),
+        warnings: count(BatchStatus::Warning),
+        failed: count(BatchStatus::Failure),
+        items,
+    })
+}
+
+fn select_organizations(pool: &DbPool, ids: Option<&[i64]>) -> Result<Vec<Organization>, AppError> {
+    let conn = pool.get()?;
+    let all = Organization::list(&conn)?;
+    Ok(match ids {
+        Some(ids) => all.into_iter().filter(|o| ids.contains(&o.id)).collect(),
+        None => all,
+    })
+}
+
+fn process(
+    pool: &DbPool,
+    org: &Organization,
+    year: i32,
+    month: i32,
+    output_dir: Option<&Path>,
+) -> Result<BatchItem, AppError> {
+    let mut conn = pool.get()?;
+    // A failed generation rolls the prefill back with it
+    let tx = conn.transaction()?;
+    let request = PrefillRequest::copy_latest(org.id, Period::new(year, month)?);
+    let prefilled = prefill::apply_in(&tx, &request)?.created as i64;
+
+    let rows = Contribution::list_for_period(&tx, org.id, year, month)?;
+    let mut messages = review(&rows);
+    if prefilled > 0 {
+        messages.push(Message::new("batch.prefilled").with_param("count", prefilled));
+    }
+
+    let file_path = output_path(&tx, org, year, month, output_dir)?;
+    let result =
+        generations::generate_in(&tx, org.id, year, month, Some(&file_path), None)?.commit(tx)?;
+
+    let status = if messages.is_empty() {
+        BatchStatus::Success
+    } else {
+        BatchStatus::Warning
+    };
+
+    Ok(BatchItem {
+        organization_id: org.id,
+        organization_name: org.name.clone(),
+        status,
+        prefilled,
+        generation_id: Some(result.generation.id),
+        file_path: result.output_path,
+        member_count: result.member_count,
+        total_employee_basic: result.total_employee_basic,
+        total_employer_basic: result.total_employer_basic,
+        messages,
+    })
+}
+
+/// Collect warnings worth a second look before the file goes out.
+fn review(rows: &[ContributionWithMember]) -> Vec<Message> {
+    let mut warnings = Vec::new();
+
+    for c in rows {
+        let who = format!("{} {} ({})", c.first_name, c.last_name, c.pesel);
+        let warning = |key| Message::new(key).with_param("member", &who);
+        if c.member_status != MemberStatus::Active {
+            warnings.push(warning("batch.member_inactive"));
+        }
+        let amounts = [
+            c.employee_basic,
+            c.employee_additional,
+            c.employer_basic,
+            c.employer_additional,
+        ];
+        if amounts.iter().all(|amount| amount.is_zero()) {
+            warnings.push(warning("batch.all_zero"));
+        }
+    }
+
+    warnings
+}
+
+/// Target file inside the batch directory, or the organization's own output
+/// directory, named by its file name template.
+fn output_path(
+    conn: &Connection,
+    org: &Organization,
+    year: i32,
+    month: i32,
+    output_dir: Option<&Path>,
+) -> Result<PathBuf, AppError> {
+    let file_name = settings::file_name(conn, org, year, month)?;
+    let dir = match output_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => settings::output_dir(conn, org.id)?
+            .ok_or_else(|| AppError::Validation("batch.no_output_dir".into()))?,
+    };
+    Ok(dir.join(file_name))
+}
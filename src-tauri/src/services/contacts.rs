@@ -0,0 +1,78 @@
+//! Contact persons of an organization. The first one added becomes the
+//! default, and deleting the default hands it on to another contact, so an
+//! organization with contacts always has one for the generated files.
+
+use crate::db::pool::DbPool;
+use crate::error::{AppError, Message};
+use crate::models::contact::{ContactPerson, SaveContactPerson};
+use crate::models::organization::Organization;
+use crate::validation::{email, phone};
+
+pub fn list(pool: &DbPool, organization_id: i64) -> Result<Vec<ContactPerson>, AppError> {
+    let conn = pool.get()?;
+    Organization::get(&conn, organization_id)?;
+    ContactPerson::list(&conn, organization_id)
+}
+
+pub fn create(
+    pool: &DbPool,
+    organization_id: i64,
+    data: &SaveContactPerson,
+) -> Result<ContactPerson, AppError> {
+    validate(data)?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    Organization::get(&tx, organization_id)?;
+    let mut contact = ContactPerson::create(&tx, organization_id, data)?;
+    if ContactPerson::get_default(&tx, organization_id)?.is_none() {
+        contact = ContactPerson::set_default(&tx, contact.id)?;
+    }
+    tx.commit()?;
+    Ok(contact)
+}
+
+pub fn update(pool: &DbPool, id: i64, data: &SaveContactPerson) -> Result<ContactPerson, AppError> {
+    validate(data)?;
+
+    let conn = pool.get()?;
+    ContactPerson::update(&conn, id, data)
+}
+
+pub fn set_default(pool: &DbPool, id: i64) -> Result<ContactPerson, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let contact = ContactPerson::set_default(&tx, id)?;
+    tx.commit()?;
+    Ok(contact)
+}
+
+pub fn delete(pool: &DbPool, id: i64) -> Result<(), AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let contact = ContactPerson::get(&tx, id)?;
+    ContactPerson::delete(&tx, id)?;
+    if contact.is_default {
+        if let Some(next) = ContactPerson::list(&tx, contact.organization_id)?.first() {
+            ContactPerson::set_default(&tx, next.id)?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Phone and e-mail are optional, but checked when given.
+fn validate(data: &SaveContactPerson) -> Result<(), AppError> {
+    if data.name.trim().is_empty() {
+        return Err(AppError::Validation(
+            Message::new("contact.name_required").with_field("name"),
+        ));
+    }
+    if !data.phone.is_empty() {
+        phone::validate(&data.phone)?;
+    }
+    if !data.email.is_empty() {
+        email::validate(&data.email)?;
+    }
+    Ok(())
+}
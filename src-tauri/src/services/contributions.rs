@@ -0,0 +1,113 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::db::pool::DbPool;
+use crate::domain::period::Period;
+use crate::error::AppError;
+use crate::models::contribution::{Contribution, ContributionWithMember, UpsertContribution};
+use crate::models::journal::RowKey;
+use crate::models::member::{Member, MemberFilter};
+use crate::services::journal::Tracker;
+
+/// Why one row of a batch was rejected; `index` is its position in the batch.
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub index: usize,
+    pub member_id: i64,
+    pub error: AppError,
+}
+
+/// Either every row was saved, or none was and `errors` says why.
+#[derive(Debug, Serialize)]
+pub struct UpsertBatchResult {
+    pub saved: usize,
+    pub errors: Vec<RowError>,
+}
+
+pub fn list_for_period(
+    pool: &DbPool,
+    organization_id: i64,
+    year: i32,
+    month: i32,
+) -> Result<Vec<ContributionWithMember>, AppError> {
+    let conn = pool.get()?;
+    Contribution::list_for_period(&conn, organization_id, year, month)
+}
+
+pub fn list_for_period_filtered(
+    pool: &DbPool,
+    organization_id: i64,
+    year: i32,
+    month: i32,
+    filter: &MemberFilter,
+) -> Result<Vec<ContributionWithMember>, AppError> {
+    let conn = pool.get()?;
+    Contribution::list_for_period_filtered(&conn, organization_id, year, month, filter)
+}
+
+pub fn upsert(pool: &DbPool, data: &UpsertContribution) -> Result<(), AppError> {
+    let change = data.validate()?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let member = Member::get(&tx, change.member_id)?;
+    let mut tracker = Tracker::new();
+    tracker.track(&tx, RowKey::contribution(change.member_id, change.period))?;
+    Contribution::upsert(&tx, &change)?;
+    tracker.record(&tx, member.organization_id, "contribution.upsert")?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Validate every row first and save them all in one transaction, or save
+/// nothing if any row is invalid.
+pub fn upsert_batch(
+    pool: &DbPool,
+    rows: &[UpsertContribution],
+) -> Result<UpsertBatchResult, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+
+    let mut changes = Vec::with_capacity(rows.len());
+    let mut errors = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let checked = row
+            .validate()
+            .and_then(|change| Ok((change, Member::get(&tx, row.member_id)?.organization_id)));
+        match checked {
+            Ok(change) => changes.push(change),
+            Err(error) => errors.push(RowError {
+                index,
+                member_id: row.member_id,
+                error,
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(UpsertBatchResult { saved: 0, errors });
+    }
+
+    // One journal entry per organization, normally just the one being edited
+    let mut trackers: BTreeMap<i64, Tracker> = BTreeMap::new();
+    for (change, organization_id) in &changes {
+        trackers
+            .entry(*organization_id)
+            .or_default()
+            .track(&tx, RowKey::contribution(change.member_id, change.period))?;
+        Contribution::upsert(&tx, change)?;
+    }
+    for (organization_id, tracker) in trackers {
+        tracker.record(&tx, organization_id, "contribution.batch")?;
+    }
+    tx.commit()?;
+
+    Ok(UpsertBatchResult {
+        saved: changes.len(),
+        errors,
+    })
+}
+
+pub fn available_periods(pool: &DbPool, organization_id: i64) -> Result<Vec<Period>, AppError> {
+    let conn = pool.get()?;
+    Contribution::get_available_periods(&conn, organization_id)
+}
@@ -0,0 +1,314 @@
+use rusqlite::{Connection, Transaction};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::db::pool::DbPool;
+use crate::domain::money::Money;
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
+use crate::generator;
+use crate::models::contribution::{Contribution, ContributionWithMember};
+use crate::models::generation::{Generation, GenerationWithSnapshot, NewGeneration};
+use crate::models::journal::Journal;
+use crate::models::organization::Organization;
+use crate::models::settings::OrganizationSettings;
+use crate::secret::Password;
+use crate::services::{archive, settings};
+
+#[derive(Debug, Serialize)]
+pub struct GenerateResult {
+    pub generation: Generation,
+    /// Where the delivered copy was written, if anywhere besides the archive.
+    pub output_path: Option<String>,
+    pub total_employee_basic: Money,
+    pub total_employee_additional: Money,
+    pub total_employer_basic: Money,
+    pub total_employer_additional: Money,
+    pub member_count: i32,
+}
+
+/// Sums of all four contribution amounts for a set of rows.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Totals {
+    pub employee_basic: Money,
+    pub employee_additional: Money,
+    pub employer_basic: Money,
+    pub employer_additional: Money,
+}
+
+impl Totals {
+    /// Fails with `money.too_large` when a sum does not fit.
+    pub fn of(contributions: &[ContributionWithMember]) -> Result<Totals, AppError> {
+        let mut totals = Totals::default();
+        for c in contributions {
+            totals = Totals {
+                employee_basic: totals
+                    .employee_basic
+                    .checked_add(c.employee_basic, "employee_basic")?,
+                employee_additional: totals
+                    .employee_additional
+                    .checked_add(c.employee_additional, "employee_additional")?,
+                employer_basic: totals
+                    .employer_basic
+                    .checked_add(c.employer_basic, "employer_basic")?,
+                employer_additional: totals
+                    .employer_additional
+                    .checked_add(c.employer_additional, "employer_additional")?,
+            };
+        }
+        Ok(totals)
+    }
+}
+
+/// Generate the ZIP for a period and store it in the archive, which
+/// `generations.file_path` points to. A copy is also written to `output_path`,
+/// or to the organization's configured output directory when none is given.
+///
+/// The archive is AES-encrypted with `password`, or with the organization's
+/// configured password when none is given.
+pub fn generate(
+    pool: &DbPool,
+    organization_id: i64,
+    year: i32,
+    month: i32,
+    output_path: Option<&Path>,
+    password: Option<&Password>,
+) -> Result<GenerateResult, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    generate_in(&tx, organization_id, year, month, output_path, password)?.commit(tx)
+}
+
+/// A generation saved in a transaction that is not committed yet. The files
+/// it wrote go unless the transaction is committed through `commit`.
+pub(crate) struct PendingGeneration {
+    result: GenerateResult,
+    written: Vec<PathBuf>,
+}
+
+impl PendingGeneration {
+    pub(crate) fn commit(self, tx: Transaction) -> Result<GenerateResult, AppError> {
+        discard_on_error(tx.commit().map_err(AppError::from), &self.written)?;
+        Ok(self.result)
+    }
+}
+
+/// `generate` within `tx`, so other changes to the period can be saved or
+/// rolled back together with the generation.
+pub(crate) fn generate_in(
+    tx: &Transaction,
+    organization_id: i64,
+    year: i32,
+    month: i32,
+    output_path: Option<&Path>,
+    password: Option<&Password>,
+) -> Result<PendingGeneration, AppError> {
+    let period = Period::new(year, month)?;
+
+    let org = Organization::get(tx, organization_id)?;
+    let contributions = Contribution::list_for_period(tx, organization_id, year, month)?;
+
+    if contributions.is_empty() {
+        return Err(AppError::Generation("generation.empty".into()));
+    }
+
+    let totals = Totals::of(&contributions)?;
+    let member_count = contributions.len() as i32;
+
+    let archive_dir = settings::archive_dir(tx)?;
+    let file_name = settings::file_name(tx, &org, year, month)?;
+    let contact = settings::contact(tx, organization_id)?;
+    let target = match output_path {
+        Some(path) => Some(path.to_path_buf()),
+        None => settings::output_dir(tx, organization_id)?.map(|dir| dir.join(&file_name)),
+    };
+    let password = match password {
+        Some(password) => Some(password.clone()),
+        None => OrganizationSettings::zip_password(tx, organization_id)?,
+    };
+
+    // Build files
+    let xml = generator::xml::build(&org, &contact, &contributions, period);
+    let csv = generator::csv::build(&contributions, period);
+
+    let generated = build_zip(&xml, &csv, password.as_ref())?;
+    let archived = archive::store(
+        &archive_dir,
+        organization_id,
+        &file_name,
+        &generated.zip_bytes,
+    )?;
+    let output_path = match target {
+        Some(path) => Some(discard_on_error(
+            archive::write_file(&path, &generated.zip_bytes),
+            std::slice::from_ref(&archived),
+        )?),
+        None => None,
+    };
+    let written: Vec<PathBuf> = std::iter::once(archived.clone())
+        .chain(output_path.clone())
+        .collect();
+
+    // Build snapshot
+    let snapshot = serde_json::json!({
+        "organization": org,
+        "contact": contact,
+        "contributions": contributions,
+        "period": period,
+    });
+
+    // Save generation record; the files written above go if it cannot be saved
+    let generation = discard_on_error(
+        record(
+            tx,
+            &NewGeneration {
+                organization_id,
+                period_year: year,
+                period_month: month,
+                snapshot_json: snapshot.to_string(),
+                file_path: archived.to_string_lossy().into_owned(),
+                checksum: archive::checksum(&generated.zip_bytes),
+                encrypted: password.is_some(),
+                total_employee_basic: totals.employee_basic,
+                total_employer_basic: totals.employer_basic,
+                member_count,
+            },
+        ),
+        &written,
+    )?;
+
+    Ok(PendingGeneration {
+        result: GenerateResult {
+            generation,
+            output_path: output_path.map(|p| p.to_string_lossy().into_owned()),
+            total_employee_basic: totals.employee_basic,
+            total_employee_additional: totals.employee_additional,
+            total_employer_basic: totals.employer_basic,
+            total_employer_additional: totals.employer_additional,
+            member_count,
+        },
+        written,
+    })
+}
+
+/// Save the generation and cut off the journal.
+fn record(conn: &Connection, generation: &NewGeneration) -> Result<Generation, AppError> {
+    let generation = Generation::insert(conn, generation)?;
+    // Edits before the generation are now part of a submitted file
+    Journal::cut_off(conn, generation.organization_id)?;
+    Ok(generation)
+}
+
+/// Remove the files of a generation that failed after they were written.
+fn discard_on_error<T>(result: Result<T, AppError>, written: &[PathBuf]) -> Result<T, AppError> {
+    if result.is_err() {
+        for path in written {
+            if let Err(e) = std::fs::remove_file(path) {
+                log::warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+    }
+    result
+}
+
+pub fn list(pool: &DbPool, organization_id: i64) -> Result<Vec<Generation>, AppError> {
+    let conn = pool.get()?;
+    Generation::list(&conn, organization_id)
+}
+
+pub fn get(pool: &DbPool, id: i64) -> Result<GenerationWithSnapshot, AppError> {
+    let conn = pool.get()?;
+    Generation::get_with_snapshot(&conn, id)
+}
+
+/// Write a past generation to `output_path`. The archived file is copied when
+/// it is intact; otherwise the ZIP is rebuilt from the stored snapshot, which
+/// for an encrypted generation needs `password` or a configured one.
+pub fn export(
+    pool: &DbPool,
+    id: i64,
+    output_path: &Path,
+    password: Option<&Password>,
+) -> Result<GenerateResult, AppError> {
+    let conn = pool.get()?;
+    let gen = Generation::get_with_snapshot(&conn, id)?;
+
+    let snapshot: serde_json::Value = serde_json::from_str(&gen.snapshot_json)
+        .map_err(|e| AppError::Other(Message::new("snapshot.invalid").with_param("details", e)))?;
+
+    let org: Organization =
+        serde_json::from_value(snapshot["organization"].clone()).map_err(|e| {
+            AppError::Other(Message::new("snapshot.organization_invalid").with_param("details", e))
+        })?;
+
+    // Snapshots from before contact persons kept the contact on the organization
+    let contact = snapshot["contact"]
+        .as_str()
+        .or_else(|| snapshot["organization"]["contact_person"].as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let contributions: Vec<ContributionWithMember> =
+        serde_json::from_value(snapshot["contributions"].clone()).map_err(|e| {
+            AppError::Other(Message::new("snapshot.contributions_invalid").with_param("details", e))
+        })?;
+
+    let zip_bytes = match archive::read_verified(&gen.file_path, &gen.checksum) {
+        Some(bytes) => bytes,
+        None => {
+            log::warn!(
+                "Archived file for generation {} unavailable, rebuilding",
+                gen.id
+            );
+            let password = match (gen.encrypted, password) {
+                (false, _) => None,
+                (true, Some(password)) => Some(password.clone()),
+                (true, None) => Some(
+                    OrganizationSettings::zip_password(&conn, gen.organization_id)?.ok_or_else(
+                        || AppError::Validation("generation.password_required".into()),
+                    )?,
+                ),
+            };
+            let period = Period::new(gen.period_year, gen.period_month)?;
+            let xml = generator::xml::build(&org, &contact, &contributions, period);
+            let csv = generator::csv::build(&contributions, period);
+            build_zip(&xml, &csv, password.as_ref())?.zip_bytes
+        }
+    };
+    let output_path = archive::write_file(output_path, &zip_bytes)?;
+
+    let totals = Totals::of(&contributions)?;
+
+    Ok(GenerateResult {
+        generation: Generation {
+            id: gen.id,
+            organization_id: gen.organization_id,
+            period_year: gen.period_year,
+            period_month: gen.period_month,
+            generated_at: gen.generated_at,
+            file_path: gen.file_path,
+            checksum: gen.checksum,
+            encrypted: gen.encrypted,
+            total_employee_basic: gen.total_employee_basic,
+            total_employer_basic: gen.total_employer_basic,
+            member_count: gen.member_count,
+        },
+        output_path: Some(output_path.to_string_lossy().into_owned()),
+        total_employee_basic: gen.total_employee_basic,
+        total_employee_additional: totals.employee_additional,
+        total_employer_basic: gen.total_employer_basic,
+        total_employer_additional: totals.employer_additional,
+        member_count: gen.member_count,
+    })
+}
+
+fn build_zip(
+    xml: &str,
+    csv: &str,
+    password: Option<&Password>,
+) -> Result<generator::zip::GeneratedFiles, AppError> {
+    match password {
+        Some(password) => generator::zip::build_encrypted(xml, csv, password),
+        None => generator::zip::build(xml, csv),
+    }
+}
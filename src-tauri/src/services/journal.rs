@@ -0,0 +1,121 @@
+//! Undo and redo of contribution and member edits, replayed from the
+//! operation journal. Generating a period or anonymizing members cuts the
+//! journal off, so neither a generated file nor removed personal data can be
+//! brought back by undo.
+
+use rusqlite::Connection;
+
+use crate::db::pool::DbPool;
+use crate::error::{AppError, Message};
+use crate::models::journal::{Journal, JournalEntry, Row, RowChange, RowKey};
+
+/// Rows an operation is about to change, read before it runs.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    before: Vec<(RowKey, Option<Row>)>,
+}
+
+impl Tracker {
+    pub(crate) fn new() -> Tracker {
+        Tracker::default()
+    }
+
+    pub(crate) fn track(&mut self, conn: &Connection, key: RowKey) -> Result<(), AppError> {
+        if !self.before.iter().any(|(k, _)| *k == key) {
+            let row = key.read(conn)?;
+            self.before.push((key, row));
+        }
+        Ok(())
+    }
+
+    /// A row the operation has just created.
+    pub(crate) fn track_created(&mut self, key: RowKey) {
+        self.before.push((key, None));
+    }
+
+    /// Read the rows again and journal those that changed.
+    pub(crate) fn record(
+        self,
+        conn: &Connection,
+        organization_id: i64,
+        action: &str,
+    ) -> Result<(), AppError> {
+        let mut changes = Vec::new();
+        for (key, before) in self.before {
+            let after = key.read(conn)?;
+            if after != before {
+                changes.push(RowChange { key, before, after });
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+        Journal::record(conn, organization_id, action, &changes)
+    }
+}
+
+pub fn list(pool: &DbPool, organization_id: i64) -> Result<Vec<JournalEntry>, AppError> {
+    let conn = pool.get()?;
+    Journal::list(&conn, organization_id)
+}
+
+/// Revert the organization's most recent operation. Returns `None` when
+/// there is nothing to undo.
+pub fn undo(pool: &DbPool, organization_id: i64) -> Result<Option<JournalEntry>, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let Some(mut entry) = Journal::last_done(&tx, organization_id)? else {
+        return Ok(None);
+    };
+
+    for change in entry.changes.iter().rev() {
+        replay(
+            &tx,
+            &change.key,
+            change.after.as_ref(),
+            change.before.as_ref(),
+        )?;
+    }
+    Journal::set_undone(&tx, entry.id, true)?;
+    tx.commit()?;
+
+    entry.undone = true;
+    Ok(Some(entry))
+}
+
+/// Apply again the operation undone last. Returns `None` when there is
+/// nothing to redo.
+pub fn redo(pool: &DbPool, organization_id: i64) -> Result<Option<JournalEntry>, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let Some(mut entry) = Journal::first_undone(&tx, organization_id)? else {
+        return Ok(None);
+    };
+
+    for change in &entry.changes {
+        replay(
+            &tx,
+            &change.key,
+            change.before.as_ref(),
+            change.after.as_ref(),
+        )?;
+    }
+    Journal::set_undone(&tx, entry.id, false)?;
+    tx.commit()?;
+
+    entry.undone = false;
+    Ok(Some(entry))
+}
+
+/// Move a row from `from` to `to`, refusing if it changed in the meantime.
+fn replay(
+    conn: &Connection,
+    key: &RowKey,
+    from: Option<&Row>,
+    to: Option<&Row>,
+) -> Result<(), AppError> {
+    if key.read(conn)?.as_ref() != from {
+        return Err(AppError::Validation(Message::new("journal.conflict")));
+    }
+    key.write(conn, to)
+}
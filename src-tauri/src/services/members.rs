@@ -0,0 +1,308 @@
+use serde::Serialize;
+
+use crate::db::pool::DbPool;
+use crate::domain::codes::{Gender, MemberStatus};
+use crate::domain::identifiers::Pesel;
+use crate::error::{AppError, Message};
+use crate::models::audit::AuditEntry;
+use crate::models::details_history::DetailsChange;
+use crate::models::journal::{Journal, RowKey};
+use crate::models::member::{CreateMember, Member, MemberPage, MemberQuery, UpdateMember};
+use crate::models::organization::Organization;
+use crate::models::pesel_history::{ChangePesel, PeselChange};
+use crate::models::transfer::{MemberTransfer, TransferMember};
+use crate::services::journal::Tracker;
+use crate::validation::pesel;
+
+pub const PESEL_CHANGE_AUDIT_ACTION: &str = "member_pesel_change";
+
+#[derive(Debug, Serialize)]
+pub struct PeselValidationResult {
+    pub valid: bool,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<Gender>,
+    pub error: Option<String>,
+}
+
+pub fn list(pool: &DbPool, organization_id: i64) -> Result<Vec<Member>, AppError> {
+    let conn = pool.get()?;
+    Member::list(&conn, organization_id)
+}
+
+pub fn query(
+    pool: &DbPool,
+    organization_id: i64,
+    query: &MemberQuery,
+) -> Result<MemberPage, AppError> {
+    let conn = pool.get()?;
+    Member::query(&conn, organization_id, query)
+}
+
+pub fn get(pool: &DbPool, id: i64) -> Result<Member, AppError> {
+    let conn = pool.get()?;
+    Member::get(&conn, id)
+}
+
+pub fn create(pool: &DbPool, data: CreateMember) -> Result<Member, AppError> {
+    // Validate PESEL and auto-derive DOB/gender
+    let info = pesel::validate(&data.pesel)?;
+    validate_names(&data.first_name, &data.last_name)?;
+
+    let mut data = data;
+    data.date_of_birth = info.date_of_birth;
+    data.gender = info.gender.to_string();
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    if Member::find_by_pesel(&tx, data.organization_id, &data.pesel)?
+        .is_some_and(|m| m.archived_at.is_some())
+    {
+        return Err(AppError::Validation(
+            Message::new("member.archived_exists").with_field("pesel"),
+        ));
+    }
+    let member = Member::create(&tx, &data)?;
+    let mut tracker = Tracker::new();
+    tracker.track_created(RowKey::Members { id: member.id });
+    tracker.record(&tx, member.organization_id, "member.create")?;
+    tx.commit()?;
+    Ok(member)
+}
+
+/// Update a member. With `effective_from`, changed names, citizenship or
+/// document are dated: the previous values go to the history and still apply
+/// to earlier periods.
+pub fn update(pool: &DbPool, id: i64, data: &UpdateMember) -> Result<Member, AppError> {
+    validate_names(&data.first_name, &data.last_name)?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let current = Member::get(&tx, id)?;
+    let mut tracker = Tracker::new();
+    tracker.track(&tx, RowKey::Members { id })?;
+    if let Some(from) = data
+        .effective_from
+        .filter(|_| details_changed(&current, data))
+    {
+        if let Some(last) = DetailsChange::list_for_member(&tx, id)?.last() {
+            if from <= last.replaced_from() {
+                return Err(AppError::Validation(
+                    Message::new("member.details_effective_from")
+                        .with_field("effective_from")
+                        .with_param("period", last.replaced_from()),
+                ));
+            }
+        }
+        let history_id = DetailsChange::insert(&tx, &current, from)?;
+        tracker.track_created(RowKey::MemberDetailsHistory { id: history_id });
+    }
+    let member = Member::update(&tx, id, data)?;
+    tracker.record(&tx, current.organization_id, "member.update")?;
+    tx.commit()?;
+    Ok(member)
+}
+
+/// Earlier names, citizenship and documents of a member, oldest first.
+pub fn list_details_history(pool: &DbPool, id: i64) -> Result<Vec<DetailsChange>, AppError> {
+    let conn = pool.get()?;
+    Member::get(&conn, id)?;
+    DetailsChange::list_for_member(&conn, id)
+}
+
+/// Correct a member's PESEL, deriving gender and date of birth from the new
+/// one. The previous PESEL is kept in the history and still used for the
+/// periods before `effective_from`, so regenerating or correcting them
+/// reports the number submitted at the time.
+///
+/// Undo cannot reach across the change, so the organization's journal is
+/// cut off.
+pub fn change_pesel(pool: &DbPool, id: i64, data: &ChangePesel) -> Result<Member, AppError> {
+    let new_pesel = Pesel::parse(data.pesel.trim())?;
+    let info = new_pesel.info()?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let member = Member::get(&tx, id)?;
+    if member.anonymized_at.is_some() {
+        return Err(AppError::Validation(Message::new(
+            "member.pesel_anonymized",
+        )));
+    }
+    if member.pesel == new_pesel {
+        return Err(AppError::Validation(
+            Message::new("member.pesel_unchanged").with_field("pesel"),
+        ));
+    }
+    if Member::find_by_pesel(&tx, member.organization_id, new_pesel.as_str())?.is_some() {
+        return Err(AppError::Validation(
+            Message::new("member.pesel_exists").with_field("pesel"),
+        ));
+    }
+    if let Some(last) = PeselChange::list_for_member(&tx, id)?.last() {
+        if data.effective_from <= last.replaced_from() {
+            return Err(AppError::Validation(
+                Message::new("member.pesel_effective_from")
+                    .with_field("effective_from")
+                    .with_param("period", last.replaced_from()),
+            ));
+        }
+    }
+
+    let change = PeselChange::insert(
+        &tx,
+        id,
+        &member.pesel,
+        member.gender,
+        &member.date_of_birth,
+        data.effective_from,
+    )?;
+    let updated = Member::set_pesel(&tx, id, &new_pesel, info.gender, &info.date_of_birth)?;
+    // The numbers themselves stay out of the audit log
+    AuditEntry::insert(
+        &tx,
+        Some(member.organization_id),
+        PESEL_CHANGE_AUDIT_ACTION,
+        &serde_json::json!({
+            "member_id": id,
+            "history_id": change.id,
+            "effective_from": data.effective_from,
+        }),
+    )?;
+    Journal::cut_off(&tx, member.organization_id)?;
+    tx.commit()?;
+    Ok(updated)
+}
+
+/// Earlier PESELs of a member, oldest first.
+pub fn list_pesel_history(pool: &DbPool, id: i64) -> Result<Vec<PeselChange>, AppError> {
+    let conn = pool.get()?;
+    Member::get(&conn, id)?;
+    PeselChange::list_for_member(&conn, id)
+}
+
+/// Transfers into or out of a membership, oldest first.
+pub fn list_transfers(pool: &DbPool, member_id: i64) -> Result<Vec<MemberTransfer>, AppError> {
+    let conn = pool.get()?;
+    Member::get(&conn, member_id)?;
+    MemberTransfer::list_for_member(&conn, member_id)
+}
+
+/// Close a membership on the transfer day and open one for the same person
+/// in another organization, linking the two. A former membership of the
+/// person there is reopened rather than duplicated.
+///
+/// Transfers are not journaled; undoing an earlier edit of either membership
+/// reports a conflict instead of reverting the transfer.
+pub fn transfer(pool: &DbPool, data: &TransferMember) -> Result<MemberTransfer, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let source = Member::get(&tx, data.member_id)?;
+    if source.status != MemberStatus::Active {
+        return Err(AppError::Validation(Message::new(
+            "member.transfer_inactive",
+        )));
+    }
+    if source.organization_id == data.organization_id {
+        return Err(AppError::Validation(
+            Message::new("member.transfer_same_organization").with_field("organization_id"),
+        ));
+    }
+    Organization::get(&tx, data.organization_id)?;
+
+    let target_id = match Member::find_by_pesel(&tx, data.organization_id, source.pesel.as_str())? {
+        Some(existing) if existing.status == MemberStatus::Active => {
+            return Err(AppError::Validation(
+                Message::new("member.transfer_exists").with_field("organization_id"),
+            ));
+        }
+        Some(existing) => {
+            Member::reopen(&tx, existing.id, &source)?;
+            existing.id
+        }
+        None => {
+            let data = CreateMember {
+                organization_id: data.organization_id,
+                pesel: source.pesel.to_string(),
+                first_name: source.first_name.clone(),
+                last_name: source.last_name.clone(),
+                gender: source.gender.to_string(),
+                date_of_birth: source.date_of_birth.clone(),
+                citizenship: Some(source.citizenship.clone()),
+                second_name: Some(source.second_name.clone()),
+                doc_type: Some(source.doc_type.clone()),
+                doc_number: Some(source.doc_number.clone()),
+            };
+            Member::create(&tx, &data)?.id
+        }
+    };
+    Member::close(&tx, source.id, data.transferred_on)?;
+    let transfer = MemberTransfer::insert(&tx, source.id, target_id, data.transferred_on)?;
+    tx.commit()?;
+    Ok(transfer)
+}
+
+/// Move a member to the trash. Their contributions stay but are left out of
+/// lists and generations until the member is restored.
+pub fn archive(pool: &DbPool, id: i64) -> Result<(), AppError> {
+    set_archived(pool, id, true, "member.archive")
+}
+
+pub fn restore(pool: &DbPool, id: i64) -> Result<Member, AppError> {
+    set_archived(pool, id, false, "member.restore")?;
+    get(pool, id)
+}
+
+fn set_archived(pool: &DbPool, id: i64, archived: bool, action: &str) -> Result<(), AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let organization_id = Member::get(&tx, id)?.organization_id;
+
+    let mut tracker = Tracker::new();
+    tracker.track(&tx, RowKey::Members { id })?;
+    Member::set_archived(&tx, id, archived)?;
+    tracker.record(&tx, organization_id, action)?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn validate_pesel(pesel_str: &str) -> PeselValidationResult {
+    match pesel::validate(pesel_str) {
+        Ok(info) => PeselValidationResult {
+            valid: true,
+            date_of_birth: Some(info.date_of_birth),
+            gender: Some(info.gender),
+            error: None,
+        },
+        Err(e) => PeselValidationResult {
+            valid: false,
+            date_of_birth: None,
+            gender: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Whether the update changes any of the fields kept in the details history,
+/// compared as `Member::update` would store them.
+fn details_changed(member: &Member, data: &UpdateMember) -> bool {
+    member.first_name != data.first_name
+        || member.last_name != data.last_name
+        || member.second_name != data.second_name.as_deref().unwrap_or("")
+        || member.citizenship != data.citizenship.as_deref().unwrap_or("PL")
+        || member.doc_type != data.doc_type.as_deref().unwrap_or("")
+        || member.doc_number != data.doc_number.as_deref().unwrap_or("")
+}
+
+fn validate_names(first_name: &str, last_name: &str) -> Result<(), AppError> {
+    if first_name.trim().is_empty() {
+        return Err(AppError::Validation(
+            Message::new("member.first_name_required").with_field("first_name"),
+        ));
+    }
+    if last_name.trim().is_empty() {
+        return Err(AppError::Validation(
+            Message::new("member.last_name_required").with_field("last_name"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,299 @@
+//! Merging a duplicate member into the record kept for the same person.
+//! Contributions move to the kept member, periods both have are resolved as
+//! requested, and generation snapshots are updated to point at the kept
+//! member. The duplicate's PESEL and details history moves with its
+//! contributions, and the duplicate is then deleted. Members whose periods
+//! fall before a PESEL correction or a dated change of details of the other
+//! are not merged, as the history would then apply to rows filed under
+//! different data.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::db::pool::DbPool;
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
+use crate::models::audit::AuditEntry;
+use crate::models::contribution::{Contribution, ContributionAmounts, ContributionChange};
+use crate::models::details_history::DetailsChange;
+use crate::models::generation::Generation;
+use crate::models::journal::Journal;
+use crate::models::member::Member;
+use crate::models::pesel_history::PeselChange;
+use crate::models::transfer::MemberTransfer;
+
+pub const AUDIT_ACTION: &str = "member_merge";
+
+/// Which amounts win for a period both members have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClashResolution {
+    #[default]
+    KeepTarget,
+    KeepSource,
+    /// Add the amounts up, keeping the target's reduced-rate flag.
+    Sum,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClashChoice {
+    pub period: Period,
+    pub resolution: ClashResolution,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeRequest {
+    /// The duplicate, deleted once merged.
+    pub source_id: i64,
+    /// The member kept.
+    pub target_id: i64,
+    /// For clashing periods without a choice of their own.
+    #[serde(default)]
+    pub resolution: ClashResolution,
+    #[serde(default)]
+    pub choices: Vec<ClashChoice>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Clash {
+    pub period: Period,
+    pub source: ContributionAmounts,
+    pub target: ContributionAmounts,
+    pub resolution: ClashResolution,
+    /// What the kept member will have for the period.
+    pub merged: ContributionAmounts,
+}
+
+/// What `apply` would do, without changing anything.
+#[derive(Debug, Serialize)]
+pub struct MergePreview {
+    pub source: Member,
+    pub target: Member,
+    /// Periods only the duplicate has, moved as they are.
+    pub moved: Vec<Period>,
+    pub clashes: Vec<Clash>,
+    pub generation_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeResult {
+    pub moved: usize,
+    pub clashes: usize,
+    pub generations_updated: usize,
+    pub audit: AuditEntry,
+}
+
+pub fn preview(pool: &DbPool, request: &MergeRequest) -> Result<MergePreview, AppError> {
+    let conn = pool.get()?;
+    plan(&conn, request)
+}
+
+/// Merge in one transaction and record it in the audit log. The
+/// organization's undo journal is cut off, since it may refer to the
+/// duplicate's rows.
+pub fn apply(pool: &DbPool, request: &MergeRequest) -> Result<MergeResult, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let plan = plan(&tx, request)?;
+    let (source, target) = (plan.source.id, plan.target.id);
+
+    for clash in &plan.clashes {
+        if clash.resolution != ClashResolution::KeepTarget {
+            Contribution::upsert(&tx, &change(target, clash.period, &clash.merged))?;
+        }
+        Contribution::delete(&tx, source, clash.period)?;
+    }
+    let moved = Contribution::reassign(&tx, source, target)?;
+
+    let snapshots = Generation::snapshots_mentioning(&tx, plan.target.organization_id, &[source])?;
+    for (id, mut snapshot) in snapshots {
+        if let Some(rows) = snapshot["contributions"].as_array_mut() {
+            for row in rows
+                .iter_mut()
+                .filter(|r| r["member_id"].as_i64() == Some(source))
+            {
+                row["member_id"] = target.into();
+            }
+        }
+        Generation::update_snapshot(&tx, id, &snapshot.to_string())?;
+    }
+
+    MemberTransfer::reassign(&tx, source, target)?;
+    PeselChange::reassign(&tx, source, target)?;
+    DetailsChange::reassign(&tx, source, target)?;
+    Member::delete(&tx, source)?;
+
+    // Ids, periods and amounts only, as for every audit entry
+    let audit = AuditEntry::insert(
+        &tx,
+        Some(plan.target.organization_id),
+        AUDIT_ACTION,
+        &serde_json::json!({
+            "source_id": source,
+            "target_id": target,
+            "moved": plan.moved,
+            "clashes": plan.clashes.iter().map(|c| serde_json::json!({
+                "period": c.period,
+                "resolution": c.resolution,
+                "source": c.source,
+                "target": c.target,
+            })).collect::<Vec<_>>(),
+            "generation_ids": plan.generation_ids,
+        }),
+    )?;
+    Journal::cut_off(&tx, plan.target.organization_id)?;
+    tx.commit()?;
+
+    Ok(MergeResult {
+        moved,
+        clashes: plan.clashes.len(),
+        generations_updated: plan.generation_ids.len(),
+        audit,
+    })
+}
+
+fn plan(conn: &Connection, request: &MergeRequest) -> Result<MergePreview, AppError> {
+    if request.source_id == request.target_id {
+        return Err(AppError::Validation(Message::new("merge.same_member")));
+    }
+    let source = Member::get(conn, request.source_id)?;
+    let target = Member::get(conn, request.target_id)?;
+    if source.organization_id != target.organization_id {
+        return Err(AppError::Validation(
+            Message::new("merge.other_organization").with_field("target_id"),
+        ));
+    }
+
+    let existing: HashMap<Period, ContributionAmounts> =
+        Contribution::list_for_member(conn, target.id)?
+            .iter()
+            .map(|c| (period_of(c.period_year, c.period_month), c.into()))
+            .collect();
+
+    let mut moved = Vec::new();
+    let mut clashes = Vec::new();
+    for contribution in Contribution::list_for_member(conn, source.id)? {
+        let period = period_of(contribution.period_year, contribution.period_month);
+        let Some(kept) = existing.get(&period) else {
+            moved.push(period);
+            continue;
+        };
+        let resolution = request
+            .choices
+            .iter()
+            .find(|c| c.period == period)
+            .map_or(request.resolution, |c| c.resolution);
+        let duplicate = ContributionAmounts::from(&contribution);
+        clashes.push(Clash {
+            period,
+            merged: resolve(resolution, &duplicate, kept)?,
+            source: duplicate,
+            target: kept.clone(),
+            resolution,
+        });
+    }
+
+    let kept: Vec<Period> = existing.keys().copied().collect();
+    check_history(
+        "merge.pesel_history",
+        &moved,
+        &kept,
+        PeselChange::list_for_member(conn, source.id)?
+            .last()
+            .map(PeselChange::replaced_from),
+        PeselChange::list_for_member(conn, target.id)?
+            .last()
+            .map(PeselChange::replaced_from),
+    )?;
+    check_history(
+        "merge.details_history",
+        &moved,
+        &kept,
+        DetailsChange::list_for_member(conn, source.id)?
+            .last()
+            .map(DetailsChange::replaced_from),
+        DetailsChange::list_for_member(conn, target.id)?
+            .last()
+            .map(DetailsChange::replaced_from),
+    )?;
+
+    let generation_ids =
+        Generation::snapshots_mentioning(conn, target.organization_id, &[source.id])?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+
+    Ok(MergePreview {
+        source,
+        target,
+        moved,
+        clashes,
+        generation_ids,
+    })
+}
+
+/// Refuse when periods of one member fall before the latest change in the
+/// other's history, since the earlier values would then apply to them too.
+fn check_history(
+    key: &'static str,
+    moved: &[Period],
+    kept: &[Period],
+    source_changed: Option<Period>,
+    target_changed: Option<Period>,
+) -> Result<(), AppError> {
+    let overlap = [(kept, source_changed), (moved, target_changed)]
+        .into_iter()
+        .find_map(|(periods, changed)| changed.filter(|c| periods.iter().any(|p| p < c)));
+    match overlap {
+        Some(period) => Err(AppError::Validation(
+            Message::new(key)
+                .with_field("target_id")
+                .with_param("period", period),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn resolve(
+    resolution: ClashResolution,
+    source: &ContributionAmounts,
+    target: &ContributionAmounts,
+) -> Result<ContributionAmounts, AppError> {
+    Ok(match resolution {
+        ClashResolution::KeepTarget => target.clone(),
+        ClashResolution::KeepSource => source.clone(),
+        ClashResolution::Sum => ContributionAmounts {
+            employee_basic: source
+                .employee_basic
+                .checked_add(target.employee_basic, "employee_basic")?,
+            employee_additional: source
+                .employee_additional
+                .checked_add(target.employee_additional, "employee_additional")?,
+            employer_basic: source
+                .employer_basic
+                .checked_add(target.employer_basic, "employer_basic")?,
+            employer_additional: source
+                .employer_additional
+                .checked_add(target.employer_additional, "employer_additional")?,
+            reduced_basic_flag: target.reduced_basic_flag,
+        },
+    })
+}
+
+fn change(member_id: i64, period: Period, amounts: &ContributionAmounts) -> ContributionChange {
+    ContributionChange {
+        member_id,
+        period,
+        employee_basic: Some(amounts.employee_basic),
+        employee_additional: Some(amounts.employee_additional),
+        employer_basic: Some(amounts.employer_basic),
+        employer_additional: Some(amounts.employer_additional),
+        reduced_basic_flag: Some(amounts.reduced_basic_flag),
+    }
+}
+
+/// Stored rows always hold a valid period.
+fn period_of(year: i32, month: i32) -> Period {
+    Period { year, month }
+}
@@ -0,0 +1,21 @@
+//! Domain operations over a `DbPool`, independent of the Tauri runtime.
+//!
+//! All validation and business rules live here; `commands` only adapt them to
+//! IPC. Tests, tooling or another frontend can call these functions directly.
+
+pub mod archive;
+pub mod audit;
+pub mod batch;
+pub mod contacts;
+pub mod contributions;
+pub mod generations;
+pub mod journal;
+pub mod members;
+pub mod merge;
+pub mod organizations;
+pub mod persons;
+pub mod prefill;
+pub mod retention;
+pub mod settings;
+pub mod subject_access;
+pub mod trash;
@@ -0,0 +1,152 @@
+use chrono::NaiveDate;
+
+use crate::db::pool::DbPool;
+use crate::domain::codes::EmployerIdType;
+use crate::error::{AppError, Message};
+use crate::models::organization::{
+    CreateOrganization, EmployerProfile, Organization, UpdateOrganization,
+};
+use crate::validation::{bank_account, krs, nip, pesel, postal_code, regon};
+
+pub fn list(pool: &DbPool) -> Result<Vec<Organization>, AppError> {
+    let conn = pool.get()?;
+    Organization::list(&conn)
+}
+
+pub fn get(pool: &DbPool, id: i64) -> Result<Organization, AppError> {
+    let conn = pool.get()?;
+    Organization::get(&conn, id)
+}
+
+pub fn create(pool: &DbPool, data: &CreateOrganization) -> Result<Organization, AppError> {
+    validate(
+        &data.name,
+        &data.nip,
+        &data.regon,
+        data.id_type,
+        &data.pesel,
+    )?;
+    validate_profile(&data.profile)?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let organization = Organization::create(&tx, data)?;
+    tx.commit()?;
+    Ok(organization)
+}
+
+pub fn update(pool: &DbPool, id: i64, data: &UpdateOrganization) -> Result<Organization, AppError> {
+    validate(
+        &data.name,
+        &data.nip,
+        &data.regon,
+        data.id_type,
+        &data.pesel,
+    )?;
+    validate_profile(&data.profile)?;
+
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let organization = Organization::update(&tx, id, data)?;
+    tx.commit()?;
+    Ok(organization)
+}
+
+/// Move an organization to the trash along with everything belonging to it.
+pub fn archive(pool: &DbPool, id: i64) -> Result<(), AppError> {
+    let conn = pool.get()?;
+    Organization::set_archived(&conn, id, true)
+}
+
+pub fn restore(pool: &DbPool, id: i64) -> Result<Organization, AppError> {
+    let conn = pool.get()?;
+    Organization::set_archived(&conn, id, false)?;
+    Organization::get(&conn, id)
+}
+
+/// The identifier of the chosen type is required; the others are checked
+/// when given.
+fn validate(
+    name: &str,
+    nip_str: &str,
+    regon_str: &str,
+    id_type: EmployerIdType,
+    pesel_str: &str,
+) -> Result<(), AppError> {
+    // The PESEL is not stored for other types, so it is not checked either
+    let pesel_str = if id_type == EmployerIdType::Pesel {
+        pesel_str
+    } else {
+        ""
+    };
+    let identifiers = [
+        (EmployerIdType::Nip, "nip", nip_str),
+        (EmployerIdType::Regon, "regon", regon_str),
+        (EmployerIdType::Pesel, "pesel", pesel_str),
+    ];
+    for (kind, field, value) in identifiers {
+        if value.is_empty() {
+            if kind == id_type {
+                return Err(AppError::Validation(
+                    Message::new("organization.identifier_required")
+                        .with_field(field)
+                        .with_param("identifier", kind),
+                ));
+            }
+            continue;
+        }
+        match kind {
+            EmployerIdType::Nip => nip::validate(value)?,
+            EmployerIdType::Regon => regon::validate(value)?,
+            EmployerIdType::Pesel => {
+                pesel::validate(value)?;
+            }
+        }
+    }
+
+    if name.trim().is_empty() {
+        return Err(AppError::Validation(
+            Message::new("organization.name_required").with_field("name"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fields left empty are not checked.
+fn validate_profile(profile: &EmployerProfile) -> Result<(), AppError> {
+    if !profile.postal_code.is_empty() {
+        postal_code::validate(&profile.postal_code)?;
+    }
+    if !profile.krs.is_empty() {
+        krs::validate(&profile.krs)?;
+    }
+    if !profile.bank_account.is_empty() {
+        bank_account::validate(&profile.bank_account, "bank_account")?;
+    }
+    if !profile.collection_account.is_empty() {
+        bank_account::validate(&profile.collection_account, "collection_account")?;
+    }
+
+    let dates = [
+        (
+            "management_agreement_date",
+            &profile.management_agreement_date,
+        ),
+        (
+            "operation_agreement_date",
+            &profile.operation_agreement_date,
+        ),
+    ];
+    for (field, date) in dates {
+        if let Some(date) = date {
+            if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                return Err(AppError::Validation(
+                    Message::new("organization.agreement_date").with_field(field),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,321 @@
+//! People across organizations: memberships grouped by PESEL, or by identity
+//! document for foreigners, with identity data that disagrees between them.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::pool::DbPool;
+use crate::domain::money::Money;
+use crate::error::AppError;
+use crate::models::contribution::{Contribution, ContributionWithMember};
+use crate::models::member::Member;
+use crate::models::organization::Organization;
+use crate::services::generations::Totals;
+
+/// How a person is looked up and identified in reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersonKey {
+    Pesel(String),
+    Document {
+        doc_type: String,
+        doc_number: String,
+    },
+}
+
+/// One person's memberships. Memberships belong to the same person when
+/// they share a PESEL or an identity document.
+#[derive(Debug, Serialize)]
+pub struct Person {
+    /// The PESEL when all memberships share one, otherwise their document.
+    pub key: PersonKey,
+    pub memberships: Vec<Membership>,
+    pub conflicts: Vec<IdentityConflict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Membership {
+    pub organization_id: i64,
+    pub organization_name: String,
+    pub member: Member,
+}
+
+/// An identity field recorded differently by the person's organizations.
+#[derive(Debug, Serialize)]
+pub struct IdentityConflict {
+    pub field: &'static str,
+    pub values: Vec<ConflictingValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConflictingValue {
+    pub value: String,
+    pub member_ids: Vec<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PersonFilter {
+    /// Only people with memberships in more than one organization.
+    pub multiple_organizations: bool,
+    /// Only people whose identity data disagrees.
+    pub conflicts_only: bool,
+}
+
+/// A person's contributions from all their organizations.
+#[derive(Debug, Serialize)]
+pub struct PersonReport {
+    pub person: Person,
+    pub contributions: Vec<PersonContribution>,
+    pub total_employee_basic: Money,
+    pub total_employee_additional: Money,
+    pub total_employer_basic: Money,
+    pub total_employer_additional: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PersonContribution {
+    pub organization_id: i64,
+    pub organization_name: String,
+    #[serde(flatten)]
+    pub contribution: ContributionWithMember,
+}
+
+/// Everyone with personal data on record, ordered by name.
+pub fn list(pool: &DbPool, filter: &PersonFilter) -> Result<Vec<Person>, AppError> {
+    let conn = pool.get()?;
+    let people = group(&conn)?
+        .into_iter()
+        .filter(|p| !filter.multiple_organizations || organization_count(p) > 1)
+        .filter(|p| !filter.conflicts_only || !p.conflicts.is_empty())
+        .collect();
+    Ok(people)
+}
+
+pub fn get(pool: &DbPool, key: &PersonKey) -> Result<Person, AppError> {
+    let conn = pool.get()?;
+    find(&conn, key)
+}
+
+/// Every contribution the person has, oldest period first.
+pub fn report(pool: &DbPool, key: &PersonKey) -> Result<PersonReport, AppError> {
+    let conn = pool.get()?;
+    let person = find(&conn, key)?;
+
+    let mut rows = Vec::new();
+    for membership in &person.memberships {
+        for contribution in Contribution::list_for_member(&conn, membership.member.id)? {
+            rows.push((
+                membership.organization_id,
+                membership.organization_name.clone(),
+                contribution,
+            ));
+        }
+    }
+    rows.sort_by(|a, b| {
+        (a.2.period_year, a.2.period_month, &a.1).cmp(&(b.2.period_year, b.2.period_month, &b.1))
+    });
+
+    let totals = Totals::of(&rows.iter().map(|r| r.2.clone()).collect::<Vec<_>>())?;
+    Ok(PersonReport {
+        person,
+        contributions: rows
+            .into_iter()
+            .map(
+                |(organization_id, organization_name, contribution)| PersonContribution {
+                    organization_id,
+                    organization_name,
+                    contribution,
+                },
+            )
+            .collect(),
+        total_employee_basic: totals.employee_basic,
+        total_employee_additional: totals.employee_additional,
+        total_employer_basic: totals.employer_basic,
+        total_employer_additional: totals.employer_additional,
+    })
+}
+
+fn find(conn: &Connection, key: &PersonKey) -> Result<Person, AppError> {
+    group(conn)?
+        .into_iter()
+        .find(|p| p.memberships.iter().any(|m| matches(&m.member, key)))
+        .ok_or_else(|| AppError::NotFound("person.not_found".into()))
+}
+
+fn matches(member: &Member, key: &PersonKey) -> bool {
+    match key {
+        PersonKey::Pesel(pesel) => member.pesel.as_str() == pesel.trim(),
+        PersonKey::Document {
+            doc_type,
+            doc_number,
+        } => document(member) == Some((doc_type.trim().to_string(), compact(doc_number))),
+    }
+}
+
+/// Group all memberships with personal data into people.
+fn group(conn: &Connection) -> Result<Vec<Person>, AppError> {
+    let members = Member::list_identified(conn)?;
+    let names: HashMap<i64, String> = Organization::list(conn)?
+        .into_iter()
+        .map(|o| (o.id, o.name))
+        .collect();
+
+    // Union-find over member indices, joined by shared PESEL or document
+    let mut parent: Vec<usize> = (0..members.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut first_by_pesel: HashMap<&str, usize> = HashMap::new();
+    let mut first_by_document: HashMap<(String, String), usize> = HashMap::new();
+    for (i, member) in members.iter().enumerate() {
+        let mut links = vec![*first_by_pesel.entry(member.pesel.as_str()).or_insert(i)];
+        if let Some(doc) = document(member) {
+            links.push(*first_by_document.entry(doc).or_insert(i));
+        }
+        for j in links {
+            let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+            parent[a] = b;
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<Member>> = BTreeMap::new();
+    let roots: Vec<usize> = (0..members.len()).map(|i| root(&mut parent, i)).collect();
+    for (member, r) in members.into_iter().zip(roots) {
+        groups.entry(r).or_default().push(member);
+    }
+
+    let mut people: Vec<Person> = groups
+        .into_values()
+        .map(|members| person(members, &names))
+        .collect();
+    people.sort_by(|a, b| {
+        let name = |p: &Person| {
+            let m = &p.memberships[0].member;
+            (m.last_name.clone(), m.first_name.clone())
+        };
+        name(a).cmp(&name(b))
+    });
+    Ok(people)
+}
+
+fn person(members: Vec<Member>, names: &HashMap<i64, String>) -> Person {
+    let conflicts = conflicts(&members);
+    let first = &members[0];
+    let key = if members.iter().all(|m| m.pesel == first.pesel) {
+        PersonKey::Pesel(first.pesel.to_string())
+    } else {
+        match members.iter().find_map(document) {
+            Some((doc_type, doc_number)) => PersonKey::Document {
+                doc_type,
+                doc_number,
+            },
+            None => PersonKey::Pesel(first.pesel.to_string()),
+        }
+    };
+    let memberships = members
+        .into_iter()
+        .map(|member| Membership {
+            organization_id: member.organization_id,
+            organization_name: names
+                .get(&member.organization_id)
+                .cloned()
+                .unwrap_or_default(),
+            member,
+        })
+        .collect();
+    Person {
+        key,
+        memberships,
+        conflicts,
+    }
+}
+
+type FieldValue = fn(&Member) -> String;
+
+/// Identity fields with more than one value among `members`, compared
+/// ignoring case and spacing. Fields left empty by an organization are
+/// missing rather than conflicting.
+fn conflicts(members: &[Member]) -> Vec<IdentityConflict> {
+    let fields: [(&'static str, FieldValue); 8] = [
+        ("pesel", |m| m.pesel.to_string()),
+        ("last_name", |m| m.last_name.clone()),
+        ("first_name", |m| m.first_name.clone()),
+        ("second_name", |m| m.second_name.clone()),
+        ("gender", |m| m.gender.to_string()),
+        ("date_of_birth", |m| m.date_of_birth.clone()),
+        ("citizenship", |m| m.citizenship.clone()),
+        ("document", |m| {
+            document(m).map_or(String::new(), |(doc_type, number)| {
+                format!("{} {}", doc_type, number)
+            })
+        }),
+    ];
+
+    let mut conflicts = Vec::new();
+    for (field, value_of) in fields {
+        // Keyed by the normalized value, showing the first spelling found
+        let mut values: Vec<(String, ConflictingValue)> = Vec::new();
+        for member in members {
+            let value = value_of(member);
+            let key = normalize(&value);
+            if key.is_empty() {
+                continue;
+            }
+            match values.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, v)) => v.member_ids.push(member.id),
+                None => values.push((
+                    key,
+                    ConflictingValue {
+                        value: value.trim().to_string(),
+                        member_ids: vec![member.id],
+                    },
+                )),
+            }
+        }
+        let values: Vec<ConflictingValue> = values.into_iter().map(|(_, v)| v).collect();
+        if values.len() > 1 {
+            conflicts.push(IdentityConflict { field, values });
+        }
+    }
+    conflicts
+}
+
+/// The member's identity document, when both its type and number are known.
+fn document(member: &Member) -> Option<(String, String)> {
+    let number = compact(&member.doc_number);
+    if member.doc_type.trim().is_empty() || number.is_empty() {
+        return None;
+    }
+    Some((member.doc_type.trim().to_string(), number))
+}
+
+/// Compare text case-insensitively and ignoring surrounding and inner spaces.
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+/// A document number without any spaces, upper case.
+fn compact(value: &str) -> String {
+    value.split_whitespace().collect::<String>().to_uppercase()
+}
+
+fn organization_count(person: &Person) -> usize {
+    let mut ids: Vec<i64> = person
+        .memberships
+        .iter()
+        .map(|m| m.organization_id)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.len()
+}
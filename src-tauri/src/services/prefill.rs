@@ -0,0 +1,358 @@
+//! Prefill of a new period: proposed contributions for active members,
+//! previewed first and then written as 'prefilled' rows.
+
+use rusqlite::Connection;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::db::pool::DbPool;
+use crate::domain::codes::{ContributionSource, YesNo};
+use crate::domain::money::Money;
+use crate::domain::period::Period;
+use crate::error::{AppError, Message};
+use crate::models::contribution::{Contribution, ContributionAmounts, ContributionWithMember};
+use crate::models::journal::RowKey;
+use crate::models::member::Member;
+use crate::models::organization::Organization;
+use crate::services::journal::Tracker;
+
+/// Statutory PPK rates, in percent of the salary.
+const EMPLOYEE_BASIC_RATE: Decimal = Decimal::from_parts(2, 0, 0, false, 0);
+const EMPLOYEE_REDUCED_BASIC_RATE: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+const EMPLOYER_BASIC_RATE: Decimal = Decimal::from_parts(15, 0, 0, false, 1);
+const EMPLOYEE_ADDITIONAL_MAX_RATE: Decimal = Decimal::from_parts(2, 0, 0, false, 0);
+const EMPLOYER_ADDITIONAL_MAX_RATE: Decimal = Decimal::from_parts(25, 0, 0, false, 1);
+
+/// Largest raise accepted by [`PrefillStrategy::Scale`], in percent.
+const MAX_SCALE_PERCENT: Decimal = Decimal::from_parts(1000, 0, 0, false, 0);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PrefillStrategy {
+    /// Copy each member's row from `source`, or from their latest earlier
+    /// period. Members with nothing to copy get zeros.
+    Copy {
+        #[serde(default)]
+        source: Option<Period>,
+    },
+    /// Zeros for everyone.
+    Zero,
+    /// Contributions at the statutory rates of each member's salary; members
+    /// without a salary are left out.
+    Salary {
+        bases: Vec<SalaryBase>,
+        #[serde(default)]
+        employee_additional_rate: Decimal,
+        #[serde(default)]
+        employer_additional_rate: Decimal,
+    },
+    /// Copy as above, with every amount changed by `percent`.
+    Scale {
+        #[serde(default)]
+        source: Option<Period>,
+        percent: Decimal,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SalaryBase {
+    pub member_id: i64,
+    pub salary: Money,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrefillRequest {
+    pub organization_id: i64,
+    pub period: Period,
+    pub strategy: PrefillStrategy,
+    /// Only these members; all active members when absent.
+    #[serde(default)]
+    pub member_ids: Option<Vec<i64>>,
+    /// Replace rows prefilled earlier. Rows entered by hand are never replaced.
+    #[serde(default)]
+    pub overwrite_prefilled: bool,
+}
+
+impl PrefillRequest {
+    /// Copy from each member's latest period, without overwriting.
+    pub fn copy_latest(organization_id: i64, period: Period) -> PrefillRequest {
+        PrefillRequest {
+            organization_id,
+            period,
+            strategy: PrefillStrategy::Copy { source: None },
+            member_ids: None,
+            overwrite_prefilled: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefillRow {
+    pub member_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    #[serde(flatten)]
+    pub amounts: ContributionAmounts,
+    /// Whether the row replaces one prefilled earlier.
+    pub overwrites: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The member already has a row entered by hand.
+    Manual,
+    /// The member already has a prefilled row and overwriting is off.
+    Prefilled,
+    /// No salary was given for the member.
+    NoSalary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedMember {
+    pub member_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrefillPreview {
+    pub rows: Vec<PrefillRow>,
+    pub skipped: Vec<SkippedMember>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrefillResult {
+    pub created: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+/// The rows `apply` would write, without writing anything.
+pub fn preview(pool: &DbPool, request: &PrefillRequest) -> Result<PrefillPreview, AppError> {
+    let conn = pool.get()?;
+    plan(&conn, request)
+}
+
+/// Write the proposed rows in one transaction.
+pub fn apply(pool: &DbPool, request: &PrefillRequest) -> Result<PrefillResult, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let result = apply_in(&tx, request)?;
+    tx.commit()?;
+    Ok(result)
+}
+
+/// `apply` within a transaction the caller commits.
+pub(crate) fn apply_in(
+    tx: &Connection,
+    request: &PrefillRequest,
+) -> Result<PrefillResult, AppError> {
+    let preview = plan(tx, request)?;
+
+    let mut result = PrefillResult {
+        created: 0,
+        overwritten: 0,
+        skipped: preview.skipped.len(),
+    };
+    let mut tracker = Tracker::new();
+    for row in &preview.rows {
+        tracker.track(tx, RowKey::contribution(row.member_id, request.period))?;
+        if Contribution::write_prefilled(tx, row.member_id, request.period, &row.amounts)? {
+            if row.overwrites {
+                result.overwritten += 1;
+            } else {
+                result.created += 1;
+            }
+        }
+    }
+    tracker.record(tx, request.organization_id, "contribution.prefill")?;
+
+    Ok(result)
+}
+
+fn plan(conn: &Connection, request: &PrefillRequest) -> Result<PrefillPreview, AppError> {
+    validate(request)?;
+    Organization::get(conn, request.organization_id)?;
+
+    let existing: HashMap<i64, ContributionSource> = Contribution::list_for_period(
+        conn,
+        request.organization_id,
+        request.period.year,
+        request.period.month,
+    )?
+    .into_iter()
+    .map(|c| (c.member_id, c.source))
+    .collect();
+
+    let source = match &request.strategy {
+        PrefillStrategy::Copy { source } | PrefillStrategy::Scale { source, .. } => *source,
+        PrefillStrategy::Zero | PrefillStrategy::Salary { .. } => None,
+    };
+    let previous = match source {
+        Some(p) => Contribution::list_for_period(conn, request.organization_id, p.year, p.month)?,
+        None => Contribution::list_latest_before(conn, request.organization_id, request.period)?,
+    };
+    let previous: HashMap<i64, ContributionWithMember> =
+        previous.into_iter().map(|c| (c.member_id, c)).collect();
+
+    let mut preview = PrefillPreview {
+        rows: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for member in Member::list_active(conn, request.organization_id)? {
+        if let Some(ids) = &request.member_ids {
+            if !ids.contains(&member.id) {
+                continue;
+            }
+        }
+
+        let skip = |reason| SkippedMember {
+            member_id: member.id,
+            first_name: member.first_name.clone(),
+            last_name: member.last_name.clone(),
+            reason,
+        };
+        let overwrites = match existing.get(&member.id) {
+            Some(ContributionSource::Manual) => {
+                preview.skipped.push(skip(SkipReason::Manual));
+                continue;
+            }
+            Some(ContributionSource::Prefilled) if !request.overwrite_prefilled => {
+                preview.skipped.push(skip(SkipReason::Prefilled));
+                continue;
+            }
+            Some(ContributionSource::Prefilled) => true,
+            None => false,
+        };
+
+        let previous: Option<ContributionAmounts> = previous.get(&member.id).map(Into::into);
+        let amounts = match &request.strategy {
+            PrefillStrategy::Copy { .. } => previous.unwrap_or_default(),
+            PrefillStrategy::Zero => ContributionAmounts::default(),
+            PrefillStrategy::Scale { percent, .. } => {
+                scale(previous.unwrap_or_default(), Decimal::ONE_HUNDRED + percent)?
+            }
+            PrefillStrategy::Salary {
+                bases,
+                employee_additional_rate,
+                employer_additional_rate,
+            } => match bases.iter().find(|b| b.member_id == member.id) {
+                Some(base) => {
+                    let reduced = previous.map_or(YesNo::No, |p| p.reduced_basic_flag);
+                    from_salary(
+                        base.salary,
+                        reduced,
+                        *employee_additional_rate,
+                        *employer_additional_rate,
+                    )?
+                }
+                None => {
+                    preview.skipped.push(skip(SkipReason::NoSalary));
+                    continue;
+                }
+            },
+        };
+
+        preview.rows.push(PrefillRow {
+            member_id: member.id,
+            first_name: member.first_name,
+            last_name: member.last_name,
+            amounts,
+            overwrites,
+        });
+    }
+
+    Ok(preview)
+}
+
+fn validate(request: &PrefillRequest) -> Result<(), AppError> {
+    match &request.strategy {
+        PrefillStrategy::Copy {
+            source: Some(source),
+        }
+        | PrefillStrategy::Scale {
+            source: Some(source),
+            ..
+        } if *source == request.period => {
+            return Err(AppError::Validation(Message::new("prefill.same_period")));
+        }
+        _ => {}
+    }
+
+    match &request.strategy {
+        PrefillStrategy::Scale { percent, .. } => {
+            if *percent < -Decimal::ONE_HUNDRED || *percent > MAX_SCALE_PERCENT {
+                return Err(AppError::Validation(
+                    Message::new("prefill.percent")
+                        .with_field("percent")
+                        .with_param("max", MAX_SCALE_PERCENT),
+                ));
+            }
+        }
+        PrefillStrategy::Salary {
+            employee_additional_rate,
+            employer_additional_rate,
+            ..
+        } => {
+            check_rate(
+                *employee_additional_rate,
+                EMPLOYEE_ADDITIONAL_MAX_RATE,
+                "employee_additional_rate",
+            )?;
+            check_rate(
+                *employer_additional_rate,
+                EMPLOYER_ADDITIONAL_MAX_RATE,
+                "employer_additional_rate",
+            )?;
+        }
+        PrefillStrategy::Copy { .. } | PrefillStrategy::Zero => {}
+    }
+    Ok(())
+}
+
+fn check_rate(rate: Decimal, max: Decimal, field: &'static str) -> Result<(), AppError> {
+    if rate < Decimal::ZERO || rate > max {
+        return Err(AppError::Validation(
+            Message::new("prefill.rate")
+                .with_field(field)
+                .with_param("max", max),
+        ));
+    }
+    Ok(())
+}
+
+fn scale(amounts: ContributionAmounts, percent: Decimal) -> Result<ContributionAmounts, AppError> {
+    Ok(ContributionAmounts {
+        employee_basic: amounts.employee_basic.percent(percent, "employee_basic")?,
+        employee_additional: amounts
+            .employee_additional
+            .percent(percent, "employee_additional")?,
+        employer_basic: amounts.employer_basic.percent(percent, "employer_basic")?,
+        employer_additional: amounts
+            .employer_additional
+            .percent(percent, "employer_additional")?,
+        reduced_basic_flag: amounts.reduced_basic_flag,
+    })
+}
+
+fn from_salary(
+    salary: Money,
+    reduced_basic_flag: YesNo,
+    employee_additional_rate: Decimal,
+    employer_additional_rate: Decimal,
+) -> Result<ContributionAmounts, AppError> {
+    let employee_basic_rate = match reduced_basic_flag {
+        YesNo::Yes => EMPLOYEE_REDUCED_BASIC_RATE,
+        YesNo::No => EMPLOYEE_BASIC_RATE,
+    };
+    Ok(ContributionAmounts {
+        employee_basic: salary.percent(employee_basic_rate, "employee_basic")?,
+        employee_additional: salary.percent(employee_additional_rate, "employee_additional")?,
+        employer_basic: salary.percent(EMPLOYER_BASIC_RATE, "employer_basic")?,
+        employer_additional: salary.percent(employer_additional_rate, "employer_additional")?,
+        reduced_basic_flag,
+    })
+}
@@ -0,0 +1,195 @@
+//! Retention policy for former participants. Once `retention_years` have
+//! passed since a member left, their personal data is anonymized in `members`
+//! and in every generation snapshot. The archived files of those generations
+//! are deleted, so exports rebuild them from the anonymized snapshots.
+//! Contribution amounts stay, so totals of past generations do not change.
+
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::db::pool::DbPool;
+use crate::domain::codes::MemberStatus;
+use crate::domain::identifiers::Pesel;
+use crate::error::AppError;
+use crate::models::audit::AuditEntry;
+use crate::models::details_history::DetailsChange;
+use crate::models::generation::Generation;
+use crate::models::journal::Journal;
+use crate::models::member::{AnonymizedPerson, Member};
+use crate::models::organization::Organization;
+use crate::models::pesel_history::PeselChange;
+use crate::models::settings::OrganizationSettings;
+
+pub const AUDIT_ACTION: &str = "retention_anonymize";
+
+/// Personal fields cleared by anonymization.
+const ANONYMIZED_FIELDS: &[&str] = &[
+    "pesel",
+    "first_name",
+    "last_name",
+    "second_name",
+    "date_of_birth",
+    "doc_type",
+    "doc_number",
+];
+
+#[derive(Debug, Serialize)]
+pub struct RetentionCandidate {
+    pub member_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub pesel: Pesel,
+    pub status: MemberStatus,
+    pub terminated_at: String,
+}
+
+/// What `apply` would do, without changing anything.
+#[derive(Debug, Serialize)]
+pub struct RetentionPreview {
+    pub organization_id: i64,
+    pub retention_years: i32,
+    /// Members who left on or before this day are affected.
+    pub cutoff: String,
+    pub members: Vec<RetentionCandidate>,
+    pub generation_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionResult {
+    pub anonymized: usize,
+    pub generations_updated: usize,
+    pub audit: AuditEntry,
+}
+
+pub fn preview(pool: &DbPool, organization_id: i64) -> Result<RetentionPreview, AppError> {
+    let conn = pool.get()?;
+    let (retention_years, cutoff) = policy(&conn, organization_id)?;
+
+    let members = Member::list_retention_due(&conn, organization_id, &cutoff.to_string())?;
+    let ids: Vec<i64> = members.iter().map(|m| m.id).collect();
+    let generation_ids = Generation::snapshots_mentioning(&conn, organization_id, &ids)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    Ok(RetentionPreview {
+        organization_id,
+        retention_years,
+        cutoff: cutoff.to_string(),
+        members: members
+            .into_iter()
+            .map(|m| RetentionCandidate {
+                member_id: m.id,
+                first_name: m.first_name,
+                last_name: m.last_name,
+                pesel: m.pesel,
+                status: m.status,
+                terminated_at: m.terminated_at.unwrap_or_default(),
+            })
+            .collect(),
+        generation_ids,
+    })
+}
+
+/// Anonymize everyone due under the organization's policy, in one
+/// transaction, and record what was done in the audit log.
+pub fn apply(pool: &DbPool, organization_id: i64) -> Result<RetentionResult, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let (retention_years, cutoff) = policy(&tx, organization_id)?;
+
+    let members = Member::list_retention_due(&tx, organization_id, &cutoff.to_string())?;
+    let ids: Vec<i64> = members.iter().map(|m| m.id).collect();
+    let replacements: HashMap<i64, AnonymizedPerson> = ids
+        .iter()
+        .map(|&id| (id, AnonymizedPerson::for_member(id)))
+        .collect();
+
+    for (id, anonymized) in &replacements {
+        Member::anonymize(&tx, *id, anonymized)?;
+        PeselChange::delete_for_member(&tx, *id)?;
+        DetailsChange::delete_for_member(&tx, *id)?;
+    }
+
+    let snapshots = Generation::snapshots_mentioning(&tx, organization_id, &ids)?;
+    let generation_ids: Vec<i64> = snapshots.iter().map(|(id, _)| *id).collect();
+    let mut archived_files = Vec::new();
+    for (id, mut snapshot) in snapshots {
+        anonymize_snapshot(&mut snapshot, &replacements);
+        Generation::update_snapshot(&tx, id, &snapshot.to_string())?;
+        archived_files.push(Generation::clear_file(&tx, id)?);
+    }
+
+    // Ids only; the audit log must not keep the data it records removing
+    let audit = AuditEntry::insert(
+        &tx,
+        Some(organization_id),
+        AUDIT_ACTION,
+        &serde_json::json!({
+            "retention_years": retention_years,
+            "cutoff": cutoff.to_string(),
+            "member_ids": ids,
+            "generation_ids": generation_ids,
+            "fields": ANONYMIZED_FIELDS,
+        }),
+    )?;
+    // Undo must not bring the removed data back
+    Journal::cut_off(&tx, organization_id)?;
+    tx.commit()?;
+
+    // Only once committed, so a failed run keeps the files it still refers to
+    for path in archived_files.iter().filter(|p| !p.is_empty()) {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove archived file {}: {}", path, e);
+        }
+    }
+
+    Ok(RetentionResult {
+        anonymized: ids.len(),
+        generations_updated: generation_ids.len(),
+        audit,
+    })
+}
+
+/// Retention period of an organization and the resulting cutoff day.
+fn policy(conn: &rusqlite::Connection, organization_id: i64) -> Result<(i32, NaiveDate), AppError> {
+    Organization::get(conn, organization_id)?;
+    let years = OrganizationSettings::get(conn, organization_id)?
+        .retention_years
+        .ok_or_else(|| AppError::Validation("retention.no_policy".into()))?;
+
+    Ok((years, cutoff(years)?))
+}
+
+/// Members who left on or before this day are past a retention period of
+/// `years`.
+pub(crate) fn cutoff(years: i32) -> Result<NaiveDate, AppError> {
+    let today = chrono::Local::now().date_naive();
+    today
+        .checked_sub_months(Months::new(12 * years.max(0) as u32))
+        .ok_or_else(|| AppError::Validation("retention.invalid_period".into()))
+}
+
+fn anonymize_snapshot(
+    snapshot: &mut serde_json::Value,
+    replacements: &HashMap<i64, AnonymizedPerson>,
+) {
+    let Some(rows) = snapshot["contributions"].as_array_mut() else {
+        return;
+    };
+    for row in rows {
+        let Some(anonymized) = row["member_id"]
+            .as_i64()
+            .and_then(|id| replacements.get(&id))
+        else {
+            continue;
+        };
+        for field in ANONYMIZED_FIELDS {
+            row[*field] = serde_json::Value::from("");
+        }
+        row["pesel"] = anonymized.pesel.as_str().into();
+        row["first_name"] = anonymized.first_name.clone().into();
+        row["last_name"] = anonymized.last_name.clone().into();
+    }
+}
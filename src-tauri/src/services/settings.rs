@@ -0,0 +1,270 @@
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+use crate::db::pool::DbPool;
+use crate::error::{AppError, Message};
+use crate::i18n;
+use crate::models::contact::ContactPerson;
+use crate::models::organization::Organization;
+use crate::models::settings::{
+    AppSettings, OrganizationSettings, UpdateOrganizationSettings, DEFAULT_CONTACT_TEMPLATE,
+    DEFAULT_FILE_NAME_TEMPLATE,
+};
+
+const PLACEHOLDERS: &[&str] = &["id", "nip", "regon", "year", "month", "period", "timestamp"];
+const CONTACT_PLACEHOLDERS: &[&str] = &["name", "phone", "email"];
+
+pub fn get_app(pool: &DbPool) -> Result<AppSettings, AppError> {
+    let conn = pool.get()?;
+    AppSettings::get(&conn)
+}
+
+pub fn update_app(pool: &DbPool, data: &AppSettings) -> Result<AppSettings, AppError> {
+    if data.archive_dir.trim().is_empty() {
+        return Err(AppError::Validation(
+            Message::new("settings.archive_dir_required").with_field("archive_dir"),
+        ));
+    }
+    if !data.file_name_template.is_empty() {
+        validate_template(&data.file_name_template)?;
+    }
+    if !data.contact_template.is_empty() {
+        validate_contact_template(&data.contact_template)?;
+    }
+
+    let conn = pool.get()?;
+    AppSettings::save(&conn, data)?;
+    let settings = AppSettings::get(&conn)?;
+    i18n::set_locale(settings.language);
+    Ok(settings)
+}
+
+/// Render messages in the saved language from now on.
+pub fn load_language(pool: &DbPool) -> Result<(), AppError> {
+    let conn = pool.get()?;
+    i18n::set_locale(AppSettings::get(&conn)?.language);
+    Ok(())
+}
+
+/// Point the archive at `archive_dir` unless the user already chose one.
+pub fn init_defaults(pool: &DbPool, archive_dir: &Path) -> Result<(), AppError> {
+    let conn = pool.get()?;
+    let mut settings = AppSettings::get(&conn)?;
+    if settings.archive_dir.is_empty() {
+        settings.archive_dir = archive_dir.to_string_lossy().into_owned();
+        AppSettings::save(&conn, &settings)?;
+    }
+    Ok(())
+}
+
+pub fn get_organization(
+    pool: &DbPool,
+    organization_id: i64,
+) -> Result<OrganizationSettings, AppError> {
+    let conn = pool.get()?;
+    Organization::get(&conn, organization_id)?;
+    OrganizationSettings::get(&conn, organization_id)
+}
+
+pub fn update_organization(
+    pool: &DbPool,
+    organization_id: i64,
+    data: &UpdateOrganizationSettings,
+) -> Result<OrganizationSettings, AppError> {
+    if !data.file_name_template.is_empty() {
+        validate_template(&data.file_name_template)?;
+    }
+    if !data.contact_template.is_empty() {
+        validate_contact_template(&data.contact_template)?;
+    }
+    if data.retention_years.is_some_and(|years| years < 1) {
+        return Err(AppError::Validation(
+            Message::new("settings.retention_years_min").with_field("retention_years"),
+        ));
+    }
+
+    let conn = pool.get()?;
+    let org = Organization::get(&conn, organization_id)?;
+    if !data.file_name_template.is_empty() {
+        check_identifiers(&data.file_name_template, &org)?;
+    }
+    OrganizationSettings::save(&conn, organization_id, data)
+}
+
+pub(crate) fn archive_dir(conn: &Connection) -> Result<PathBuf, AppError> {
+    let settings = AppSettings::get(conn)?;
+    if settings.archive_dir.is_empty() {
+        return Err(AppError::Other("settings.archive_dir_missing".into()));
+    }
+    Ok(PathBuf::from(settings.archive_dir))
+}
+
+/// Output directory for an organization: its own, else the global one.
+pub(crate) fn output_dir(
+    conn: &Connection,
+    organization_id: i64,
+) -> Result<Option<PathBuf>, AppError> {
+    let org = OrganizationSettings::get(conn, organization_id)?;
+    if !org.output_dir.is_empty() {
+        return Ok(Some(PathBuf::from(org.output_dir)));
+    }
+    let app = AppSettings::get(conn)?;
+    Ok((!app.output_dir.is_empty()).then(|| PathBuf::from(app.output_dir)))
+}
+
+/// File name for a generated ZIP, from the most specific template configured.
+pub(crate) fn file_name(
+    conn: &Connection,
+    org: &Organization,
+    year: i32,
+    month: i32,
+) -> Result<String, AppError> {
+    let org_settings = OrganizationSettings::get(conn, org.id)?;
+    let template = if !org_settings.file_name_template.is_empty() {
+        org_settings.file_name_template
+    } else {
+        let app = AppSettings::get(conn)?;
+        if app.file_name_template.is_empty() {
+            DEFAULT_FILE_NAME_TEMPLATE.to_string()
+        } else {
+            app.file_name_template
+        }
+    };
+    render_file_name(&template, org, year, month)
+}
+
+/// Substitute `{id}`, `{nip}`, `{regon}`, `{year}`, `{month}`, `{period}` and
+/// `{timestamp}` in a file name template. `{id}` is whichever identifier the
+/// employer goes by; `{nip}` and `{regon}` fail for an employer without one.
+pub fn render_file_name(
+    template: &str,
+    org: &Organization,
+    year: i32,
+    month: i32,
+) -> Result<String, AppError> {
+    validate_template(template)?;
+    check_identifiers(template, org)?;
+
+    Ok(template
+        .replace("{id}", &org.identifier())
+        .replace("{nip}", &org.nip.digits())
+        .replace("{regon}", &org.regon.digits())
+        .replace("{year}", &format!("{:04}", year))
+        .replace("{month}", &format!("{:02}", month))
+        .replace("{period}", &format!("{:04}-{:02}", year, month))
+        .replace(
+            "{timestamp}",
+            &chrono::Local::now().format("%Y%m%d_%H%M%S").to_string(),
+        ))
+}
+
+/// Value of `<KONTAKT>`: the organization's default contact in the most
+/// specific format configured, or empty when it has no contacts.
+pub(crate) fn contact(conn: &Connection, organization_id: i64) -> Result<String, AppError> {
+    let Some(contact) = ContactPerson::get_default(conn, organization_id)? else {
+        return Ok(String::new());
+    };
+    let org_settings = OrganizationSettings::get(conn, organization_id)?;
+    let template = if !org_settings.contact_template.is_empty() {
+        org_settings.contact_template
+    } else {
+        let app = AppSettings::get(conn)?;
+        if app.contact_template.is_empty() {
+            DEFAULT_CONTACT_TEMPLATE.to_string()
+        } else {
+            app.contact_template
+        }
+    };
+    render_contact(&template, &contact)
+}
+
+/// Substitute `{name}`, `{phone}` and `{email}` in a contact template. A
+/// placeholder with no value is left out together with the label written
+/// before it, so `{name}, tel. {phone}` gives just the name for a contact
+/// without a phone. Separators left at either end are trimmed and runs of
+/// whitespace collapse to one space.
+pub fn render_contact(template: &str, contact: &ContactPerson) -> Result<String, AppError> {
+    validate_contact_template(template)?;
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        // Validation guarantees every brace is closed and names a known placeholder
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let end = start + length;
+        let value = match &rest[start + 1..end] {
+            "name" => contact.name.trim(),
+            "phone" => contact.phone.trim(),
+            _ => contact.email.trim(),
+        };
+        if !value.is_empty() {
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(value);
+        }
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    let rendered = rendered.trim_matches(|c: char| c.is_whitespace() || ",;|-".contains(c));
+    Ok(rendered.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn validate_contact_template(template: &str) -> Result<(), AppError> {
+    validate_placeholders(template, CONTACT_PLACEHOLDERS, "contact_template")
+}
+
+fn validate_template(template: &str) -> Result<(), AppError> {
+    if template.contains(['/', '\\']) {
+        return Err(AppError::Validation(
+            Message::new("settings.template_path").with_field("file_name_template"),
+        ));
+    }
+    if !template.to_lowercase().ends_with(".zip") {
+        return Err(AppError::Validation(
+            Message::new("settings.template_extension").with_field("file_name_template"),
+        ));
+    }
+
+    validate_placeholders(template, PLACEHOLDERS, "file_name_template")
+}
+
+/// Reject `{nip}` or `{regon}` for an employer that has no such number, which
+/// would leave the file name without any identifier.
+fn check_identifiers(template: &str, org: &Organization) -> Result<(), AppError> {
+    for (placeholder, value) in [("{nip}", org.nip.digits()), ("{regon}", org.regon.digits())] {
+        if template.contains(placeholder) && value.is_empty() {
+            return Err(AppError::Validation(
+                Message::new("settings.template_identifier_missing")
+                    .with_field("file_name_template")
+                    .with_param("placeholder", placeholder),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_placeholders(
+    template: &str,
+    placeholders: &[&str],
+    field: &'static str,
+) -> Result<(), AppError> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            AppError::Validation(Message::new("settings.template_unclosed").with_field(field))
+        })?;
+        let name = &rest[start + 1..start + end];
+        if !placeholders.contains(&name) {
+            return Err(AppError::Validation(
+                Message::new("settings.template_placeholder")
+                    .with_field(field)
+                    .with_param("placeholder", format!("{{{}}}", name)),
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    Ok(())
+}
@@ -0,0 +1,356 @@
+//! GDPR subject access: everything stored about one person, across all
+//! organizations, exported as JSON or a self-contained HTML page.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::db::pool::DbPool;
+use crate::domain::identifiers::{Nip, Regon};
+use crate::error::{AppError, Message};
+use crate::generator::xml::escape;
+use crate::models::audit::AuditEntry;
+use crate::models::contribution::{Contribution, ContributionWithMember};
+use crate::models::details_history::DetailsChange;
+use crate::models::generation::Generation;
+use crate::models::member::Member;
+use crate::models::organization::Organization;
+use crate::models::pesel_history::PeselChange;
+use crate::models::transfer::MemberTransfer;
+use crate::services::archive;
+
+pub const AUDIT_ACTION: &str = "subject_access_export";
+
+/// The person to report on: one membership, or everyone with a PESEL.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubjectQuery {
+    Member(i64),
+    Pesel(String),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Html,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubjectReport {
+    pub pesel: String,
+    pub created_at: String,
+    pub records: Vec<SubjectRecord>,
+}
+
+/// One membership of the person, with everything linked to it.
+#[derive(Debug, Serialize)]
+pub struct SubjectRecord {
+    pub organization: SubjectEmployer,
+    pub member: Member,
+    pub contributions: Vec<ContributionWithMember>,
+    pub generations: Vec<GenerationEntry>,
+    /// Moves into or out of this membership, linking it to the others.
+    pub transfers: Vec<MemberTransfer>,
+    /// PESELs the membership had before a correction.
+    pub pesel_history: Vec<PeselChange>,
+    /// Names, citizenship and documents before dated changes.
+    pub details_history: Vec<DetailsChange>,
+}
+
+/// The employer of a membership. A sole proprietor's PESEL is a third
+/// party's personal data, so only the name, NIP and REGON are reported.
+#[derive(Debug, Serialize)]
+pub struct SubjectEmployer {
+    pub id: i64,
+    pub name: String,
+    pub nip: Nip,
+    pub regon: Regon,
+}
+
+impl From<Organization> for SubjectEmployer {
+    fn from(org: Organization) -> SubjectEmployer {
+        SubjectEmployer {
+            id: org.id,
+            name: org.name,
+            nip: org.nip,
+            regon: org.regon,
+        }
+    }
+}
+
+/// A generated file that included the person, with their row as stored.
+#[derive(Debug, Serialize)]
+pub struct GenerationEntry {
+    pub generation_id: i64,
+    pub period_year: i32,
+    pub period_month: i32,
+    pub generated_at: String,
+    pub file_path: String,
+    pub entry: serde_json::Value,
+}
+
+pub fn collect(pool: &DbPool, query: &SubjectQuery) -> Result<SubjectReport, AppError> {
+    let conn = pool.get()?;
+
+    let pesel = match query {
+        SubjectQuery::Member(id) => Member::get(&conn, *id)?.pesel.to_string(),
+        SubjectQuery::Pesel(pesel) => pesel.trim().to_string(),
+    };
+    // Memberships recorded under the PESEL earlier belong to the person too
+    let mut members = Member::list_by_pesel(&conn, &pesel)?;
+    for id in PeselChange::member_ids_with(&conn, &pesel)? {
+        if !members.iter().any(|m| m.id == id) {
+            members.push(Member::get(&conn, id)?);
+        }
+    }
+    if members.is_empty() {
+        return Err(AppError::NotFound("subject.not_found".into()));
+    }
+
+    let mut records = Vec::new();
+    for member in members {
+        let organization = Organization::get(&conn, member.organization_id)?.into();
+        let contributions = Contribution::list_for_member(&conn, member.id)?;
+        let transfers = MemberTransfer::list_for_member(&conn, member.id)?;
+        let pesel_history = PeselChange::list_for_member(&conn, member.id)?;
+        let details_history = DetailsChange::list_for_member(&conn, member.id)?;
+
+        let mut generations = Vec::new();
+        for (id, json) in Generation::list_snapshots(&conn, member.organization_id)? {
+            let snapshot: serde_json::Value = serde_json::from_str(&json).map_err(|e| {
+                AppError::Other(Message::new("snapshot.invalid").with_param("details", e))
+            })?;
+            let Some(entry) = find_entry(&snapshot, member.id, &pesel) else {
+                continue;
+            };
+            let generation = Generation::get_with_snapshot(&conn, id)?;
+            generations.push(GenerationEntry {
+                generation_id: id,
+                period_year: generation.period_year,
+                period_month: generation.period_month,
+                generated_at: generation.generated_at,
+                file_path: generation.file_path,
+                entry,
+            });
+        }
+
+        records.push(SubjectRecord {
+            organization,
+            member,
+            contributions,
+            generations,
+            transfers,
+            pesel_history,
+            details_history,
+        });
+    }
+
+    Ok(SubjectReport {
+        pesel,
+        created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        records,
+    })
+}
+
+/// Write the report to `output_path` and note the export in the audit log of
+/// every organization involved. Returns the absolute path written.
+pub fn export(
+    pool: &DbPool,
+    query: &SubjectQuery,
+    format: ExportFormat,
+    output_path: &Path,
+) -> Result<PathBuf, AppError> {
+    let report = collect(pool, query)?;
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&report).map_err(|e| {
+            AppError::Other(Message::new("subject.report_invalid").with_param("details", e))
+        })?,
+        ExportFormat::Html => render_html(&report),
+    };
+    let path = archive::write_file(output_path, contents.as_bytes())?;
+
+    let conn = pool.get()?;
+    for record in &report.records {
+        AuditEntry::insert(
+            &conn,
+            Some(record.organization.id),
+            AUDIT_ACTION,
+            &serde_json::json!({
+                "member_id": record.member.id,
+                "contributions": record.contributions.len(),
+                "generations": record.generations.len(),
+            }),
+        )?;
+    }
+
+    Ok(path)
+}
+
+/// The person's row in a generation snapshot, matched by member id or PESEL.
+fn find_entry(
+    snapshot: &serde_json::Value,
+    member_id: i64,
+    pesel: &str,
+) -> Option<serde_json::Value> {
+    snapshot["contributions"]
+        .as_array()?
+        .iter()
+        .find(|row| {
+            row["member_id"].as_i64() == Some(member_id) || row["pesel"].as_str() == Some(pesel)
+        })
+        .cloned()
+}
+
+fn render_html(report: &SubjectReport) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"pl\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>Dane osobowe – PESEL {}</title>\n",
+        escape(&report.pesel)
+    ));
+    html.push_str(
+        "<style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse;margin-bottom:1.5em}\
+         th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}th{background:#f3f3f3}</style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!(
+        "<h1>Dane osobowe – PESEL {}</h1>\n<p>Raport wygenerowany {}</p>\n",
+        escape(&report.pesel),
+        escape(&report.created_at)
+    ));
+
+    for record in &report.records {
+        let org = &record.organization;
+        let m = &record.member;
+        let identifiers: Vec<String> = [("NIP", org.nip.as_str()), ("REGON", org.regon.as_str())]
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(label, value)| format!("{} {}", label, escape(value)))
+            .collect();
+        html.push_str(&format!("<h2>{}", escape(&org.name)));
+        if !identifiers.is_empty() {
+            html.push_str(&format!(" ({})", identifiers.join(", ")));
+        }
+        html.push_str("</h2>\n");
+
+        html.push_str("<h3>Dane uczestnika</h3>\n<table>\n");
+        for (label, value) in [
+            ("PESEL", m.pesel.as_str()),
+            ("Imię", &m.first_name),
+            ("Drugie imię", &m.second_name),
+            ("Nazwisko", &m.last_name),
+            ("Płeć", m.gender.as_str()),
+            ("Data urodzenia", &m.date_of_birth),
+            ("Obywatelstwo", &m.citizenship),
+            ("Rodzaj dokumentu", &m.doc_type),
+            ("Numer dokumentu", &m.doc_number),
+            ("Status", m.status.as_str()),
+            ("Data zakończenia", m.terminated_at.as_deref().unwrap_or("")),
+            ("Utworzono", &m.created_at),
+            ("Zmieniono", &m.updated_at),
+        ] {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                label,
+                escape(value)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        if !record.pesel_history.is_empty() {
+            html.push_str(
+                "<h3>Wcześniejsze numery PESEL</h3>\n<table>\n\
+                 <tr><th>PESEL</th><th>Stosowany do okresu</th><th>Zmieniono</th></tr>\n",
+            );
+            for h in &record.pesel_history {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>przed {}</td><td>{}</td></tr>\n",
+                    escape(h.pesel.as_str()),
+                    h.replaced_from(),
+                    escape(&h.changed_at),
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !record.details_history.is_empty() {
+            html.push_str(
+                "<h3>Wcześniejsze dane osobowe</h3>\n<table>\n\
+                 <tr><th>Imię</th><th>Drugie imię</th><th>Nazwisko</th><th>Obywatelstwo</th>\
+                 <th>Dokument</th><th>Stosowane do okresu</th><th>Zmieniono</th></tr>\n",
+            );
+            for d in &record.details_history {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{} {}</td><td>przed {}</td><td>{}</td></tr>\n",
+                    escape(&d.first_name),
+                    escape(&d.second_name),
+                    escape(&d.last_name),
+                    escape(&d.citizenship),
+                    escape(&d.doc_type),
+                    escape(&d.doc_number),
+                    d.replaced_from(),
+                    escape(&d.changed_at),
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !record.transfers.is_empty() {
+            html.push_str(
+                "<h3>Przeniesienia</h3>\n<table>\n<tr><th>Data</th><th>Z</th><th>Do</th></tr>\n",
+            );
+            for t in &record.transfers {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&t.transferred_on),
+                    escape(&t.from_organization_name),
+                    escape(&t.to_organization_name),
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("<h3>Składki</h3>\n");
+        if record.contributions.is_empty() {
+            html.push_str("<p>Brak składek.</p>\n");
+        } else {
+            html.push_str(
+                "<table>\n<tr><th>Okres</th><th>Prac. podst.</th><th>Prac. dod.</th>\
+                 <th>Firma podst.</th><th>Firma dod.</th><th>Obniż.</th></tr>\n",
+            );
+            for c in &record.contributions {
+                html.push_str(&format!(
+                    "<tr><td>{}-{:02}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    c.period_year,
+                    c.period_month,
+                    c.employee_basic,
+                    c.employee_additional,
+                    c.employer_basic,
+                    c.employer_additional,
+                    c.reduced_basic_flag,
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("<h3>Wygenerowane pliki</h3>\n");
+        if record.generations.is_empty() {
+            html.push_str("<p>Brak plików zawierających dane uczestnika.</p>\n");
+        } else {
+            html.push_str("<table>\n<tr><th>Okres</th><th>Wygenerowano</th><th>Plik</th></tr>\n");
+            for g in &record.generations {
+                html.push_str(&format!(
+                    "<tr><td>{}-{:02}</td><td>{}</td><td>{}</td></tr>\n",
+                    g.period_year,
+                    g.period_month,
+                    escape(&g.generated_at),
+                    escape(&g.file_path),
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,181 @@
+//! Archived organizations and members. Archiving only hides them; purging
+//! deletes them for good, which is allowed once no generation refers to them
+//! or once the organization's retention period has passed.
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::db::pool::DbPool;
+use crate::error::{AppError, Message};
+use crate::models::audit::AuditEntry;
+use crate::models::generation::Generation;
+use crate::models::journal::Journal;
+use crate::models::member::Member;
+use crate::models::organization::Organization;
+use crate::models::settings::OrganizationSettings;
+use crate::services::retention;
+
+pub const PURGE_MEMBER_AUDIT_ACTION: &str = "trash_purge_member";
+pub const PURGE_ORGANIZATION_AUDIT_ACTION: &str = "trash_purge_organization";
+
+#[derive(Debug, Serialize)]
+pub struct TrashedOrganization {
+    pub organization: Organization,
+    /// Generations of the organization, deleted along with it.
+    pub generations: usize,
+    pub can_purge: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashedMember {
+    pub member: Member,
+    pub organization_name: String,
+    /// Generations whose snapshot lists the member.
+    pub generations: usize,
+    pub can_purge: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Trash {
+    pub organizations: Vec<TrashedOrganization>,
+    /// Archived members of organizations that are not archived themselves.
+    pub members: Vec<TrashedMember>,
+}
+
+pub fn list(pool: &DbPool) -> Result<Trash, AppError> {
+    let conn = pool.get()?;
+
+    let mut organizations = Vec::new();
+    for organization in Organization::list_archived(&conn)? {
+        let generations = Generation::list(&conn, organization.id)?;
+        organizations.push(TrashedOrganization {
+            can_purge: organization_purgeable(&conn, organization.id, &generations)?,
+            generations: generations.len(),
+            organization,
+        });
+    }
+
+    let mut members = Vec::new();
+    for member in Member::list_archived(&conn)? {
+        let organization = Organization::get(&conn, member.organization_id)?;
+        if organization.archived_at.is_some() {
+            continue;
+        }
+        let generations =
+            Generation::snapshots_mentioning(&conn, member.organization_id, &[member.id])?.len();
+        members.push(TrashedMember {
+            can_purge: member_purgeable(&conn, &member, generations)?,
+            generations,
+            organization_name: organization.name,
+            member,
+        });
+    }
+
+    Ok(Trash {
+        organizations,
+        members,
+    })
+}
+
+/// Delete an archived member with their contributions and history. The
+/// organization's undo journal is cut off, since it may refer to their rows.
+pub fn purge_member(pool: &DbPool, id: i64) -> Result<AuditEntry, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let member = Member::get(&tx, id)?;
+    if member.archived_at.is_none() {
+        return Err(AppError::Validation(Message::new("trash.not_archived")));
+    }
+    let generations =
+        Generation::snapshots_mentioning(&tx, member.organization_id, &[member.id])?.len();
+    if !member_purgeable(&tx, &member, generations)? {
+        return Err(AppError::Validation(Message::new("trash.purge_blocked")));
+    }
+
+    Member::delete(&tx, id)?;
+    // Ids only, as for every audit entry
+    let audit = AuditEntry::insert(
+        &tx,
+        Some(member.organization_id),
+        PURGE_MEMBER_AUDIT_ACTION,
+        &serde_json::json!({ "member_id": id, "generations": generations }),
+    )?;
+    Journal::cut_off(&tx, member.organization_id)?;
+    tx.commit()?;
+    Ok(audit)
+}
+
+/// Delete an archived organization with its members, contributions and
+/// generations. The audit entry outlives it, so it is not tied to the
+/// organization.
+pub fn purge_organization(pool: &DbPool, id: i64) -> Result<AuditEntry, AppError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let organization = Organization::get(&tx, id)?;
+    if organization.archived_at.is_none() {
+        return Err(AppError::Validation(Message::new("trash.not_archived")));
+    }
+    let generations = Generation::list(&tx, id)?;
+    if !organization_purgeable(&tx, id, &generations)? {
+        return Err(AppError::Validation(Message::new("trash.purge_blocked")));
+    }
+
+    Organization::delete(&tx, id)?;
+    let audit = AuditEntry::insert(
+        &tx,
+        None,
+        PURGE_ORGANIZATION_AUDIT_ACTION,
+        &serde_json::json!({ "organization_id": id, "generations": generations.len() }),
+    )?;
+    tx.commit()?;
+    Ok(audit)
+}
+
+/// A member can go once no generation lists them, or once they left on or
+/// before the retention cutoff.
+fn member_purgeable(
+    conn: &Connection,
+    member: &Member,
+    generations: usize,
+) -> Result<bool, AppError> {
+    if generations == 0 {
+        return Ok(true);
+    }
+    let Some(cutoff) = cutoff(conn, member.organization_id)? else {
+        return Ok(false);
+    };
+    Ok(member
+        .terminated_at
+        .as_deref()
+        .is_some_and(|day| day <= cutoff.to_string().as_str()))
+}
+
+/// An organization can go once it has no generations, or once its latest one
+/// is past the retention cutoff.
+fn organization_purgeable(
+    conn: &Connection,
+    organization_id: i64,
+    generations: &[Generation],
+) -> Result<bool, AppError> {
+    if generations.is_empty() {
+        return Ok(true);
+    }
+    let Some(cutoff) = cutoff(conn, organization_id)? else {
+        return Ok(false);
+    };
+    let cutoff = cutoff.to_string();
+    Ok(generations.iter().all(|g| {
+        g.generated_at
+            .get(..10)
+            .is_some_and(|day| day <= cutoff.as_str())
+    }))
+}
+
+/// Without a retention policy, data referred to by a generation is kept.
+fn cutoff(conn: &Connection, organization_id: i64) -> Result<Option<NaiveDate>, AppError> {
+    OrganizationSettings::get(conn, organization_id)?
+        .retention_years
+        .map(retention::cutoff)
+        .transpose()
+}
@@ -1,8 +1,5 @@
-use r2d2::Pool;
-use r2d2_sqlite::SqliteConnectionManager;
-
-pub type DbPool = Pool<SqliteConnectionManager>;
+pub use crate::db::database::Database;
 
 pub struct AppState {
-    pub db: DbPool,
+    pub db: Database,
 }
@@ -0,0 +1,82 @@
+use crate::error::{AppError, Message};
+
+/// Check a bank account given as a Polish NRB (26 digits) or as an IBAN,
+/// spaces allowed. `field` names the form field reported on error.
+pub fn validate(account: &str, field: &'static str) -> Result<(), AppError> {
+    let compact: String = account
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let iban = if compact.chars().all(|c| c.is_ascii_digit()) {
+        format!("PL{}", compact)
+    } else {
+        compact
+    };
+
+    // Checked before any slicing, which is by byte
+    let valid_form = iban.chars().all(|c| c.is_ascii_alphanumeric())
+        && iban.len() >= 15
+        && iban.len() <= 34
+        && iban[..2].chars().all(|c| c.is_ascii_uppercase());
+    if !valid_form || (iban.starts_with("PL") && iban.len() != 28) {
+        return Err(AppError::Validation(
+            Message::new("bank_account.length").with_field(field),
+        ));
+    }
+
+    // ISO 13616: country code and check digits move to the end, letters
+    // become 10..35, and the number must leave 1 modulo 97
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |acc, c| {
+            let value = c.to_digit(36).unwrap();
+            if value < 10 {
+                (acc * 10 + value) % 97
+            } else {
+                (acc * 100 + value) % 97
+            }
+        });
+    if remainder != 1 {
+        return Err(AppError::Validation(
+            Message::new("bank_account.checksum").with_field(field),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_account() {
+        assert!(validate("61 1090 1014 0000 0712 1981 2874", "bank_account").is_ok());
+        assert!(validate("PL61109010140000071219812874", "bank_account").is_ok());
+        assert!(validate("DE89 3704 0044 0532 0130 00", "bank_account").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_account() {
+        assert!(validate("61 1090 1014 0000 0712 1981 2875", "bank_account").is_err());
+        assert!(validate("1090 1014 0000 0712", "bank_account").is_err());
+        assert!(validate("PL6110901014000007121981287", "bank_account").is_err());
+    }
+
+    #[test]
+    fn test_non_ascii_account_is_rejected() {
+        for account in [
+            "1ąąąąąąąąąąąąąą",
+            "ŁÓ61 1090 1014 0000 0712 1981 2874",
+            "PL61żółć",
+        ] {
+            let err = validate(account, "bank_account").unwrap_err();
+            match err {
+                AppError::Validation(message) => assert_eq!(message.key, "bank_account.length"),
+                other => panic!("expected a validation error, got {:?}", other),
+            }
+        }
+    }
+}
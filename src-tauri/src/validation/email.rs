@@ -0,0 +1,42 @@
+use crate::error::{AppError, Message};
+
+/// E-mail address: `local@domain`, the domain having at least two labels.
+/// Only the shape is checked, not whether the mailbox exists.
+pub fn validate(email: &str) -> Result<(), AppError> {
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|label| !label.is_empty())
+                && !email.chars().any(|c| c.is_whitespace() || c.is_control())
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(AppError::Validation(
+            Message::new("email.format").with_field("email"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_email() {
+        assert!(validate("kadry@firma.pl").is_ok());
+        assert!(validate("jan.kowalski+ppk@mail.firma.com.pl").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_email() {
+        assert!(validate("kadry.firma.pl").is_err());
+        assert!(validate("kadry@firma").is_err());
+        assert!(validate("kadry@@firma.pl").is_err());
+        assert!(validate("kadry@firma..pl").is_err());
+        assert!(validate("jan kowalski@firma.pl").is_err());
+    }
+}
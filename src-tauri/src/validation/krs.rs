@@ -0,0 +1,22 @@
+use crate::error::{AppError, Message};
+
+/// KRS number: 10 digits, leading zeros included. It has no check digit.
+pub fn validate(krs: &str) -> Result<(), AppError> {
+    if krs.len() != 10 || !krs.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::Validation(
+            Message::new("krs.length").with_field("krs"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_krs() {
+        assert!(validate("0000123456").is_ok());
+        assert!(validate("123456").is_err());
+    }
+}
@@ -1,3 +1,8 @@
+pub mod bank_account;
+pub mod email;
+pub mod krs;
 pub mod nip;
 pub mod pesel;
+pub mod phone;
+pub mod postal_code;
 pub mod regon;
@@ -1,4 +1,4 @@
-use crate::error::AppError;
+use crate::error::{AppError, Message};
 
 const WEIGHTS: [u32; 9] = [6, 5, 7, 2, 3, 4, 5, 6, 7];
 
@@ -7,19 +7,24 @@ pub fn validate(nip: &str) -> Result<(), AppError> {
     let nip_clean: String = nip.chars().filter(|c| c.is_ascii_digit()).collect();
 
     if nip_clean.len() != 10 {
-        return Err(AppError::Validation("NIP musi mieć 10 cyfr".into()));
+        return Err(AppError::Validation(
+            Message::new("nip.length").with_field("nip"),
+        ));
     }
 
-    let digits: Vec<u32> = nip_clean
-        .chars()
-        .map(|c| c.to_digit(10).unwrap())
-        .collect();
+    let digits: Vec<u32> = nip_clean.chars().map(|c| c.to_digit(10).unwrap()).collect();
 
-    let sum: u32 = digits[..9].iter().zip(WEIGHTS.iter()).map(|(d, w)| d * w).sum();
+    let sum: u32 = digits[..9]
+        .iter()
+        .zip(WEIGHTS.iter())
+        .map(|(d, w)| d * w)
+        .sum();
     let check = sum % 11;
 
     if check == 10 || check != digits[9] {
-        return Err(AppError::Validation("Nieprawidłowa suma kontrolna NIP".into()));
+        return Err(AppError::Validation(
+            Message::new("nip.checksum").with_field("nip"),
+        ));
     }
 
     Ok(())
@@ -0,0 +1,45 @@
+use crate::error::{AppError, Message};
+
+/// Phone number: nine digits, or an international one starting with `+` and
+/// its country code, at most 15 digits. Spaces and dashes between digits are
+/// allowed.
+pub fn validate(phone: &str) -> Result<(), AppError> {
+    let (international, number) = match phone.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, phone),
+    };
+    let digits: String = number.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+    let length_ok = if international {
+        (8..=15).contains(&digits.len())
+    } else {
+        digits.len() == 9
+    };
+    let separators_ok = !number.starts_with([' ', '-']) && !number.ends_with([' ', '-']);
+    if !length_ok || !separators_ok || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::Validation(
+            Message::new("phone.format").with_field("phone"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_phone() {
+        assert!(validate("600123456").is_ok());
+        assert!(validate("600 123 456").is_ok());
+        assert!(validate("22-555-01-02").is_ok());
+        assert!(validate("+48 600 123 456").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_phone() {
+        assert!(validate("60012345").is_err());
+        assert!(validate("600-123-45a").is_err());
+        assert!(validate("+48").is_err());
+        assert!(validate(" 600123456").is_err());
+    }
+}
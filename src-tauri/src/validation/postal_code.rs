@@ -0,0 +1,34 @@
+use crate::error::{AppError, Message};
+
+/// Polish postal code, `NN-NNN`.
+pub fn validate(code: &str) -> Result<(), AppError> {
+    let bytes = code.as_bytes();
+    let valid = bytes.len() == 6
+        && bytes[2] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 2 || b.is_ascii_digit());
+    if !valid {
+        return Err(AppError::Validation(
+            Message::new("postal_code.format").with_field("postal_code"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_postal_code() {
+        assert!(validate("00-950").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_postal_code() {
+        assert!(validate("00950").is_err());
+        assert!(validate("0O-950").is_err());
+    }
+}
@@ -1,12 +1,17 @@
-use crate::error::AppError;
+use crate::error::{AppError, Message};
 
 const WEIGHTS: [u32; 8] = [8, 9, 2, 3, 4, 5, 6, 7];
+const WEIGHTS_14: [u32; 13] = [2, 4, 8, 5, 0, 9, 7, 3, 6, 1, 2, 4, 8];
 
+/// REGON of 9 digits, or of 14 for a local unit. The first nine digits of a
+/// 14-digit REGON are the REGON of the parent entity and are checked too.
 pub fn validate(regon: &str) -> Result<(), AppError> {
     let regon_clean: String = regon.chars().filter(|c| c.is_ascii_digit()).collect();
 
-    if regon_clean.len() != 9 {
-        return Err(AppError::Validation("REGON musi mieć 9 cyfr".into()));
+    if regon_clean.len() != 9 && regon_clean.len() != 14 {
+        return Err(AppError::Validation(
+            Message::new("regon.length").with_field("regon"),
+        ));
     }
 
     let digits: Vec<u32> = regon_clean
@@ -14,17 +19,26 @@ pub fn validate(regon: &str) -> Result<(), AppError> {
         .map(|c| c.to_digit(10).unwrap())
         .collect();
 
-    let sum: u32 = digits[..8].iter().zip(WEIGHTS.iter()).map(|(d, w)| d * w).sum();
-    let check = sum % 11;
-    let check = if check == 10 { 0 } else { check };
-
-    if check != digits[8] {
-        return Err(AppError::Validation("Nieprawidłowa suma kontrolna REGON".into()));
+    if !checksum_matches(&digits[..9], &WEIGHTS)
+        || (digits.len() == 14 && !checksum_matches(&digits, &WEIGHTS_14))
+    {
+        return Err(AppError::Validation(
+            Message::new("regon.checksum").with_field("regon"),
+        ));
     }
 
     Ok(())
 }
 
+/// The last digit is the weighted sum of the others modulo 11, 10 counting as 0.
+fn checksum_matches(digits: &[u32], weights: &[u32]) -> bool {
+    let (check_digit, rest) = digits.split_last().unwrap();
+    let sum: u32 = rest.iter().zip(weights.iter()).map(|(d, w)| d * w).sum();
+    let check = sum % 11;
+    let check = if check == 10 { 0 } else { check };
+    check == *check_digit
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,10 +46,15 @@ mod tests {
     #[test]
     fn test_valid_regon() {
         assert!(validate("123456785").is_ok());
+        assert!(validate("12345678500010").is_ok());
     }
 
     #[test]
     fn test_invalid_regon() {
         assert!(validate("123456789").is_err());
+        assert!(validate("12345678500011").is_err());
+        // Parent REGON with a wrong check digit
+        assert!(validate("12345678900010").is_err());
+        assert!(validate("1234567850001").is_err());
     }
 }
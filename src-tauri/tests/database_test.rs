@@ -0,0 +1,297 @@
+use app_lib::db::backup::{BackupKind, BackupOptions};
+use app_lib::db::database::Database;
+use app_lib::db::migrations;
+use app_lib::domain::codes::EmployerIdType;
+use app_lib::error::AppError;
+use app_lib::models::organization::{CreateOrganization, EmployerProfile};
+use app_lib::secret::Password;
+use app_lib::services::organizations;
+
+fn temp_db() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ppk-db-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("ppk_gen.db")
+}
+
+fn create_org(db: &Database) {
+    organizations::create(
+        &db.pool().unwrap(),
+        &CreateOrganization {
+            name: "Test Org".to_string(),
+            nip: "5261040828".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+}
+
+fn org_count(db: &Database) -> usize {
+    organizations::list(&db.pool().unwrap()).unwrap().len()
+}
+
+#[test]
+fn test_plain_database_opens_unlocked() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    let status = db.status();
+    assert!(!status.encrypted);
+    assert!(!status.locked);
+
+    drop(db);
+    let db = Database::open(&path).unwrap();
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_encrypt_existing_database_and_unlock() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    let status = db.encrypt(&Password::new("tajne-haslo")).unwrap();
+    assert!(status.encrypted);
+    assert!(!status.locked);
+    assert_eq!(org_count(&db), 1);
+
+    // The file no longer contains readable SQLite pages
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(!bytes.starts_with(b"SQLite format 3"));
+    drop(db);
+
+    let db = Database::open(&path).unwrap();
+    assert!(db.status().locked);
+    assert!(matches!(db.pool(), Err(AppError::Locked)));
+    assert!(db.unlock(&Password::new("zle-haslo")).is_err());
+    assert!(db.status().locked);
+
+    db.unlock(&Password::new("tajne-haslo")).unwrap();
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_encrypt_waits_for_connections_in_use() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    // A command that checked out a connection before the encryption started
+    let pool = db.pool().unwrap();
+    let conn = pool.get().unwrap();
+    let started = std::time::Instant::now();
+    let command = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        drop(conn);
+    });
+    db.encrypt(&Password::new("tajne-haslo")).unwrap();
+    command.join().unwrap();
+    assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+
+    // A handle kept from before cannot write past the swap
+    let stale = pool.get().unwrap();
+    assert!(stale
+        .execute("UPDATE organizations SET name = 'Zmieniona'", [])
+        .is_err());
+    drop(stale);
+    assert_eq!(
+        organizations::list(&db.pool().unwrap()).unwrap()[0].name,
+        "Test Org"
+    );
+}
+
+#[test]
+fn test_encrypt_converts_automatic_backups() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+    let backup = db.auto_backup(BackupKind::Daily).unwrap();
+
+    db.encrypt(&Password::new("tajne-haslo")).unwrap();
+
+    // The backup is kept, but its database is no longer readable without the key
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(&backup).unwrap()).unwrap();
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut zip.by_index(0).unwrap(), &mut bytes).unwrap();
+    assert!(!bytes.starts_with(b"SQLite format 3"));
+
+    create_org(&db);
+    db.restore(&backup, None).unwrap();
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_change_database_password() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+    db.encrypt(&Password::new("stare-haslo")).unwrap();
+
+    assert!(db
+        .change_password(&Password::new("zle-haslo"), &Password::new("nowe-haslo"))
+        .is_err());
+    assert!(db
+        .change_password(&Password::new("stare-haslo"), &Password::new("krotkie"))
+        .is_err());
+    let backup = db.auto_backup(BackupKind::Daily).unwrap();
+    db.change_password(&Password::new("stare-haslo"), &Password::new("nowe-haslo"))
+        .unwrap();
+    assert_eq!(org_count(&db), 1);
+
+    // Backups taken under the old password still restore
+    create_org(&db);
+    db.restore(&backup, None).unwrap();
+    assert_eq!(org_count(&db), 1);
+    drop(db);
+
+    let db = Database::open(&path).unwrap();
+    assert!(db.unlock(&Password::new("stare-haslo")).is_err());
+    db.unlock(&Password::new("nowe-haslo")).unwrap();
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_change_password_waits_for_connections_in_use() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+    db.encrypt(&Password::new("stare-haslo")).unwrap();
+
+    let pool = db.pool().unwrap();
+    let conn = pool.get().unwrap();
+    let started = std::time::Instant::now();
+    let command = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        drop(conn);
+    });
+    db.change_password(&Password::new("stare-haslo"), &Password::new("nowe-haslo"))
+        .unwrap();
+    command.join().unwrap();
+    assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+
+    // A handle kept from before cannot write past the swap
+    let stale = pool.get().unwrap();
+    assert!(stale
+        .execute("UPDATE organizations SET name = 'Zmieniona'", [])
+        .is_err());
+    drop(stale);
+    assert_eq!(
+        organizations::list(&db.pool().unwrap()).unwrap()[0].name,
+        "Test Org"
+    );
+}
+
+#[test]
+fn test_backup_and_restore_with_password() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    let target = path.with_file_name("kopia.zip");
+    let options = BackupOptions {
+        compress: true,
+        password: Some(Password::new("haslo-kopii")),
+    };
+    db.backup(&target, &options).unwrap();
+    create_org(&db);
+    assert_eq!(org_count(&db), 2);
+
+    assert!(db.restore(&target, None).is_err());
+    assert!(db
+        .restore(&target, Some(&Password::new("zle-haslo")))
+        .is_err());
+    assert_eq!(org_count(&db), 2);
+
+    db.restore(&target, Some(&Password::new("haslo-kopii")))
+        .unwrap();
+    assert_eq!(org_count(&db), 1);
+
+    // The state from before the restore was kept
+    let backups = db.list_backups().unwrap();
+    assert!(backups.iter().any(|b| b.kind == BackupKind::Restore));
+}
+
+#[test]
+fn test_backup_of_encrypted_database_restores() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+    db.encrypt(&Password::new("tajne-haslo")).unwrap();
+
+    let target = path.with_file_name("kopia.db");
+    db.backup(&target, &BackupOptions::default()).unwrap();
+    let bytes = std::fs::read(&target).unwrap();
+    assert!(!bytes.starts_with(b"SQLite format 3"));
+
+    create_org(&db);
+    db.restore(&target, None).unwrap();
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_restore_rejects_backup_from_newer_version() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    let target = path.with_file_name("kopia.db");
+    db.backup(&target, &BackupOptions::default()).unwrap();
+    {
+        let conn = rusqlite::Connection::open(&target).unwrap();
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES ('999_future')",
+            [],
+        )
+        .unwrap();
+    }
+
+    create_org(&db);
+    let err = db.restore(&target, None).unwrap_err();
+    assert!(err.to_string().contains("nowszej wersji"));
+    assert_eq!(org_count(&db), 2);
+}
+
+#[test]
+fn test_restore_rejects_file_that_is_not_a_backup() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+
+    let target = path.with_file_name("notatki.db");
+    std::fs::write(&target, "to nie jest baza danych").unwrap();
+    assert!(db.restore(&target, None).is_err());
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_pending_migrations_are_preceded_by_backup() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+    create_org(&db);
+    {
+        // Roll the last migrations back so reopening applies them again
+        let conn = db.pool().unwrap().get().unwrap();
+        migrations::revert_to(&conn, Some("002_settings")).unwrap();
+    }
+    assert!(db.list_backups().unwrap().is_empty());
+    drop(db);
+
+    let db = Database::open(&path).unwrap();
+    let backups = db.list_backups().unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].kind, BackupKind::Migration);
+    assert_eq!(org_count(&db), 1);
+}
+
+#[test]
+fn test_daily_backup_is_taken_once_a_day() {
+    let path = temp_db();
+    let db = Database::open(&path).unwrap();
+
+    assert!(db.daily_backup().unwrap().is_some());
+    assert!(db.daily_backup().unwrap().is_none());
+    assert_eq!(db.list_backups().unwrap().len(), 1);
+}
@@ -0,0 +1,113 @@
+-- Database as left by the app at schema version 006_operation_journal, with sample data.
+BEGIN TRANSACTION;
+CREATE TABLE app_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+INSERT INTO "app_settings" VALUES('archive_dir','/fixture/archive','2025-02-01 10:00:00');
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    details_json TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+INSERT INTO "audit_log" VALUES(1,1,'subject_access_export','{"member_id":1,"contributions":1,"generations":1}','2025-03-01 10:00:00');
+CREATE TABLE "contributions" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    member_id INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL CHECK(period_month >= 1 AND period_month <= 12),
+    employee_basic INTEGER NOT NULL DEFAULT 0 CHECK(employee_basic >= 0),
+    employee_additional INTEGER NOT NULL DEFAULT 0 CHECK(employee_additional >= 0),
+    employer_basic INTEGER NOT NULL DEFAULT 0 CHECK(employer_basic >= 0),
+    employer_additional INTEGER NOT NULL DEFAULT 0 CHECK(employer_additional >= 0),
+    reduced_basic_flag TEXT NOT NULL DEFAULT 'N' CHECK(reduced_basic_flag IN ('T', 'N')),
+    source TEXT NOT NULL DEFAULT 'manual' CHECK(source IN ('manual', 'prefilled')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(member_id, period_year, period_month)
+);
+INSERT INTO "contributions" VALUES(1,1,2025,11,9438,0,7078,0,'N','manual','2025-12-02 09:00:00');
+INSERT INTO "contributions" VALUES(2,2,2025,5,18892,0,14169,0,'N','prefilled','2025-06-02 09:00:00');
+CREATE TABLE "generations" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    period_year INTEGER NOT NULL,
+    period_month INTEGER NOT NULL,
+    generated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    snapshot_json TEXT NOT NULL,
+    file_path TEXT NOT NULL DEFAULT '',
+    total_employee_basic INTEGER NOT NULL DEFAULT 0,
+    total_employer_basic INTEGER NOT NULL DEFAULT 0,
+    member_count INTEGER NOT NULL DEFAULT 0,
+    checksum TEXT NOT NULL DEFAULT '',
+    encrypted INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO "generations" VALUES(1,1,2025,11,'2025-12-03 08:00:00','{"organization":{"id":1,"name":"Fixture Org","nip":"5261040828","regon":"123456785","contact_person":"Jan Kowalski","created_at":"2025-01-15 10:00:00","updated_at":"2025-01-15 10:00:00"},"contributions":[{"id":1,"member_id":1,"period_year":2025,"period_month":11,"employee_basic":"94.38","employee_additional":"0.00","employer_basic":"70.78","employer_additional":"0.00","reduced_basic_flag":"N","source":"manual","updated_at":"2025-12-02 09:00:00","pesel":"85032212342","first_name":"Maria","last_name":"Testowa","gender":"K","date_of_birth":"1985-03-22","citizenship":"PL","second_name":"","doc_type":"","doc_number":"","member_status":"active"}],"period":{"year":2025,"month":11}}','/fixture/archive/1/SKLADKA.zip',9438,7078,1,'abc123',1);
+CREATE TABLE members (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    pesel TEXT NOT NULL,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    gender TEXT NOT NULL CHECK(gender IN ('M', 'K')),
+    date_of_birth TEXT NOT NULL,
+    citizenship TEXT NOT NULL DEFAULT 'PL',
+    second_name TEXT NOT NULL DEFAULT '',
+    doc_type TEXT NOT NULL DEFAULT '',
+    doc_number TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active', 'resigned', 'terminated')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')), terminated_at TEXT, anonymized_at TEXT,
+    UNIQUE(organization_id, pesel)
+);
+INSERT INTO "members" VALUES(1,1,'85032212342','Maria','Testowa','K','1985-03-22','PL','','','','active','2025-01-15 10:00:00','2025-01-15 10:00:00',NULL,NULL);
+INSERT INTO "members" VALUES(2,1,'92061578905','Anna','Przykladowa','K','1992-06-15','PL','','','','terminated','2025-01-15 10:00:00','2025-06-30 12:00:00','2025-06-30',NULL);
+CREATE TABLE operation_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    changes_json TEXT NOT NULL,
+    undone INTEGER NOT NULL DEFAULT 0 CHECK(undone IN (0, 1)),
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+INSERT INTO "operation_journal" VALUES(1,1,'contribution.upsert','[{"key":{"table":"contributions","member_id":1,"period_year":2025,"period_month":11},"before":{"id":1,"member_id":1,"period_year":2025,"period_month":11,"employee_basic":0,"employee_additional":0,"employer_basic":7078,"employer_additional":0,"reduced_basic_flag":"N","source":"manual","updated_at":"2025-12-02 09:00:00"},"after":{"id":1,"member_id":1,"period_year":2025,"period_month":11,"employee_basic":9438,"employee_additional":0,"employer_basic":7078,"employer_additional":0,"reduced_basic_flag":"N","source":"manual","updated_at":"2025-12-02 09:00:00"}}]',0,'2026-03-01 10:00:00');
+CREATE TABLE organization_settings (
+    organization_id INTEGER PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    output_dir TEXT NOT NULL DEFAULT '',
+    file_name_template TEXT NOT NULL DEFAULT '',
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+, zip_password TEXT NOT NULL DEFAULT '', retention_years INTEGER);
+INSERT INTO "organization_settings" VALUES(1,'/fixture/out','{nip}_{period}.zip','2025-02-01 10:00:00','fixture-password',5);
+CREATE TABLE organizations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    nip TEXT NOT NULL,
+    regon TEXT NOT NULL,
+    contact_person TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+INSERT INTO "organizations" VALUES(1,'Fixture Org','5261040828','123456785','Jan Kowalski','2025-01-15 10:00:00','2025-01-15 10:00:00');
+CREATE TABLE schema_migrations (version TEXT PRIMARY KEY, applied_at TEXT NOT NULL DEFAULT (datetime('now')));
+INSERT INTO "schema_migrations" VALUES('001_initial','2025-01-15 10:00:00');
+INSERT INTO "schema_migrations" VALUES('002_settings','2025-01-15 10:00:00');
+INSERT INTO "schema_migrations" VALUES('003_zip_password','2025-01-15 10:00:00');
+INSERT INTO "schema_migrations" VALUES('004_retention','2025-01-15 10:00:00');
+INSERT INTO "schema_migrations" VALUES('005_money_grosze','2026-02-01 10:00:00');
+INSERT INTO "schema_migrations" VALUES('006_operation_journal','2026-03-01 10:00:00');
+CREATE INDEX idx_members_org ON members(organization_id);
+CREATE INDEX idx_audit_log_org ON audit_log(organization_id);
+CREATE INDEX idx_contributions_member ON contributions(member_id);
+CREATE INDEX idx_contributions_period ON contributions(period_year, period_month);
+CREATE INDEX idx_generations_org ON generations(organization_id);
+CREATE INDEX idx_operation_journal_org ON operation_journal(organization_id, undone);
+DELETE FROM "sqlite_sequence";
+INSERT INTO "sqlite_sequence" VALUES('organizations',1);
+INSERT INTO "sqlite_sequence" VALUES('members',2);
+INSERT INTO "sqlite_sequence" VALUES('audit_log',1);
+INSERT INTO "sqlite_sequence" VALUES('contributions',2);
+INSERT INTO "sqlite_sequence" VALUES('generations',1);
+INSERT INTO "sqlite_sequence" VALUES('operation_journal',1);
+COMMIT;
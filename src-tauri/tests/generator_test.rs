@@ -1,19 +1,31 @@
+use app_lib::domain::codes::{ContributionSource, EmployerIdType, Gender, MemberStatus, YesNo};
+use app_lib::domain::identifiers::{Nip, Pesel, Regon};
+use app_lib::domain::money::Money;
+use app_lib::domain::period::Period;
 use app_lib::generator;
 use app_lib::models::contribution::ContributionWithMember;
-use app_lib::models::organization::Organization;
+use app_lib::models::organization::{EmployerProfile, Organization};
+use app_lib::secret::Password;
 
 fn sample_org() -> Organization {
     Organization {
         id: 1,
         name: "Test Org".to_string(),
-        nip: "5261040828".to_string(),
-        regon: "123456785".to_string(),
-        contact_person: "Jan Kowalski".to_string(),
+        nip: Nip::parse("5261040828").unwrap(),
+        regon: Regon::parse("123456785").unwrap(),
+        id_type: EmployerIdType::Nip,
+        pesel: None,
+        profile: EmployerProfile::default(),
         created_at: "2026-01-09".to_string(),
         updated_at: "2026-01-09".to_string(),
+        archived_at: None,
     }
 }
 
+fn period() -> Period {
+    Period::new(2025, 12).unwrap()
+}
+
 fn sample_contributions() -> Vec<ContributionWithMember> {
     vec![
         ContributionWithMember {
@@ -21,46 +33,46 @@ fn sample_contributions() -> Vec<ContributionWithMember> {
             member_id: 1,
             period_year: 2025,
             period_month: 12,
-            employee_basic: "94.38".to_string(),
-            employee_additional: "0.00".to_string(),
-            employer_basic: "70.78".to_string(),
-            employer_additional: "0.00".to_string(),
-            reduced_basic_flag: "N".to_string(),
-            source: "manual".to_string(),
+            employee_basic: Money::from_grosze(9438),
+            employee_additional: Money::ZERO,
+            employer_basic: Money::from_grosze(7078),
+            employer_additional: Money::ZERO,
+            reduced_basic_flag: YesNo::No,
+            source: ContributionSource::Manual,
             updated_at: "2026-01-09".to_string(),
-            pesel: "85032212342".to_string(),
+            pesel: Pesel::parse("85032212342").unwrap(),
             first_name: "MARIA".to_string(),
             last_name: "TESTOWA".to_string(),
-            gender: "K".to_string(),
+            gender: Gender::K,
             date_of_birth: "1985-03-22".to_string(),
             citizenship: "PL".to_string(),
             second_name: "".to_string(),
             doc_type: "".to_string(),
             doc_number: "".to_string(),
-            member_status: "active".to_string(),
+            member_status: MemberStatus::Active,
         },
         ContributionWithMember {
             id: 2,
             member_id: 2,
             period_year: 2025,
             period_month: 12,
-            employee_basic: "188.92".to_string(),
-            employee_additional: "0.00".to_string(),
-            employer_basic: "141.69".to_string(),
-            employer_additional: "0.00".to_string(),
-            reduced_basic_flag: "N".to_string(),
-            source: "manual".to_string(),
+            employee_basic: Money::from_grosze(18892),
+            employee_additional: Money::ZERO,
+            employer_basic: Money::from_grosze(14169),
+            employer_additional: Money::ZERO,
+            reduced_basic_flag: YesNo::No,
+            source: ContributionSource::Manual,
             updated_at: "2026-01-09".to_string(),
-            pesel: "92061578905".to_string(),
+            pesel: Pesel::parse("92061578905").unwrap(),
             first_name: "EWA".to_string(),
             last_name: "PRZYKLADOWA ".to_string(),
-            gender: "K".to_string(),
+            gender: Gender::K,
             date_of_birth: "1992-06-15".to_string(),
             citizenship: "PL".to_string(),
             second_name: "".to_string(),
             doc_type: "D".to_string(),
             doc_number: "ABC123456".to_string(),
-            member_status: "active".to_string(),
+            member_status: MemberStatus::Active,
         },
     ]
 }
@@ -69,7 +81,7 @@ fn sample_contributions() -> Vec<ContributionWithMember> {
 fn test_xml_structure_matches_sample() {
     let org = sample_org();
     let contributions = sample_contributions();
-    let xml = generator::xml::build(&org, &contributions, 2025, 12);
+    let xml = generator::xml::build(&org, "Jan Kowalski", &contributions, period());
 
     // Verify CRLF line endings
     assert!(xml.contains("\r\n"), "XML must use CRLF line endings");
@@ -118,10 +130,26 @@ fn test_xml_structure_matches_sample() {
     assert!(!xml.contains("\t"), "XML must not contain tabs");
 }
 
+#[test]
+fn test_xml_escapes_free_text() {
+    let mut contributions = sample_contributions();
+    contributions[0].last_name = "O'Brien <Smith>".to_string();
+    let xml = generator::xml::build(
+        &sample_org(),
+        "Jan \"Kowalski\" jan&ola@firma.pl",
+        &contributions,
+        period(),
+    );
+
+    assert!(xml.contains("<KONTAKT>Jan &quot;Kowalski&quot; jan&amp;ola@firma.pl</KONTAKT>"));
+    assert!(xml.contains("<NAZWISKO>O&apos;BRIEN &lt;SMITH&gt;</NAZWISKO>"));
+    assert!(!xml.contains("<SMITH>"));
+}
+
 #[test]
 fn test_csv_structure_matches_sample() {
     let contributions = sample_contributions();
-    let csv = generator::csv::build(&contributions, 2025, 12);
+    let csv = generator::csv::build(&contributions, period());
 
     let lines: Vec<&str> = csv.split("\r\n").collect();
 
@@ -154,7 +182,7 @@ fn test_csv_structure_matches_sample() {
 #[test]
 fn test_csv_contains_last_name() {
     let contributions = sample_contributions();
-    let csv = generator::csv::build(&contributions, 2025, 12);
+    let csv = generator::csv::build(&contributions, period());
     let lines: Vec<&str> = csv.split("\r\n").collect();
 
     // Check first row has TESTOWA in it
@@ -175,7 +203,37 @@ fn test_zip_creates_valid_output() {
     assert!(result.csv_filename.ends_with(".csv"));
 
     // Verify it's a valid ZIP
+    let reader = std::io::Cursor::new(&result.zip_bytes);
+    let archive = zip::ZipArchive::new(reader).unwrap();
+    assert_eq!(archive.len(), 2);
+}
+
+#[test]
+fn test_encrypted_zip_requires_password() {
+    use std::io::Read;
+
+    let xml = "<PPK><WERSJA>test</WERSJA></PPK>";
+    let csv = "LP;NR_PESEL\r\n\"1\";\"12345678901\"";
+    let password = Password::new("tajne-haslo");
+    let result = generator::zip::build_encrypted(xml, csv, &password).unwrap();
+
     let reader = std::io::Cursor::new(&result.zip_bytes);
     let mut archive = zip::ZipArchive::new(reader).unwrap();
     assert_eq!(archive.len(), 2);
+
+    // Plain read must fail
+    assert!(archive.by_index(0).is_err());
+
+    // Wrong password must fail
+    assert!(archive.by_index_decrypt(0, b"zle-haslo").is_err());
+
+    let mut content = String::new();
+    archive
+        .by_name_decrypt(&result.xml_filename, b"tajne-haslo")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, xml);
+
+    assert_eq!(format!("{:?}", password), "Password(***)");
 }
@@ -0,0 +1,248 @@
+use app_lib::db::migrations;
+use app_lib::domain::codes::MemberStatus;
+use app_lib::domain::money::Money;
+use app_lib::error::AppError;
+use app_lib::models::contact::ContactPerson;
+use app_lib::models::contribution::Contribution;
+use app_lib::models::generation::Generation;
+use app_lib::models::member::Member;
+use app_lib::models::settings::OrganizationSettings;
+use rusqlite::Connection;
+
+/// Databases as left by every released schema version, oldest first.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "001_initial",
+        include_str!("fixtures/schema_001_initial.sql"),
+    ),
+    (
+        "002_settings",
+        include_str!("fixtures/schema_002_settings.sql"),
+    ),
+    (
+        "003_zip_password",
+        include_str!("fixtures/schema_003_zip_password.sql"),
+    ),
+    (
+        "004_retention",
+        include_str!("fixtures/schema_004_retention.sql"),
+    ),
+    (
+        "005_money_grosze",
+        include_str!("fixtures/schema_005_money_grosze.sql"),
+    ),
+    (
+        "006_operation_journal",
+        include_str!("fixtures/schema_006_operation_journal.sql"),
+    ),
+    (
+        "007_member_search",
+        include_str!("fixtures/schema_007_member_search.sql"),
+    ),
+    (
+        "008_member_transfers",
+        include_str!("fixtures/schema_008_member_transfers.sql"),
+    ),
+    (
+        "009_pesel_history",
+        include_str!("fixtures/schema_009_pesel_history.sql"),
+    ),
+    (
+        "010_member_details_history",
+        include_str!("fixtures/schema_010_member_details_history.sql"),
+    ),
+    (
+        "011_archive",
+        include_str!("fixtures/schema_011_archive.sql"),
+    ),
+    (
+        "012_employer_profile",
+        include_str!("fixtures/schema_012_employer_profile.sql"),
+    ),
+    (
+        "013_contact_persons",
+        include_str!("fixtures/schema_013_contact_persons.sql"),
+    ),
+    (
+        "014_employer_identification",
+        include_str!("fixtures/schema_014_employer_identification.sql"),
+    ),
+];
+
+fn open_fixture(sql: &str) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    // Dumps list tables alphabetically, children before their parents
+    conn.execute_batch("PRAGMA foreign_keys=OFF;").unwrap();
+    conn.execute_batch(sql).unwrap();
+    conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+    conn
+}
+
+/// Column names and declared types of every table, for comparing schemas.
+fn columns(conn: &Connection) -> Vec<(String, Vec<(String, String)>)> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .unwrap();
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    tables
+        .into_iter()
+        .map(|table| {
+            let mut stmt = conn
+                .prepare(&format!("PRAGMA table_info({})", table))
+                .unwrap();
+            let cols = stmt
+                .query_map([], |row| Ok((row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            (table, cols)
+        })
+        .collect()
+}
+
+#[test]
+fn test_fixtures_cover_every_migration() {
+    assert_eq!(FIXTURES.last().unwrap().0, migrations::latest_version());
+}
+
+#[test]
+fn test_every_fixture_upgrades_to_latest() {
+    for (version, sql) in FIXTURES {
+        let conn = open_fixture(sql);
+        assert_eq!(
+            migrations::current_version(&conn).unwrap().as_deref(),
+            Some(*version)
+        );
+
+        migrations::run(&conn).unwrap();
+        assert_eq!(
+            migrations::current_version(&conn).unwrap().as_deref(),
+            Some(migrations::latest_version()),
+            "upgrading from {}",
+            version
+        );
+
+        // Data written by the old version is still readable by the current models
+        let members = Member::list(&conn, 1).unwrap();
+        assert_eq!(members.len(), 2, "upgrading from {}", version);
+        let terminated = members
+            .iter()
+            .find(|m| m.status == MemberStatus::Terminated)
+            .unwrap();
+        assert!(
+            terminated.terminated_at.is_some(),
+            "upgrading from {}",
+            version
+        );
+
+        let contributions = Contribution::list_for_member(&conn, 1).unwrap();
+        assert_eq!(contributions[0].employee_basic, Money::from_grosze(9438));
+
+        let generations = Generation::list(&conn, 1).unwrap();
+        assert_eq!(generations.len(), 1);
+        assert_eq!(
+            generations[0].total_employee_basic,
+            Money::from_grosze(9438)
+        );
+
+        OrganizationSettings::get(&conn, 1).unwrap();
+
+        // The free-text contact became the default contact person
+        let contact = ContactPerson::get_default(&conn, 1).unwrap().unwrap();
+        assert_eq!(contact.name, "Jan Kowalski", "upgrading from {}", version);
+    }
+}
+
+#[test]
+fn test_reverting_restores_every_past_schema() {
+    let (_, latest) = FIXTURES.last().unwrap();
+    let conn = open_fixture(latest);
+
+    for (version, sql) in FIXTURES.iter().rev().skip(1) {
+        migrations::revert_to(&conn, Some(version)).unwrap();
+        assert_eq!(
+            migrations::current_version(&conn).unwrap().as_deref(),
+            Some(*version)
+        );
+        assert_eq!(
+            columns(&conn),
+            columns(&open_fixture(sql)),
+            "reverting to {}",
+            version
+        );
+    }
+
+    // And back up again without losing the rows that survived
+    migrations::run(&conn).unwrap();
+    assert_eq!(columns(&conn), columns(&open_fixture(latest)));
+    // Older versions have no trash, so the archived member is back in use
+    assert_eq!(Member::list(&conn, 1).unwrap().len(), 3);
+
+    migrations::revert_to(&conn, None).unwrap();
+    assert_eq!(migrations::current_version(&conn).unwrap(), None);
+}
+
+#[test]
+fn test_newer_database_is_refused() {
+    let (_, latest) = FIXTURES.last().unwrap();
+    let conn = open_fixture(latest);
+    conn.execute(
+        "INSERT INTO schema_migrations (version) VALUES ('999_future')",
+        [],
+    )
+    .unwrap();
+
+    let result = migrations::run(&conn);
+    assert!(matches!(result, Err(AppError::NewerSchema(ref v)) if v == "999_future"));
+}
+
+#[test]
+fn test_malformed_amount_stops_the_upgrade() {
+    let (_, sql) = FIXTURES
+        .iter()
+        .find(|(v, _)| *v == "004_retention")
+        .unwrap();
+    let conn = open_fixture(sql);
+    conn.execute(
+        "UPDATE contributions SET employer_basic = '12,50' WHERE id = 1",
+        [],
+    )
+    .unwrap();
+
+    assert!(migrations::run(&conn).is_err());
+    assert_eq!(
+        migrations::current_version(&conn).unwrap().as_deref(),
+        Some("004_retention")
+    );
+    let stored: String = conn
+        .query_row(
+            "SELECT employer_basic FROM contributions WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(stored, "12,50");
+
+    // Decimals with no digits on one side of the point were valid amounts
+    let conn = open_fixture(sql);
+    conn.execute(
+        "UPDATE contributions SET employee_additional = '.5', employer_additional = '1.'
+         WHERE id = 1",
+        [],
+    )
+    .unwrap();
+    migrations::run(&conn).unwrap();
+    let converted: (i64, i64) = conn
+        .query_row(
+            "SELECT employee_additional, employer_additional FROM contributions WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(converted, (50, 100));
+}
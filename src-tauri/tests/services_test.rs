@@ -0,0 +1,1866 @@
+use app_lib::db::pool::{create_memory_pool, DbPool};
+use app_lib::domain::codes::{ContributionSource, EmployerIdType, Gender, MemberStatus};
+use app_lib::domain::period::Period;
+use app_lib::error::AppError;
+use app_lib::models::contact::SaveContactPerson;
+use app_lib::models::contribution::UpsertContribution;
+use app_lib::models::member::{CreateMember, MemberFilter, MemberQuery, MemberSort, UpdateMember};
+use app_lib::models::organization::{CreateOrganization, EmployerProfile, UpdateOrganization};
+use app_lib::models::pesel_history::ChangePesel;
+use app_lib::models::settings::UpdateOrganizationSettings;
+use app_lib::models::transfer::TransferMember;
+use app_lib::secret::Password;
+use app_lib::services::merge::{self, ClashChoice, ClashResolution, MergeRequest};
+use app_lib::services::persons::{self, PersonFilter, PersonKey};
+use app_lib::services::prefill::{self, PrefillRequest, PrefillStrategy, SalaryBase, SkipReason};
+use app_lib::services::{
+    archive, audit, batch, contacts, contributions, generations, journal, members, organizations,
+    retention, settings, subject_access, trash,
+};
+use rust_decimal::Decimal;
+
+fn setup() -> (DbPool, i64) {
+    let pool = create_memory_pool().unwrap();
+    settings::init_defaults(&pool, &temp_dir()).unwrap();
+    let org = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Test Org".to_string(),
+            nip: "5261040828".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+    (pool, org.id)
+}
+
+fn add_member(pool: &DbPool, organization_id: i64, pesel: &str, last_name: &str) -> i64 {
+    members::create(
+        pool,
+        CreateMember {
+            organization_id,
+            pesel: pesel.to_string(),
+            first_name: "Maria".to_string(),
+            last_name: last_name.to_string(),
+            gender: String::new(),
+            date_of_birth: String::new(),
+            citizenship: None,
+            second_name: None,
+            doc_type: None,
+            doc_number: None,
+        },
+    )
+    .unwrap()
+    .id
+}
+
+fn temp_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("ppk-test-{}", uuid::Uuid::new_v4()))
+}
+
+/// The XML file of an unencrypted ZIP.
+fn generated_xml(path: &str) -> String {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+    let mut xml = String::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        if file.name().ends_with(".xml") {
+            std::io::Read::read_to_string(&mut file, &mut xml).unwrap();
+        }
+    }
+    xml
+}
+
+fn set_amounts(pool: &DbPool, member_id: i64, employee_basic: &str, employer_basic: &str) {
+    contributions::upsert(
+        pool,
+        &UpsertContribution {
+            member_id,
+            period_year: 2025,
+            period_month: 12,
+            employee_basic: Some(employee_basic.to_string()),
+            employee_additional: None,
+            employer_basic: Some(employer_basic.to_string()),
+            employer_additional: None,
+            reduced_basic_flag: None,
+        },
+    )
+    .unwrap();
+}
+
+/// An employee contribution for 2025-11, the period before `set_amounts`.
+fn set_november(pool: &DbPool, member_id: i64, employee_basic: &str) {
+    contributions::upsert(
+        pool,
+        &UpsertContribution {
+            member_id,
+            period_year: 2025,
+            period_month: 11,
+            employee_basic: Some(employee_basic.to_string()),
+            employee_additional: None,
+            employer_basic: None,
+            employer_additional: None,
+            reduced_basic_flag: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_create_organization_rejects_invalid_nip() {
+    let pool = create_memory_pool().unwrap();
+    let result = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Test Org".to_string(),
+            nip: "1234567890".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    );
+    assert!(result.is_err());
+    assert!(organizations::list(&pool).unwrap().is_empty());
+}
+
+#[test]
+fn test_create_member_derives_data_from_pesel() {
+    let (pool, org_id) = setup();
+    let id = add_member(&pool, org_id, "85032212342", "Testowa");
+
+    let member = members::get(&pool, id).unwrap();
+    assert_eq!(member.date_of_birth, "1985-03-22");
+    assert_eq!(member.gender, Gender::K);
+    assert_eq!(member.citizenship, "PL");
+}
+
+#[test]
+fn test_prefill_copies_latest_period() {
+    let (pool, org_id) = setup();
+    let first = add_member(&pool, org_id, "85032212342", "Testowa");
+    add_member(&pool, org_id, "92061578905", "Przykladowa");
+    set_amounts(&pool, first, "94.38", "70.78");
+
+    let request = PrefillRequest::copy_latest(org_id, Period::new(2026, 1).unwrap());
+    let result = prefill::apply(&pool, &request).unwrap();
+    assert_eq!(result.created, 2);
+
+    let rows = contributions::list_for_period(&pool, org_id, 2026, 1).unwrap();
+    let copied = rows.iter().find(|c| c.member_id == first).unwrap();
+    assert_eq!(copied.employee_basic.to_string(), "94.38");
+    assert_eq!(copied.source, ContributionSource::Prefilled);
+}
+
+#[test]
+fn test_prefill_strategies_respect_selection_and_manual_rows() {
+    let (pool, org_id) = setup();
+    let first = add_member(&pool, org_id, "85032212342", "Testowa");
+    let second = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    set_amounts(&pool, first, "100.00", "75.00");
+    set_amounts(&pool, second, "50.00", "37.50");
+    let january = Period::new(2026, 1).unwrap();
+
+    let scale = PrefillRequest {
+        strategy: PrefillStrategy::Scale {
+            source: Some(Period::new(2025, 12).unwrap()),
+            percent: Decimal::new(10, 0),
+        },
+        member_ids: Some(vec![first]),
+        ..PrefillRequest::copy_latest(org_id, january)
+    };
+    let preview = prefill::preview(&pool, &scale).unwrap();
+    assert_eq!(preview.rows.len(), 1);
+    assert_eq!(preview.rows[0].amounts.employee_basic.to_string(), "110.00");
+    assert_eq!(preview.rows[0].amounts.employer_basic.to_string(), "82.50");
+    assert!(contributions::list_for_period(&pool, org_id, 2026, 1)
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(prefill::apply(&pool, &scale).unwrap().created, 1);
+
+    // A hand-entered row is kept; the prefilled one is replaced only on request
+    contributions::upsert(
+        &pool,
+        &UpsertContribution {
+            member_id: second,
+            period_year: 2026,
+            period_month: 1,
+            employee_basic: Some("1.00".to_string()),
+            employee_additional: None,
+            employer_basic: None,
+            employer_additional: None,
+            reduced_basic_flag: None,
+        },
+    )
+    .unwrap();
+    let salary = PrefillRequest {
+        strategy: PrefillStrategy::Salary {
+            bases: vec![
+                SalaryBase {
+                    member_id: first,
+                    salary: "4719.00".parse().unwrap(),
+                },
+                SalaryBase {
+                    member_id: second,
+                    salary: "5000.00".parse().unwrap(),
+                },
+            ],
+            employee_additional_rate: Decimal::ZERO,
+            employer_additional_rate: Decimal::ZERO,
+        },
+        ..PrefillRequest::copy_latest(org_id, january)
+    };
+    let preview = prefill::preview(&pool, &salary).unwrap();
+    assert!(preview.rows.is_empty());
+    let reasons: Vec<SkipReason> = preview.skipped.iter().map(|s| s.reason).collect();
+    assert_eq!(reasons, [SkipReason::Manual, SkipReason::Prefilled]);
+
+    let result = prefill::apply(
+        &pool,
+        &PrefillRequest {
+            overwrite_prefilled: true,
+            ..salary
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        (result.created, result.overwritten, result.skipped),
+        (0, 1, 1)
+    );
+
+    let rows = contributions::list_for_period(&pool, org_id, 2026, 1).unwrap();
+    let by_member = |id| rows.iter().find(|c| c.member_id == id).unwrap();
+    assert_eq!(by_member(first).employee_basic.to_string(), "94.38");
+    assert_eq!(by_member(first).employer_basic.to_string(), "70.79");
+    assert_eq!(by_member(second).employee_basic.to_string(), "1.00");
+    assert_eq!(by_member(second).source, ContributionSource::Manual);
+}
+
+#[test]
+fn test_upsert_rejects_invalid_period_and_amount() {
+    let (pool, org_id) = setup();
+    let member_id = add_member(&pool, org_id, "85032212342", "Testowa");
+
+    let result = contributions::upsert(
+        &pool,
+        &UpsertContribution {
+            member_id,
+            period_year: 2025,
+            period_month: 13,
+            employee_basic: None,
+            employee_additional: None,
+            employer_basic: None,
+            employer_additional: None,
+            reduced_basic_flag: None,
+        },
+    );
+    assert!(result.is_err());
+
+    let bad_amount = contributions::upsert(
+        &pool,
+        &UpsertContribution {
+            member_id,
+            period_year: 2025,
+            period_month: 12,
+            employee_basic: Some("12,50".to_string()),
+            employee_additional: None,
+            employer_basic: None,
+            employer_additional: None,
+            reduced_basic_flag: None,
+        },
+    );
+    match bad_amount {
+        Err(AppError::Validation(message)) => {
+            assert_eq!(message.key, "money.invalid");
+            assert_eq!(message.field, Some("employee_basic"));
+        }
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_upsert_batch_saves_all_rows_or_none() {
+    let (pool, org_id) = setup();
+    let first = add_member(&pool, org_id, "85032212342", "Testowa");
+    let second = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    let row = |member_id, employee_basic: &str| UpsertContribution {
+        member_id,
+        period_year: 2025,
+        period_month: 12,
+        employee_basic: Some(employee_basic.to_string()),
+        employee_additional: None,
+        employer_basic: None,
+        employer_additional: None,
+        reduced_basic_flag: None,
+    };
+
+    let rejected = contributions::upsert_batch(
+        &pool,
+        &[row(first, "94.38"), row(second, "1.001"), row(9999, "1.00")],
+    )
+    .unwrap();
+    assert_eq!(rejected.saved, 0);
+    let failed: Vec<(usize, &str)> = rejected
+        .errors
+        .iter()
+        .map(|e| (e.index, e.error.message().key))
+        .collect();
+    assert_eq!(failed, [(1, "money.scale"), (2, "member.not_found")]);
+    assert!(contributions::list_for_period(&pool, org_id, 2025, 12)
+        .unwrap()
+        .is_empty());
+
+    let saved =
+        contributions::upsert_batch(&pool, &[row(first, "94.38"), row(second, "188.92")]).unwrap();
+    assert_eq!(saved.saved, 2);
+    assert!(saved.errors.is_empty());
+    assert_eq!(
+        contributions::list_for_period(&pool, org_id, 2025, 12)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_generate_sums_totals_and_records_generation() {
+    let (pool, org_id) = setup();
+    let first = add_member(&pool, org_id, "85032212342", "Testowa");
+    let second = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    set_amounts(&pool, first, "94.38", "70.78");
+    set_amounts(&pool, second, "188.92", "141.69");
+
+    let dir = temp_dir();
+    let path = dir.join("SKLADKA_202512.zip");
+    let result = generations::generate(&pool, org_id, 2025, 12, Some(&path), None).unwrap();
+    assert_eq!(result.total_employee_basic.to_string(), "283.30");
+    assert_eq!(result.total_employer_basic.to_string(), "212.47");
+    assert_eq!(result.member_count, 2);
+    assert_eq!(
+        result.output_path.as_deref(),
+        Some(&*path.to_string_lossy())
+    );
+
+    let history = generations::list(&pool, org_id).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, result.generation.id);
+
+    // The archived copy is the one linked from the generation record
+    let archived = std::fs::read(&history[0].file_path).unwrap();
+    assert_eq!(archived, std::fs::read(&path).unwrap());
+    assert_eq!(archive::checksum(&archived), history[0].checksum);
+
+    let archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(archive.len(), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_export_copies_archived_file() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+
+    let first = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    assert!(first.output_path.is_none());
+
+    let dir = temp_dir();
+    let copy = dir.join("copy.zip");
+    let exported = generations::export(&pool, first.generation.id, &copy, None).unwrap();
+
+    assert_eq!(exported.total_employee_basic.to_string(), "94.38");
+    assert_eq!(
+        std::fs::read(&copy).unwrap(),
+        std::fs::read(&first.generation.file_path).unwrap()
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_generate_uses_configured_output_dir_and_template() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+
+    let dir = temp_dir();
+    settings::update_organization(
+        &pool,
+        org_id,
+        &UpdateOrganizationSettings {
+            output_dir: dir.to_string_lossy().into_owned(),
+            file_name_template: "{nip}_{period}_SKLADKA.zip".to_string(),
+            contact_template: String::new(),
+            zip_password: None,
+            retention_years: None,
+        },
+    )
+    .unwrap();
+
+    let result = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let expected = dir.join("5261040828_2025-12_SKLADKA.zip");
+    assert_eq!(
+        result.output_path.as_deref(),
+        Some(&*expected.to_string_lossy())
+    );
+    assert!(expected.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_failed_generation_leaves_no_files_or_record() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    pool.get()
+        .unwrap()
+        .execute_batch(
+            "CREATE TRIGGER fail_generation BEFORE INSERT ON generations
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+        )
+        .unwrap();
+
+    let dir = temp_dir();
+    let output = dir.join("out.zip");
+    let result = generations::generate(&pool, org_id, 2025, 12, Some(&output), None);
+    assert_eq!(result.unwrap_err().code(), "database");
+
+    assert!(!output.exists());
+    let archive_dir = std::path::PathBuf::from(settings::get_app(&pool).unwrap().archive_dir);
+    let archived = std::fs::read_dir(archive_dir.join(org_id.to_string())).unwrap();
+    assert_eq!(archived.count(), 0);
+    assert!(generations::list(&pool, org_id).unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_invalid_file_name_template_is_rejected() {
+    let (pool, org_id) = setup();
+    for template in [
+        "{nip}_{unknown}.zip",
+        "../{nip}.zip",
+        "{nip}.xml",
+        "{nip.zip",
+    ] {
+        let result = settings::update_organization(
+            &pool,
+            org_id,
+            &UpdateOrganizationSettings {
+                output_dir: String::new(),
+                file_name_template: template.to_string(),
+                contact_template: String::new(),
+                zip_password: None,
+                retention_years: None,
+            },
+        );
+        assert!(result.is_err(), "template {} should be rejected", template);
+    }
+}
+
+#[test]
+fn test_verify_archive_detects_missing_and_modified_files() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+
+    let first = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let second = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let third = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    std::fs::remove_file(&first.generation.file_path).unwrap();
+    std::fs::write(&second.generation.file_path, b"tampered").unwrap();
+
+    let checks = archive::verify(&pool, org_id).unwrap();
+    let status = |id| {
+        checks
+            .iter()
+            .find(|c| c.generation_id == id)
+            .unwrap()
+            .status
+    };
+    assert_eq!(status(first.generation.id), archive::ArchiveStatus::Missing);
+    assert_eq!(
+        status(second.generation.id),
+        archive::ArchiveStatus::Modified
+    );
+    assert_eq!(status(third.generation.id), archive::ArchiveStatus::Ok);
+}
+
+#[test]
+fn test_undo_and_redo_replay_the_journal_until_generation() {
+    let (pool, org_id) = setup();
+    let first = add_member(&pool, org_id, "85032212342", "Testowa");
+    let second = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    set_amounts(&pool, first, "94.38", "70.78");
+    set_amounts(&pool, second, "188.92", "141.69");
+    let amounts = |member_id| {
+        contributions::list_for_period(&pool, org_id, 2025, 12)
+            .unwrap()
+            .into_iter()
+            .find(|c| c.member_id == member_id)
+            .map(|c| c.employee_basic.to_string())
+    };
+
+    // Archiving a member hides their contributions; undo brings both back
+    members::archive(&pool, second).unwrap();
+    assert_eq!(amounts(second), None);
+    let undone = journal::undo(&pool, org_id).unwrap().unwrap();
+    assert_eq!(undone.action, "member.archive");
+    assert!(members::get(&pool, second).unwrap().archived_at.is_none());
+    assert_eq!(amounts(second).as_deref(), Some("188.92"));
+
+    journal::redo(&pool, org_id).unwrap().unwrap();
+    assert!(members::get(&pool, second).unwrap().archived_at.is_some());
+    assert_eq!(amounts(second), None);
+    journal::undo(&pool, org_id).unwrap().unwrap();
+
+    set_amounts(&pool, first, "1.00", "1.00");
+    journal::undo(&pool, org_id).unwrap().unwrap();
+    assert_eq!(amounts(first).as_deref(), Some("94.38"));
+    // A new edit drops what could have been redone
+    set_amounts(&pool, second, "2.00", "2.00");
+    assert!(journal::redo(&pool, org_id).unwrap().is_none());
+
+    generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    assert!(journal::undo(&pool, org_id).unwrap().is_none());
+    assert!(journal::list(&pool, org_id).unwrap().is_empty());
+}
+
+#[test]
+fn test_generate_without_contributions_fails() {
+    let (pool, org_id) = setup();
+    let path = temp_dir().join("SKLADKA.zip");
+    assert!(generations::generate(&pool, org_id, 2025, 12, Some(&path), None).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_batch_writes_zip_per_organization() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    let empty = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Empty Org".to_string(),
+            nip: "7680002466".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+
+    let dir = temp_dir();
+    let request = batch::BatchRequest {
+        organization_ids: None,
+        year: 2026,
+        month: 1,
+        output_dir: Some(dir.to_string_lossy().into_owned()),
+    };
+
+    let mut progress = Vec::new();
+    let summary = batch::generate_all(&pool, &request, |p| progress.push(p.current)).unwrap();
+
+    assert_eq!(progress, vec![1, 2]);
+    assert_eq!(summary.items.len(), 2);
+    assert_eq!(summary.warnings, 1);
+    assert_eq!(summary.failed, 1);
+
+    let ok = summary
+        .items
+        .iter()
+        .find(|i| i.organization_id == org_id)
+        .unwrap();
+    assert_eq!(ok.status, batch::BatchStatus::Warning);
+    assert_eq!(ok.messages[0].key, "batch.prefilled");
+    assert_eq!(ok.messages[0].params["count"], "1");
+    assert!(dir.join("5261040828_SKLADKA_2026_01.zip").exists());
+
+    let failed = summary
+        .items
+        .iter()
+        .find(|i| i.organization_id == empty.id)
+        .unwrap();
+    assert_eq!(failed.status, batch::BatchStatus::Failure);
+    assert_eq!(failed.messages[0].key, "generation.empty");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_batch_failure_leaves_the_period_as_it_was() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    let journal_before = journal::list(&pool, org_id).unwrap().len();
+
+    // The ZIP cannot be written where a directory of the same name stands
+    let dir = temp_dir();
+    std::fs::create_dir_all(dir.join("5261040828_SKLADKA_2026_01.zip")).unwrap();
+    let request = batch::BatchRequest {
+        organization_ids: None,
+        year: 2026,
+        month: 1,
+        output_dir: Some(dir.to_string_lossy().into_owned()),
+    };
+    let summary = batch::generate_all(&pool, &request, |_| {}).unwrap();
+    assert_eq!(summary.failed, 1);
+
+    assert!(contributions::list_for_period(&pool, org_id, 2026, 1)
+        .unwrap()
+        .is_empty());
+    assert_eq!(journal::list(&pool, org_id).unwrap().len(), journal_before);
+    assert!(generations::list(&pool, org_id).unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_configured_zip_password_encrypts_output_but_not_snapshot() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    settings::update_organization(
+        &pool,
+        org_id,
+        &UpdateOrganizationSettings {
+            output_dir: String::new(),
+            file_name_template: String::new(),
+            contact_template: String::new(),
+            zip_password: Some(Password::new("tajne-haslo")),
+            retention_years: None,
+        },
+    )
+    .unwrap();
+    assert!(
+        settings::get_organization(&pool, org_id)
+            .unwrap()
+            .has_zip_password
+    );
+
+    let result = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    assert!(result.generation.encrypted);
+
+    let file = std::fs::File::open(&result.generation.file_path).unwrap();
+    let mut archive = zip::ZipArchive::new(file).unwrap();
+    assert!(archive.by_index(0).is_err());
+    assert!(archive.by_index_decrypt(0, b"tajne-haslo").is_ok());
+
+    let stored = generations::get(&pool, result.generation.id).unwrap();
+    assert!(!stored.snapshot_json.contains("tajne-haslo"));
+}
+
+#[test]
+fn test_password_given_at_generation_time_overrides_configured() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+
+    let password = Password::new("jednorazowe");
+    let result = generations::generate(&pool, org_id, 2025, 12, None, Some(&password)).unwrap();
+    assert!(result.generation.encrypted);
+
+    let file = std::fs::File::open(&result.generation.file_path).unwrap();
+    let mut archive = zip::ZipArchive::new(file).unwrap();
+    assert!(archive.by_index_decrypt(0, b"jednorazowe").is_ok());
+
+    // Without the archive, rebuilding an encrypted generation needs the password
+    std::fs::remove_file(&result.generation.file_path).unwrap();
+    let copy = temp_dir().join("copy.zip");
+    assert!(generations::export(&pool, result.generation.id, &copy, None).is_err());
+    generations::export(&pool, result.generation.id, &copy, Some(&password)).unwrap();
+    assert!(copy.exists());
+}
+
+/// Mark a member as terminated on the given day.
+#[test]
+fn test_member_query_filters_sorts_and_pages() {
+    let (pool, org_id) = setup();
+    let testowa = add_member(&pool, org_id, "85032212342", "Testowa");
+    let przykladowa = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    let nowak = add_member(&pool, org_id, "90090515836", "Nowak");
+    members::update(
+        &pool,
+        nowak,
+        &UpdateMember {
+            first_name: "Jan".to_string(),
+            last_name: "Nowak".to_string(),
+            gender: Gender::M,
+            date_of_birth: "1990-09-05".to_string(),
+            citizenship: Some("UA".to_string()),
+            second_name: None,
+            doc_type: Some("1".to_string()),
+            doc_number: Some("AB_123".to_string()),
+            status: None,
+            effective_from: None,
+        },
+    )
+    .unwrap();
+
+    let ids = |query: MemberQuery| -> Vec<i64> {
+        members::query(&pool, org_id, &query)
+            .unwrap()
+            .members
+            .iter()
+            .map(|m| m.id)
+            .collect()
+    };
+    let filtered = |filter: MemberFilter| {
+        ids(MemberQuery {
+            filter,
+            ..Default::default()
+        })
+    };
+
+    assert_eq!(
+        ids(MemberQuery::default()),
+        vec![nowak, przykladowa, testowa]
+    );
+    let search = |text: &str| MemberFilter {
+        search: Some(text.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(filtered(search("testo")), vec![testowa]);
+    assert_eq!(filtered(search("maria 920615")), vec![przykladowa]);
+    // LIKE wildcards in the search are literal
+    assert_eq!(filtered(search("b_1")), vec![nowak]);
+    assert!(filtered(search("%")).is_empty());
+    assert_eq!(
+        filtered(MemberFilter {
+            citizenship: Some("ua".to_string()),
+            ..Default::default()
+        }),
+        vec![nowak]
+    );
+    assert_eq!(
+        filtered(MemberFilter {
+            missing_documents: true,
+            ..Default::default()
+        }),
+        vec![przykladowa, testowa]
+    );
+    let on = chrono::NaiveDate::from_ymd_opt(2026, 9, 5).unwrap();
+    assert_eq!(
+        filtered(MemberFilter {
+            min_age: Some(36),
+            age_on: Some(on),
+            ..Default::default()
+        }),
+        vec![nowak, testowa]
+    );
+    assert_eq!(
+        filtered(MemberFilter {
+            min_age: Some(34),
+            max_age: Some(36),
+            age_on: Some(on),
+            ..Default::default()
+        }),
+        vec![nowak, przykladowa]
+    );
+    let err = members::query(
+        &pool,
+        org_id,
+        &MemberQuery {
+            filter: MemberFilter {
+                min_age: Some(40),
+                max_age: Some(30),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), "validation");
+
+    // Pages continue after the cursor in the chosen order
+    let query = MemberQuery {
+        sort: MemberSort::DateOfBirth,
+        descending: true,
+        limit: Some(2),
+        ..Default::default()
+    };
+    let first = members::query(&pool, org_id, &query).unwrap();
+    assert_eq!(first.total, 3);
+    assert_eq!(
+        first.members.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![przykladowa, nowak]
+    );
+    assert_eq!(first.next, Some(nowak));
+    let second = members::query(
+        &pool,
+        org_id,
+        &MemberQuery {
+            after: first.next,
+            ..query
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        second.members.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![testowa]
+    );
+    assert_eq!(second.next, None);
+
+    for id in [testowa, przykladowa, nowak] {
+        set_amounts(&pool, id, "10.00", "15.00");
+    }
+    let rows =
+        contributions::list_for_period_filtered(&pool, org_id, 2025, 12, &search("nowak")).unwrap();
+    assert_eq!(
+        rows.iter().map(|r| r.member_id).collect::<Vec<_>>(),
+        vec![nowak]
+    );
+}
+
+#[test]
+fn test_member_search_ignores_case_of_polish_letters() {
+    let (pool, org_id) = setup();
+    let lukasiewicz = add_member(&pool, org_id, "85032212342", "Łukasiewicz");
+    add_member(&pool, org_id, "92061578905", "Lis");
+
+    for text in ["łukasiewicz", "ŁUKASIEWICZ", "ŁuKa"] {
+        let query = MemberQuery {
+            filter: MemberFilter {
+                search: Some(text.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let found = members::query(&pool, org_id, &query).unwrap().members;
+        assert_eq!(
+            found.iter().map(|m| m.id).collect::<Vec<_>>(),
+            vec![lukasiewicz]
+        );
+    }
+}
+
+fn terminate(pool: &DbPool, member_id: i64, on: &str) {
+    let member = members::get(pool, member_id).unwrap();
+    members::update(
+        pool,
+        member_id,
+        &UpdateMember {
+            first_name: member.first_name,
+            last_name: member.last_name,
+            gender: member.gender,
+            date_of_birth: member.date_of_birth,
+            citizenship: Some(member.citizenship),
+            second_name: Some(member.second_name),
+            doc_type: Some(member.doc_type),
+            doc_number: Some(member.doc_number),
+            status: Some(MemberStatus::Terminated),
+            effective_from: None,
+        },
+    )
+    .unwrap();
+    pool.get()
+        .unwrap()
+        .execute(
+            "UPDATE members SET terminated_at = ?1 WHERE id = ?2",
+            (on, member_id),
+        )
+        .unwrap();
+}
+
+fn set_retention(pool: &DbPool, org_id: i64, years: Option<i32>) {
+    settings::update_organization(
+        pool,
+        org_id,
+        &UpdateOrganizationSettings {
+            output_dir: String::new(),
+            file_name_template: String::new(),
+            contact_template: String::new(),
+            zip_password: None,
+            retention_years: years,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_retention_requires_policy() {
+    let (pool, org_id) = setup();
+    assert!(retention::preview(&pool, org_id).is_err());
+    assert!(retention::apply(&pool, org_id).is_err());
+}
+
+#[test]
+fn test_retention_anonymizes_former_members_and_keeps_totals() {
+    let (pool, org_id) = setup();
+    let former = add_member(&pool, org_id, "85032212342", "Testowa");
+    let recent = add_member(&pool, org_id, "92061578905", "Przykladowa");
+    let active = add_member(&pool, org_id, "90090515836", "Aktywny");
+    for member in [former, recent, active] {
+        set_amounts(&pool, member, "94.38", "70.78");
+    }
+    let generated = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+
+    terminate(&pool, former, "2015-06-30");
+    terminate(
+        &pool,
+        recent,
+        &chrono::Local::now().format("%Y-%m-%d").to_string(),
+    );
+    set_retention(&pool, org_id, Some(5));
+
+    let preview = retention::preview(&pool, org_id).unwrap();
+    assert_eq!(preview.members.len(), 1);
+    assert_eq!(preview.members[0].member_id, former);
+    assert_eq!(preview.generation_ids, vec![generated.generation.id]);
+    // The preview changes nothing
+    assert_eq!(
+        members::get(&pool, former).unwrap().pesel.as_str(),
+        "85032212342"
+    );
+
+    let result = retention::apply(&pool, org_id).unwrap();
+    assert_eq!(result.anonymized, 1);
+    assert_eq!(result.generations_updated, 1);
+
+    let anonymized = members::get(&pool, former).unwrap();
+    assert_ne!(anonymized.pesel.as_str(), "85032212342");
+    assert_ne!(anonymized.last_name, "Testowa");
+    assert!(anonymized.anonymized_at.is_some());
+    assert_eq!(
+        members::get(&pool, recent).unwrap().pesel.as_str(),
+        "92061578905"
+    );
+
+    let generation = generations::get(&pool, generated.generation.id).unwrap();
+    assert!(!generation.snapshot_json.contains("85032212342"));
+    assert!(!generation.snapshot_json.contains("Testowa"));
+    assert!(generation.snapshot_json.contains("92061578905"));
+    assert_eq!(
+        generation.total_employee_basic,
+        generated.total_employee_basic
+    );
+
+    let rows = contributions::list_for_period(&pool, org_id, 2025, 12).unwrap();
+    assert_eq!(rows.len(), 3);
+
+    // The archived file still held the data, so exports rebuild it instead
+    assert!(!std::path::Path::new(&generated.generation.file_path).exists());
+    assert_eq!(generation.file_path, "");
+    let copy = temp_dir().join("copy.zip");
+    generations::export(&pool, generated.generation.id, &copy, None).unwrap();
+    let xml = generated_xml(&copy.to_string_lossy());
+    assert!(!xml.contains("85032212342"));
+    assert!(!xml.contains("Testowa"));
+    assert!(xml.contains("92061578905"));
+
+    // The audit record lists ids, not the removed data
+    let log = audit::list(&pool, org_id).unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].details["member_ids"], serde_json::json!([former]));
+    assert!(!log[0].details.to_string().contains("85032212342"));
+
+    // Nobody is left to anonymize
+    assert!(retention::preview(&pool, org_id)
+        .unwrap()
+        .members
+        .is_empty());
+}
+
+#[test]
+fn test_transfer_closes_membership_and_links_the_new_one() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    let other_org = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Druga Spółka".to_string(),
+            nip: "7680002466".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+    let on = |day: &str| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+
+    let same = members::transfer(
+        &pool,
+        &TransferMember {
+            member_id: member,
+            organization_id: org_id,
+            transferred_on: on("2026-03-31"),
+        },
+    );
+    assert_eq!(same.unwrap_err().code(), "validation");
+
+    let transfer = members::transfer(
+        &pool,
+        &TransferMember {
+            member_id: member,
+            organization_id: other_org.id,
+            transferred_on: on("2026-03-31"),
+        },
+    )
+    .unwrap();
+    assert_eq!(transfer.from_member_id, member);
+    assert_eq!(transfer.to_organization_name, "Druga Spółka");
+
+    let source = members::get(&pool, member).unwrap();
+    assert_eq!(source.status, MemberStatus::Terminated);
+    assert_eq!(source.terminated_at.as_deref(), Some("2026-03-31"));
+    let target = members::get(&pool, transfer.to_member_id).unwrap();
+    assert_eq!(target.organization_id, other_org.id);
+    assert_eq!(target.status, MemberStatus::Active);
+    assert_eq!(
+        (target.pesel, target.last_name),
+        (source.pesel, source.last_name)
+    );
+
+    // Only active memberships move
+    let again = members::transfer(
+        &pool,
+        &TransferMember {
+            member_id: member,
+            organization_id: other_org.id,
+            transferred_on: on("2026-04-30"),
+        },
+    );
+    assert_eq!(again.unwrap_err().code(), "validation");
+
+    // Moving back reopens the former membership
+    let back = members::transfer(
+        &pool,
+        &TransferMember {
+            member_id: target.id,
+            organization_id: org_id,
+            transferred_on: on("2026-06-30"),
+        },
+    )
+    .unwrap();
+    assert_eq!(back.to_member_id, member);
+    let reopened = members::get(&pool, member).unwrap();
+    assert_eq!(reopened.status, MemberStatus::Active);
+    assert_eq!(reopened.terminated_at, None);
+    assert_eq!(members::list(&pool, org_id).unwrap().len(), 1);
+
+    let history = members::list_transfers(&pool, member).unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|t| t.transferred_on.as_str())
+            .collect::<Vec<_>>(),
+        vec!["2026-03-31", "2026-06-30"]
+    );
+    let report =
+        subject_access::collect(&pool, &subject_access::SubjectQuery::Member(member)).unwrap();
+    assert!(report.records.iter().all(|r| r.transfers.len() == 2));
+}
+
+fn set_document(pool: &DbPool, member_id: i64, doc_number: &str) {
+    let member = members::get(pool, member_id).unwrap();
+    members::update(
+        pool,
+        member_id,
+        &UpdateMember {
+            first_name: member.first_name,
+            last_name: member.last_name,
+            gender: member.gender,
+            date_of_birth: member.date_of_birth,
+            citizenship: Some("UA".to_string()),
+            second_name: None,
+            doc_type: Some("2".to_string()),
+            doc_number: Some(doc_number.to_string()),
+            status: None,
+            effective_from: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_person_registry_groups_memberships_and_finds_conflicts() {
+    let (pool, org_id) = setup();
+    let other_org = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Druga Spółka".to_string(),
+            nip: "7680002466".to_string(),
+            regon: "123456785".to_string(),
+            id_type: EmployerIdType::Nip,
+            pesel: String::new(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+
+    let here = add_member(&pool, org_id, "85032212342", "Testowa");
+    let there = add_member(&pool, other_org.id, "85032212342", "testowa ");
+    add_member(&pool, org_id, "92061578905", "Przykladowa");
+    set_amounts(&pool, here, "10.00", "15.00");
+    set_amounts(&pool, there, "20.00", "30.00");
+
+    // A foreigner recorded under different PESELs, with the same passport
+    let foreigner = add_member(&pool, org_id, "70010100018", "Bondarenko");
+    let renumbered = add_member(&pool, other_org.id, "88070712344", "Bondarenko");
+    set_document(&pool, foreigner, "FA 123456");
+    set_document(&pool, renumbered, "fa123456");
+
+    let all = persons::list(&pool, &PersonFilter::default()).unwrap();
+    assert_eq!(all.len(), 3);
+
+    // Names differing only in case and spacing are the same
+    let person = persons::get(&pool, &PersonKey::Pesel("85032212342".to_string())).unwrap();
+    assert_eq!(person.memberships.len(), 2);
+    assert!(person.conflicts.is_empty());
+
+    let key = PersonKey::Document {
+        doc_type: "2".to_string(),
+        doc_number: "FA123456".to_string(),
+    };
+    let person = persons::get(&pool, &key).unwrap();
+    assert_eq!(person.key, key);
+    let fields: Vec<&str> = person.conflicts.iter().map(|c| c.field).collect();
+    assert_eq!(fields, vec!["pesel", "gender", "date_of_birth"]);
+    assert_eq!(person.conflicts[0].values[0].member_ids, vec![foreigner]);
+
+    let filter = PersonFilter {
+        conflicts_only: true,
+        ..Default::default()
+    };
+    assert_eq!(persons::list(&pool, &filter).unwrap().len(), 1);
+    let filter = PersonFilter {
+        multiple_organizations: true,
+        ..Default::default()
+    };
+    assert_eq!(persons::list(&pool, &filter).unwrap().len(), 2);
+
+    let report = persons::report(&pool, &PersonKey::Pesel("85032212342".to_string())).unwrap();
+    assert_eq!(
+        report
+            .contributions
+            .iter()
+            .map(|c| c.organization_name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Druga Spółka", "Test Org"]
+    );
+    assert_eq!(report.total_employee_basic.to_string(), "30.00");
+    assert_eq!(report.total_employer_basic.to_string(), "45.00");
+
+    let unknown = persons::get(&pool, &PersonKey::Pesel("92061578906".to_string()));
+    assert_eq!(unknown.unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn test_subject_access_collects_data_across_organizations() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    let generated = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+
+    let other_org = organizations::create(
+        &pool,
+        &CreateOrganization {
+            name: "Druga <Spółka>".to_string(),
+            nip: String::new(),
+            regon: String::new(),
+            id_type: EmployerIdType::Pesel,
+            pesel: "70010100018".to_string(),
+            profile: EmployerProfile::default(),
+        },
+    )
+    .unwrap();
+    add_member(&pool, other_org.id, "85032212342", "Testowa");
+    add_member(&pool, other_org.id, "92061578905", "Przykladowa");
+
+    let query = subject_access::SubjectQuery::Member(member);
+    let report = subject_access::collect(&pool, &query).unwrap();
+    assert_eq!(report.pesel, "85032212342");
+    assert_eq!(report.records.len(), 2);
+
+    let first = &report.records[0];
+    assert_eq!(first.member.id, member);
+    assert_eq!(first.contributions.len(), 1);
+    assert_eq!(first.generations.len(), 1);
+    assert_eq!(first.generations[0].generation_id, generated.generation.id);
+    assert_eq!(first.generations[0].entry["employee_basic"], "94.38");
+    assert!(report.records[1].generations.is_empty());
+
+    let dir = temp_dir();
+    let query = subject_access::SubjectQuery::Pesel("85032212342".to_string());
+    let json = subject_access::export(
+        &pool,
+        &query,
+        subject_access::ExportFormat::Json,
+        &dir.join("dane.json"),
+    )
+    .unwrap();
+    let parsed: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(parsed["records"].as_array().unwrap().len(), 2);
+    assert!(!std::fs::read_to_string(&json)
+        .unwrap()
+        .contains("92061578905"));
+    // Nor the PESEL of an employer identified by it
+    assert!(!std::fs::read_to_string(&json)
+        .unwrap()
+        .contains("70010100018"));
+
+    let html = subject_access::export(
+        &pool,
+        &query,
+        subject_access::ExportFormat::Html,
+        &dir.join("dane.html"),
+    )
+    .unwrap();
+    let html = std::fs::read_to_string(html).unwrap();
+    assert!(html.contains("<h2>Druga &lt;Spółka&gt;</h2>"));
+    assert!(html.contains("<h2>Test Org (NIP 5261040828, REGON 123456785)</h2>"));
+    assert!(!html.contains("70010100018"));
+    assert!(html.contains("94.38"));
+
+    // Each export is noted in the audit log of both organizations
+    assert_eq!(audit::list(&pool, org_id).unwrap().len(), 2);
+    assert_eq!(audit::list(&pool, other_org.id).unwrap().len(), 2);
+
+    let unknown = subject_access::SubjectQuery::Pesel("92061578906".to_string());
+    assert!(subject_access::collect(&pool, &unknown).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_merge_moves_contributions_and_resolves_clashes() {
+    let (pool, org_id) = setup();
+    let target = add_member(&pool, org_id, "85032212342", "Testowa");
+    let source = add_member(&pool, org_id, "92061578905", "Testowa");
+    set_amounts(&pool, target, "94.38", "70.78");
+    set_amounts(&pool, source, "10.00", "5.00");
+    set_november(&pool, source, "50.00");
+    let generated = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+
+    let request = |resolution| MergeRequest {
+        source_id: source,
+        target_id: target,
+        resolution: ClashResolution::KeepTarget,
+        choices: vec![ClashChoice {
+            period: Period::new(2025, 12).unwrap(),
+            resolution,
+        }],
+    };
+    let same = MergeRequest {
+        source_id: target,
+        ..request(ClashResolution::Sum)
+    };
+    assert_eq!(
+        merge::preview(&pool, &same).unwrap_err().code(),
+        "validation"
+    );
+
+    let preview = merge::preview(&pool, &request(ClashResolution::Sum)).unwrap();
+    assert_eq!(preview.moved, vec![Period::new(2025, 11).unwrap()]);
+    assert_eq!(preview.clashes.len(), 1);
+    assert_eq!(
+        preview.clashes[0].merged.employee_basic.to_string(),
+        "104.38"
+    );
+    assert_eq!(preview.generation_ids, vec![generated.generation.id]);
+    // The preview changes nothing
+    assert!(members::get(&pool, source).is_ok());
+
+    let result = merge::apply(&pool, &request(ClashResolution::Sum)).unwrap();
+    assert_eq!(
+        (result.moved, result.clashes, result.generations_updated),
+        (1, 1, 1)
+    );
+    assert!(members::get(&pool, source).is_err());
+
+    let december = contributions::list_for_period(&pool, org_id, 2025, 12).unwrap();
+    assert_eq!(december.len(), 1);
+    assert_eq!(december[0].member_id, target);
+    assert_eq!(december[0].employee_basic.to_string(), "104.38");
+    assert_eq!(december[0].employer_basic.to_string(), "75.78");
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    assert_eq!(november[0].member_id, target);
+
+    let generation = generations::get(&pool, generated.generation.id).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_str(&generation.snapshot_json).unwrap();
+    assert!(snapshot["contributions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|row| row["member_id"] == serde_json::json!(target)));
+    assert_eq!(
+        generation.total_employee_basic,
+        generated.total_employee_basic
+    );
+
+    // The audit record lists ids and amounts, not personal data
+    let log = audit::list(&pool, org_id).unwrap();
+    assert_eq!(log[0].action, merge::AUDIT_ACTION);
+    assert_eq!(log[0].details["source_id"], serde_json::json!(source));
+    assert!(!log[0].details.to_string().contains("92061578905"));
+}
+
+#[test]
+fn test_merge_keeps_the_duplicates_earlier_pesel() {
+    let (pool, org_id) = setup();
+    let target = add_member(&pool, org_id, "92061578905", "Testowa");
+    let source = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, target, "94.38", "70.78");
+    set_november(&pool, source, "50.00");
+    members::change_pesel(
+        &pool,
+        source,
+        &ChangePesel {
+            pesel: "90090515836".to_string(),
+            effective_from: Period::new(2025, 12).unwrap(),
+        },
+    )
+    .unwrap();
+    let request = |target_id: i64| MergeRequest {
+        source_id: source,
+        target_id,
+        resolution: ClashResolution::KeepTarget,
+        choices: Vec::new(),
+    };
+
+    // A member with a November of their own would have it shown under the
+    // duplicate's former PESEL
+    let filed_earlier = add_member(&pool, org_id, "88070712344", "Testowa");
+    set_november(&pool, filed_earlier, "30.00");
+    assert_eq!(
+        validation_field(merge::apply(&pool, &request(filed_earlier))),
+        ("merge.pesel_history", Some("target_id"))
+    );
+    assert_eq!(members::list_pesel_history(&pool, source).unwrap().len(), 1);
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    let kept = november
+        .iter()
+        .find(|c| c.member_id == filed_earlier)
+        .unwrap();
+    assert_eq!(kept.pesel.as_str(), "88070712344");
+    members::archive(&pool, filed_earlier).unwrap();
+
+    merge::apply(&pool, &request(target)).unwrap();
+
+    // November was filed under the duplicate's former PESEL and keeps it
+    let history = members::list_pesel_history(&pool, target).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].pesel.as_str(), "85032212342");
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    assert_eq!(november[0].member_id, target);
+    assert_eq!(november[0].pesel.as_str(), "85032212342");
+    let december = contributions::list_for_period(&pool, org_id, 2025, 12).unwrap();
+    assert_eq!(december[0].pesel.as_str(), "92061578905");
+    let regenerated = generations::generate(&pool, org_id, 2025, 11, None, None).unwrap();
+    let generation = generations::get(&pool, regenerated.generation.id).unwrap();
+    assert!(generation.snapshot_json.contains("85032212342"));
+}
+
+#[test]
+fn test_merge_keeps_the_duplicates_earlier_details() {
+    let (pool, org_id) = setup();
+    let target = add_member(&pool, org_id, "92061578905", "Nowak");
+    let source = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, target, "94.38", "70.78");
+    set_november(&pool, source, "50.00");
+    members::update(
+        &pool,
+        source,
+        &UpdateMember {
+            first_name: "Maria".to_string(),
+            last_name: "Nowak".to_string(),
+            gender: Gender::K,
+            date_of_birth: "1985-03-22".to_string(),
+            citizenship: None,
+            second_name: None,
+            doc_type: None,
+            doc_number: None,
+            status: None,
+            effective_from: Period::new(2025, 12).ok(),
+        },
+    )
+    .unwrap();
+    let request = |target_id: i64| MergeRequest {
+        source_id: source,
+        target_id,
+        resolution: ClashResolution::KeepTarget,
+        choices: Vec::new(),
+    };
+
+    // A member with a November of their own would have it shown under the
+    // duplicate's former surname
+    let filed_earlier = add_member(&pool, org_id, "88070712344", "Kowalska");
+    set_november(&pool, filed_earlier, "30.00");
+    assert_eq!(
+        validation_field(merge::apply(&pool, &request(filed_earlier))),
+        ("merge.details_history", Some("target_id"))
+    );
+    assert_eq!(
+        members::list_details_history(&pool, source).unwrap().len(),
+        1
+    );
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    let kept = november
+        .iter()
+        .find(|c| c.member_id == filed_earlier)
+        .unwrap();
+    assert_eq!(kept.last_name, "Kowalska");
+    members::archive(&pool, filed_earlier).unwrap();
+
+    merge::apply(&pool, &request(target)).unwrap();
+
+    // November was filed under the duplicate's former surname and keeps it
+    assert_eq!(
+        members::list_details_history(&pool, target).unwrap().len(),
+        1
+    );
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    assert_eq!(november[0].member_id, target);
+    assert_eq!(november[0].last_name, "Testowa");
+    let december = contributions::list_for_period(&pool, org_id, 2025, 12).unwrap();
+    assert_eq!(december[0].last_name, "Nowak");
+}
+
+#[test]
+fn test_change_pesel_keeps_history_for_earlier_periods() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    set_november(&pool, member, "50.00");
+    let change = |pesel: &str, year, month| ChangePesel {
+        pesel: pesel.to_string(),
+        effective_from: Period::new(year, month).unwrap(),
+    };
+
+    let invalid = members::change_pesel(&pool, member, &change("92061578906", 2025, 12));
+    assert_eq!(invalid.unwrap_err().code(), "validation");
+    let unchanged = members::change_pesel(&pool, member, &change("85032212342", 2025, 12));
+    assert_eq!(unchanged.unwrap_err().code(), "validation");
+
+    let updated = members::change_pesel(&pool, member, &change("90090515836", 2025, 12)).unwrap();
+    assert_eq!(updated.pesel.as_str(), "90090515836");
+    assert_eq!(updated.gender, Gender::M);
+    assert_eq!(updated.date_of_birth, "1990-09-05");
+
+    // Earlier periods keep the PESEL they were submitted with
+    let november = contributions::list_for_period(&pool, org_id, 2025, 11).unwrap();
+    assert_eq!(november[0].pesel.as_str(), "85032212342");
+    assert_eq!(november[0].gender, Gender::K);
+    assert_eq!(november[0].date_of_birth, "1985-03-22");
+    let december = contributions::list_for_period(&pool, org_id, 2025, 12).unwrap();
+    assert_eq!(december[0].pesel.as_str(), "90090515836");
+    let regenerated = generations::generate(&pool, org_id, 2025, 11, None, None).unwrap();
+    let generation = generations::get(&pool, regenerated.generation.id).unwrap();
+    assert!(generation.snapshot_json.contains("85032212342"));
+
+    let history = members::list_pesel_history(&pool, member).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].pesel.as_str(), "85032212342");
+    let earlier = members::change_pesel(&pool, member, &change("88070712344", 2025, 12));
+    assert_eq!(earlier.unwrap_err().code(), "validation");
+
+    // The former PESEL still finds the person, and stays out of the audit log
+    let report = subject_access::collect(
+        &pool,
+        &subject_access::SubjectQuery::Pesel("85032212342".to_string()),
+    )
+    .unwrap();
+    assert_eq!(report.records[0].pesel_history.len(), 1);
+    let log = audit::list(&pool, org_id).unwrap();
+    assert_eq!(log[0].action, members::PESEL_CHANGE_AUDIT_ACTION);
+    assert!(!log[0].details.to_string().contains("85032212342"));
+}
+
+#[test]
+fn test_dated_name_change_keeps_earlier_periods() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    set_november(&pool, member, "50.00");
+    let rename = |last_name: &str, effective_from: Option<Period>| UpdateMember {
+        first_name: "Maria".to_string(),
+        last_name: last_name.to_string(),
+        gender: Gender::K,
+        date_of_birth: "1985-03-22".to_string(),
+        citizenship: None,
+        second_name: None,
+        doc_type: None,
+        doc_number: None,
+        status: None,
+        effective_from,
+    };
+
+    let married =
+        members::update(&pool, member, &rename("Nowak", Period::new(2025, 12).ok())).unwrap();
+    assert_eq!(married.last_name, "Nowak");
+    let name_in = |month| {
+        contributions::list_for_period(&pool, org_id, 2025, month).unwrap()[0]
+            .last_name
+            .clone()
+    };
+    assert_eq!(name_in(11), "Testowa");
+    assert_eq!(name_in(12), "Nowak");
+    assert_eq!(
+        members::list_details_history(&pool, member).unwrap().len(),
+        1
+    );
+
+    // A later change must start after the previous one
+    let earlier = members::update(
+        &pool,
+        member,
+        &rename("Kowalska", Period::new(2025, 12).ok()),
+    );
+    assert_eq!(earlier.unwrap_err().code(), "validation");
+
+    // Without a date the edit corrects the current name and adds no history
+    members::update(&pool, member, &rename("Nowakowa", None)).unwrap();
+    assert_eq!(name_in(11), "Testowa");
+    assert_eq!(name_in(12), "Nowakowa");
+    assert_eq!(
+        members::list_details_history(&pool, member).unwrap().len(),
+        1
+    );
+
+    // Undo of the dated change removes its history too
+    journal::undo(&pool, org_id).unwrap();
+    journal::undo(&pool, org_id).unwrap();
+    assert_eq!(members::get(&pool, member).unwrap().last_name, "Testowa");
+    assert!(members::list_details_history(&pool, member)
+        .unwrap()
+        .is_empty());
+    assert_eq!(name_in(11), "Testowa");
+}
+
+#[test]
+fn test_archive_hides_data_and_purge_waits_for_retention() {
+    let (pool, org_id) = setup();
+    let former = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, former, "94.38", "70.78");
+    generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let mistake = add_member(&pool, org_id, "92061578905", "Przykladowa");
+
+    members::archive(&pool, former).unwrap();
+    members::archive(&pool, mistake).unwrap();
+    assert!(members::list(&pool, org_id).unwrap().is_empty());
+    assert!(contributions::list_for_period(&pool, org_id, 2025, 12)
+        .unwrap()
+        .is_empty());
+    // The archived member keeps their PESEL; adding them again is refused
+    let again = members::create(
+        &pool,
+        CreateMember {
+            organization_id: org_id,
+            pesel: "85032212342".to_string(),
+            first_name: "Maria".to_string(),
+            last_name: "Testowa".to_string(),
+            gender: String::new(),
+            date_of_birth: String::new(),
+            citizenship: None,
+            second_name: None,
+            doc_type: None,
+            doc_number: None,
+        },
+    );
+    assert_eq!(again.unwrap_err().code(), "validation");
+
+    let listed = trash::list(&pool).unwrap();
+    let can_purge = |id| {
+        listed
+            .members
+            .iter()
+            .find(|m| m.member.id == id)
+            .unwrap()
+            .can_purge
+    };
+    assert!(can_purge(mistake));
+    assert!(!can_purge(former));
+
+    // Nothing refers to a member added by mistake
+    trash::purge_member(&pool, mistake).unwrap();
+    assert!(members::get(&pool, mistake).is_err());
+    // One listed in a generation waits until the retention period has passed
+    assert_eq!(
+        trash::purge_member(&pool, former).unwrap_err().code(),
+        "validation"
+    );
+    set_retention(&pool, org_id, Some(5));
+    terminate(&pool, former, "2015-06-30");
+    let audit = trash::purge_member(&pool, former).unwrap();
+    assert_eq!(audit.details["member_id"], serde_json::json!(former));
+    assert!(!audit.details.to_string().contains("85032212342"));
+
+    // Only archived data can be purged
+    let kept = add_member(&pool, org_id, "90090515836", "Aktywny");
+    assert_eq!(
+        trash::purge_member(&pool, kept).unwrap_err().code(),
+        "validation"
+    );
+
+    // An archived organization takes its members out of every list
+    organizations::archive(&pool, org_id).unwrap();
+    assert!(organizations::list(&pool).unwrap().is_empty());
+    assert!(persons::list(&pool, &PersonFilter::default())
+        .unwrap()
+        .is_empty());
+    let listed = trash::list(&pool).unwrap();
+    assert_eq!(listed.organizations.len(), 1);
+    assert_eq!(listed.organizations[0].generations, 1);
+    assert!(!listed.organizations[0].can_purge);
+    assert_eq!(
+        trash::purge_organization(&pool, org_id).unwrap_err().code(),
+        "validation"
+    );
+
+    organizations::restore(&pool, org_id).unwrap();
+    assert_eq!(members::list(&pool, org_id).unwrap().len(), 1);
+    assert!(trash::list(&pool).unwrap().organizations.is_empty());
+}
+
+/// The fields of the organization created by `setup`, to be overridden.
+fn test_org_update() -> UpdateOrganization {
+    UpdateOrganization {
+        name: "Test Org".to_string(),
+        nip: "5261040828".to_string(),
+        regon: "123456785".to_string(),
+        id_type: EmployerIdType::Nip,
+        pesel: String::new(),
+        profile: EmployerProfile::default(),
+    }
+}
+
+/// The catalogue key and field of the validation error `result` must hold.
+fn validation_field<T: std::fmt::Debug>(
+    result: Result<T, AppError>,
+) -> (&'static str, Option<&'static str>) {
+    match result {
+        Err(AppError::Validation(message)) => (message.key, message.field),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_employer_profile_is_validated_and_saved() {
+    let (pool, org_id) = setup();
+    let update = |profile: EmployerProfile| {
+        organizations::update(
+            &pool,
+            org_id,
+            &UpdateOrganization {
+                profile,
+                ..test_org_update()
+            },
+        )
+    };
+    let profile = EmployerProfile {
+        street: "Marszałkowska".to_string(),
+        building_number: "1".to_string(),
+        postal_code: "00-950".to_string(),
+        city: "Warszawa".to_string(),
+        krs: "0000123456".to_string(),
+        bank_account: "61 1090 1014 0000 0712 1981 2874".to_string(),
+        management_agreement_number: "UZ/2019/17".to_string(),
+        management_agreement_date: Some("2019-10-25".to_string()),
+        collection_account: "PL10105000997603123456789123".to_string(),
+        ..EmployerProfile::default()
+    };
+
+    let saved = update(profile.clone()).unwrap().profile;
+    assert_eq!(saved.city, "Warszawa");
+    assert_eq!(
+        saved.management_agreement_date.as_deref(),
+        Some("2019-10-25")
+    );
+    assert_eq!(saved.operation_agreement_date, None);
+
+    let field_of = |profile: EmployerProfile| validation_field(update(profile)).1;
+    let wrong_account = EmployerProfile {
+        bank_account: "61 1090 1014 0000 0712 1981 2875".to_string(),
+        ..profile.clone()
+    };
+    assert_eq!(field_of(wrong_account), Some("bank_account"));
+    let wrong_collection = EmployerProfile {
+        collection_account: "PL10105000997603123456789124".to_string(),
+        ..profile.clone()
+    };
+    assert_eq!(field_of(wrong_collection), Some("collection_account"));
+    let wrong_postal_code = EmployerProfile {
+        postal_code: "00950".to_string(),
+        ..profile.clone()
+    };
+    assert_eq!(field_of(wrong_postal_code), Some("postal_code"));
+    let wrong_date = EmployerProfile {
+        operation_agreement_date: Some("2019-02-30".to_string()),
+        ..profile
+    };
+    assert_eq!(field_of(wrong_date), Some("operation_agreement_date"));
+
+    // Rejected updates leave the saved profile alone
+    assert_eq!(
+        organizations::get(&pool, org_id).unwrap().profile.krs,
+        "0000123456"
+    );
+}
+
+#[test]
+fn test_default_contact_fills_kontakt_in_configured_format() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    let contact = |name: &str, phone: &str, email: &str| SaveContactPerson {
+        name: name.to_string(),
+        phone: phone.to_string(),
+        email: email.to_string(),
+    };
+
+    let field_of =
+        |data: SaveContactPerson| validation_field(contacts::create(&pool, org_id, &data)).1;
+    assert_eq!(
+        field_of(contact("Anna Nowak", "600 123", "")),
+        Some("phone")
+    );
+    assert_eq!(
+        field_of(contact("Anna Nowak", "", "kadry@firma")),
+        Some("email")
+    );
+    assert_eq!(field_of(contact(" ", "", "")), Some("name"));
+
+    // The first contact becomes the default
+    let anna = contact("Anna Nowak", "600 123 456", "kadry@firma.pl");
+    let anna = contacts::create(&pool, org_id, &anna).unwrap();
+    let jan = contacts::create(&pool, org_id, &contact("Jan Kowalski", "", "")).unwrap();
+    assert!(anna.is_default);
+    assert!(!jan.is_default);
+
+    let rejected = settings::update_organization(
+        &pool,
+        org_id,
+        &UpdateOrganizationSettings {
+            output_dir: String::new(),
+            file_name_template: String::new(),
+            contact_template: "{name} {fax}".to_string(),
+            zip_password: None,
+            retention_years: None,
+        },
+    );
+    assert!(matches!(
+        rejected,
+        Err(AppError::Validation(ref m)) if m.field == Some("contact_template")
+    ));
+    settings::update_organization(
+        &pool,
+        org_id,
+        &UpdateOrganizationSettings {
+            output_dir: String::new(),
+            file_name_template: String::new(),
+            contact_template: "{name}, tel. {phone}".to_string(),
+            zip_password: None,
+            retention_years: None,
+        },
+    )
+    .unwrap();
+
+    let kontakt = |path: &str| {
+        let xml = generated_xml(path);
+        let start = xml.find("<KONTAKT>").unwrap() + "<KONTAKT>".len();
+        let end = xml.find("</KONTAKT>").unwrap();
+        xml[start..end].to_string()
+    };
+    let first = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    assert_eq!(
+        kontakt(&first.generation.file_path),
+        "Anna Nowak, tel. 600 123 456"
+    );
+
+    contacts::set_default(&pool, jan.id).unwrap();
+    let second = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    assert_eq!(kontakt(&second.generation.file_path), "Jan Kowalski");
+
+    // A rebuilt file keeps the contact it was generated with
+    std::fs::remove_file(&first.generation.file_path).unwrap();
+    let copy = temp_dir().join("copy.zip");
+    generations::export(&pool, first.generation.id, &copy, None).unwrap();
+    assert_eq!(
+        kontakt(&copy.to_string_lossy()),
+        "Anna Nowak, tel. 600 123 456"
+    );
+
+    // Deleting the default hands it on
+    contacts::delete(&pool, jan.id).unwrap();
+    let remaining = contacts::list(&pool, org_id).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].is_default);
+}
+
+#[test]
+fn test_employer_identified_by_local_regon_or_pesel() {
+    let (pool, org_id) = setup();
+    let member = add_member(&pool, org_id, "85032212342", "Testowa");
+    set_amounts(&pool, member, "94.38", "70.78");
+    let update = |nip: &str, regon: &str, id_type: EmployerIdType, pesel: &str| {
+        let changes = UpdateOrganization {
+            nip: nip.to_string(),
+            regon: regon.to_string(),
+            id_type,
+            pesel: pesel.to_string(),
+            ..test_org_update()
+        };
+        organizations::update(&pool, org_id, &changes)
+    };
+
+    // The identifier of the chosen type is required, a REGON may have 14 digits
+    assert_eq!(
+        validation_field(update("5261040828", "", EmployerIdType::Regon, "")),
+        ("organization.identifier_required", Some("regon"))
+    );
+    assert_eq!(
+        validation_field(update(
+            "5261040828",
+            "12345678500011",
+            EmployerIdType::Regon,
+            ""
+        )),
+        ("regon.checksum", Some("regon"))
+    );
+    update("5261040828", "12345678500010", EmployerIdType::Regon, "").unwrap();
+    let local_unit = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let xml = generated_xml(&local_unit.generation.file_path);
+    assert!(xml.contains("<NIP>5261040828</NIP>"));
+    assert!(xml.contains("<REGON>12345678500010</REGON>"));
+    assert!(!xml.contains("<PESEL>"));
+
+    // A sole proprietor without NIP or REGON
+    assert_eq!(
+        validation_field(update("", "", EmployerIdType::Pesel, "")),
+        ("organization.identifier_required", Some("pesel"))
+    );
+    let org = update("", "", EmployerIdType::Pesel, "70010100018").unwrap();
+    assert_eq!(org.identifier(), "70010100018");
+    let sole = generations::generate(&pool, org_id, 2025, 12, None, None).unwrap();
+    let xml = generated_xml(&sole.generation.file_path);
+    assert!(xml.contains("<PESEL>70010100018</PESEL>"));
+    assert!(!xml.contains("<NIP>"));
+    assert!(!xml.contains("<REGON>"));
+    assert!(sole
+        .generation
+        .file_path
+        .ends_with("70010100018_SKLADKA_2025_12.zip"));
+    // A template naming a NIP the employer does not have is refused
+    let refused = settings::render_file_name("{nip}_SKLADKA_{year}_{month}.zip", &org, 2025, 12);
+    assert_eq!(
+        validation_field(refused),
+        (
+            "settings.template_identifier_missing",
+            Some("file_name_template")
+        )
+    );
+
+    // Switching back drops the PESEL
+    let org = update("5261040828", "", EmployerIdType::Nip, "70010100018").unwrap();
+    assert_eq!(org.pesel, None);
+}
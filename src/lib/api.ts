@@ -1,9 +1,17 @@
-import { invoke } from './invoke';
+import { invoke, isTauri } from './invoke';
 import type {
-  Organization, CreateOrganization,
-  Member, CreateMember, UpdateMember,
-  ContributionWithMember, UpsertContribution, Period,
+  Organization, CreateOrganization, ContactPerson, SaveContactPerson,
+  Member, CreateMember, UpdateMember, MemberFilter, MemberQuery, MemberPage, MemberTransfer, TransferMember,
+  MergeRequest, MergePreview, MergeResult, ChangePesel, PeselChange, DetailsChange,
+  ContributionWithMember, UpsertContribution, UpsertBatchResult, Period,
+  PrefillRequest, PrefillPreview, PrefillResult, JournalEntry,
   Generation, GenerateResult, PeselValidationResult,
+  BatchRequest, BatchSummary, BatchProgress, ArchiveCheck,
+  AppSettings, OrganizationSettings, UpdateOrganizationSettings,
+  RetentionPreview, RetentionResult, AuditEntry, Trash,
+  SubjectQuery, SubjectReport, ExportFormat,
+  Person, PersonFilter, PersonKey, PersonReport,
+  DatabaseStatus, BackupFile, BackupOptions,
 } from './types';
 
 // Organizations
@@ -11,27 +19,107 @@ export const listOrganizations = () => invoke<Organization[]>('list_organization
 export const getOrganization = (id: number) => invoke<Organization>('get_organization', { id });
 export const createOrganization = (data: CreateOrganization) => invoke<Organization>('create_organization', { data });
 export const updateOrganization = (id: number, data: CreateOrganization) => invoke<Organization>('update_organization', { id, data });
-export const deleteOrganization = (id: number) => invoke<void>('delete_organization', { id });
+export const archiveOrganization = (id: number) => invoke<void>('archive_organization', { id });
+export const restoreOrganization = (id: number) => invoke<Organization>('restore_organization', { id });
+
+// Contact persons
+export const listContacts = (organizationId: number) => invoke<ContactPerson[]>('list_contacts', { organizationId });
+export const createContact = (organizationId: number, data: SaveContactPerson) =>
+  invoke<ContactPerson>('create_contact', { organizationId, data });
+export const updateContact = (id: number, data: SaveContactPerson) => invoke<ContactPerson>('update_contact', { id, data });
+export const setDefaultContact = (id: number) => invoke<ContactPerson>('set_default_contact', { id });
+export const deleteContact = (id: number) => invoke<void>('delete_contact', { id });
 
 // Members
 export const listMembers = (organizationId: number) => invoke<Member[]>('list_members', { organizationId });
+export const queryMembers = (organizationId: number, query: MemberQuery) =>
+  invoke<MemberPage>('query_members', { organizationId, query });
 export const getMember = (id: number) => invoke<Member>('get_member', { id });
 export const createMember = (data: CreateMember) => invoke<Member>('create_member', { data });
 export const updateMember = (id: number, data: UpdateMember) => invoke<Member>('update_member', { id, data });
-export const deleteMember = (id: number) => invoke<void>('delete_member', { id });
+export const listDetailsHistory = (id: number) => invoke<DetailsChange[]>('list_details_history', { id });
+export const changeMemberPesel = (id: number, data: ChangePesel) => invoke<Member>('change_member_pesel', { id, data });
+export const listPeselHistory = (id: number) => invoke<PeselChange[]>('list_pesel_history', { id });
+export const transferMember = (data: TransferMember) => invoke<MemberTransfer>('transfer_member', { data });
+export const listMemberTransfers = (memberId: number) =>
+  invoke<MemberTransfer[]>('list_member_transfers', { memberId });
+export const previewMerge = (request: MergeRequest) => invoke<MergePreview>('preview_merge', { request });
+export const mergeMembers = (request: MergeRequest) => invoke<MergeResult>('merge_members', { request });
+export const archiveMember = (id: number) => invoke<void>('archive_member', { id });
+export const restoreMember = (id: number) => invoke<Member>('restore_member', { id });
 export const validatePesel = (peselStr: string) => invoke<PeselValidationResult>('validate_pesel', { peselStr });
 
 // Contributions
-export const listContributions = (organizationId: number, year: number, month: number) =>
-  invoke<ContributionWithMember[]>('list_contributions', { organizationId, year, month });
+export const listContributions = (organizationId: number, year: number, month: number, filter?: MemberFilter) =>
+  invoke<ContributionWithMember[]>('list_contributions', { organizationId, year, month, filter: filter ?? null });
 export const upsertContribution = (data: UpsertContribution) => invoke<void>('upsert_contribution', { data });
-export const prefillContributions = (organizationId: number, year: number, month: number) =>
-  invoke<number>('prefill_contributions', { organizationId, year, month });
+export const upsertContributions = (rows: UpsertContribution[]) =>
+  invoke<UpsertBatchResult>('upsert_contributions', { rows });
+export const previewPrefill = (request: PrefillRequest) => invoke<PrefillPreview>('preview_prefill', { request });
+export const prefillContributions = (request: PrefillRequest) =>
+  invoke<PrefillResult>('prefill_contributions', { request });
 export const getAvailablePeriods = (organizationId: number) => invoke<Period[]>('get_available_periods', { organizationId });
 
+// Undo/redo
+export const listJournal = (organizationId: number) => invoke<JournalEntry[]>('list_journal', { organizationId });
+export const undo = (organizationId: number) => invoke<JournalEntry | null>('undo', { organizationId });
+export const redo = (organizationId: number) => invoke<JournalEntry | null>('redo', { organizationId });
+
 // Generations
-export const generatePpk = (organizationId: number, year: number, month: number) =>
-  invoke<GenerateResult>('generate_ppk', { organizationId, year, month });
+export const generatePpk = (organizationId: number, year: number, month: number, path: string | null, password: string | null = null) =>
+  invoke<GenerateResult>('generate_ppk', { organizationId, year, month, path, password });
+export const generateBatch = (request: BatchRequest) => invoke<BatchSummary>('generate_batch', { request });
+/** Follow `batch-progress` events; resolves to a function that stops listening. */
+export async function onBatchProgress(handler: (progress: BatchProgress) => void): Promise<() => void> {
+  if (isTauri()) {
+    const { listen } = await import('@tauri-apps/api/event');
+    return listen<BatchProgress>('batch-progress', event => handler(event.payload));
+  }
+  const { listenMock } = await import('../mock/mock-events');
+  return listenMock('batch-progress', handler);
+}
 export const listGenerations = (organizationId: number) => invoke<Generation[]>('list_generations', { organizationId });
 export const getGeneration = (id: number) => invoke<GenerateResult>('get_generation', { id });
-export const exportGeneration = (id: number) => invoke<GenerateResult>('export_generation', { id });
+export const exportGeneration = (id: number, path: string, password: string | null = null) =>
+  invoke<GenerateResult>('export_generation', { id, path, password });
+export const verifyArchive = (organizationId: number) => invoke<ArchiveCheck[]>('verify_archive', { organizationId });
+
+// Settings
+export const getAppSettings = () => invoke<AppSettings>('get_app_settings');
+export const updateAppSettings = (data: AppSettings) => invoke<AppSettings>('update_app_settings', { data });
+export const getOrganizationSettings = (organizationId: number) =>
+  invoke<OrganizationSettings>('get_organization_settings', { organizationId });
+export const updateOrganizationSettings = (organizationId: number, data: UpdateOrganizationSettings) =>
+  invoke<OrganizationSettings>('update_organization_settings', { organizationId, data });
+
+// Retention
+export const previewRetention = (organizationId: number) => invoke<RetentionPreview>('preview_retention', { organizationId });
+export const applyRetention = (organizationId: number) => invoke<RetentionResult>('apply_retention', { organizationId });
+
+// Audit
+export const listAuditLog = (organizationId: number) => invoke<AuditEntry[]>('list_audit_log', { organizationId });
+
+// Trash
+export const listTrash = () => invoke<Trash>('list_trash');
+export const purgeMember = (id: number) => invoke<AuditEntry>('purge_member', { id });
+export const purgeOrganization = (id: number) => invoke<AuditEntry>('purge_organization', { id });
+
+// Persons
+export const listPersons = (filter: PersonFilter) => invoke<Person[]>('list_persons', { filter });
+export const getPerson = (key: PersonKey) => invoke<Person>('get_person', { key });
+export const getPersonReport = (key: PersonKey) => invoke<PersonReport>('get_person_report', { key });
+
+// Subject access
+export const getSubjectData = (query: SubjectQuery) => invoke<SubjectReport>('get_subject_data', { query });
+export const exportSubjectData = (query: SubjectQuery, format: ExportFormat, path: string) =>
+  invoke<string>('export_subject_data', { query, format, path });
+
+// Database
+export const getDatabaseStatus = () => invoke<DatabaseStatus>('get_database_status');
+export const unlockDatabase = (password: string) => invoke<DatabaseStatus>('unlock_database', { password });
+export const encryptDatabase = (password: string) => invoke<DatabaseStatus>('encrypt_database', { password });
+export const changeDatabasePassword = (currentPassword: string, newPassword: string) =>
+  invoke<DatabaseStatus>('change_database_password', { currentPassword, newPassword });
+export const createBackup = (path: string, options: BackupOptions = {}) => invoke<void>('create_backup', { path, options });
+export const listBackups = () => invoke<BackupFile[]>('list_backups');
+export const restoreBackup = (path: string, password: string | null = null) => invoke<void>('restore_backup', { path, password });
@@ -0,0 +1,43 @@
+/** Error returned by a backend command, as serialized by `AppError`. */
+export interface AppErrorBody {
+  code: string;
+  field: string | null;
+  message_key: string;
+  params: Record<string, string>;
+  message: string;
+}
+
+/** A warning or note from the backend, serialized like an error without its code. */
+export type BackendMessage = Omit<AppErrorBody, 'code'>;
+
+export class AppError extends Error {
+  readonly code: string;
+  readonly field: string | null;
+  readonly messageKey: string;
+  readonly params: Record<string, string>;
+
+  constructor(body: AppErrorBody) {
+    super(body.message);
+    this.name = 'AppError';
+    this.code = body.code;
+    this.field = body.field;
+    this.messageKey = body.message_key;
+    this.params = body.params;
+  }
+
+  /** Keeps `${e}` in templates showing just the message. */
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isAppErrorBody(value: unknown): value is AppErrorBody {
+  return typeof value === 'object' && value !== null
+    && typeof (value as AppErrorBody).code === 'string'
+    && typeof (value as AppErrorBody).message === 'string';
+}
+
+/** Turn a rejected command into an `AppError` where the backend sent one. */
+export function toAppError(error: unknown): unknown {
+  return isAppErrorBody(error) ? new AppError(error) : error;
+}
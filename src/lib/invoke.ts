@@ -1,8 +1,10 @@
+import { toAppError } from './errors';
+
 type InvokeFn = <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
 
 let cachedInvoke: InvokeFn | null = null;
 
-function isTauri(): boolean {
+export function isTauri(): boolean {
   return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
 }
 
@@ -17,5 +19,9 @@ export async function invoke<T>(cmd: string, args?: Record<string, unknown>): Pr
       console.info('[mock] Tauri runtime not detected — using in-memory mock backend');
     }
   }
-  return cachedInvoke<T>(cmd, args);
+  try {
+    return await cachedInvoke<T>(cmd, args);
+  } catch (e) {
+    throw toAppError(e);
+  }
 }
@@ -1,18 +1,67 @@
-export interface Organization {
+import type { AppErrorBody, BackendMessage } from './errors';
+
+/** Registered address, registry numbers, accounts and PPK agreements; empty when not filled in. */
+export interface EmployerProfile {
+  street: string;
+  building_number: string;
+  apartment_number: string;
+  postal_code: string;
+  city: string;
+  krs: string;
+  /** The employer's own account, as NRB or IBAN. */
+  bank_account: string;
+  management_agreement_number: string;
+  management_agreement_date: string | null;
+  operation_agreement_number: string;
+  operation_agreement_date: string | null;
+  /** The institution's account contributions are paid to. */
+  collection_account: string;
+}
+
+/** Which identifier the employer goes by in generated files. */
+export type EmployerIdType = 'NIP' | 'REGON' | 'PESEL';
+
+export interface Organization extends EmployerProfile {
   id: number;
   name: string;
+  /** Empty when the employer is identified otherwise. */
   nip: string;
+  /** 9 digits, or 14 for a local unit; empty when the employer has none. */
   regon: string;
-  contact_person: string;
+  id_type: EmployerIdType;
+  /** Only for sole proprietors identified by PESEL. */
+  pesel: string | null;
   created_at: string;
   updated_at: string;
+  /** Set while the organization is in the trash. */
+  archived_at: string | null;
 }
 
-export interface CreateOrganization {
+export interface CreateOrganization extends EmployerProfile {
   name: string;
   nip: string;
   regon: string;
-  contact_person: string;
+  id_type: EmployerIdType;
+  /** Ignored unless the employer is identified by PESEL. */
+  pesel: string;
+}
+
+export interface ContactPerson {
+  id: number;
+  organization_id: number;
+  name: string;
+  phone: string;
+  email: string;
+  /** The contact written into generated files; at most one per organization. */
+  is_default: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SaveContactPerson {
+  name: string;
+  phone: string;
+  email: string;
 }
 
 export interface Member {
@@ -28,8 +77,130 @@ export interface Member {
   doc_type: string;
   doc_number: string;
   status: string;
+  terminated_at: string | null;
+  anonymized_at: string | null;
   created_at: string;
   updated_at: string;
+  /** Set while the member is in the trash. */
+  archived_at: string | null;
+}
+
+export interface MemberFilter {
+  search?: string | null;
+  status?: string | null;
+  citizenship?: string | null;
+  min_age?: number | null;
+  max_age?: number | null;
+  age_on?: string | null;
+  missing_documents?: boolean;
+}
+
+export type MemberSort = 'last_name' | 'first_name' | 'pesel' | 'date_of_birth' | 'status' | 'created_at';
+
+export interface MemberQuery extends MemberFilter {
+  sort?: MemberSort;
+  descending?: boolean;
+  after?: number | null;
+  limit?: number | null;
+}
+
+export interface MemberPage {
+  members: Member[];
+  total: number;
+  next: number | null;
+}
+
+export interface MemberTransfer {
+  id: number;
+  from_member_id: number;
+  from_organization_id: number;
+  from_organization_name: string;
+  to_member_id: number;
+  to_organization_id: number;
+  to_organization_name: string;
+  transferred_on: string;
+  created_at: string;
+}
+
+export interface DetailsChange {
+  id: number;
+  member_id: number;
+  first_name: string;
+  last_name: string;
+  second_name: string;
+  citizenship: string;
+  doc_type: string;
+  doc_number: string;
+  replaced_from_year: number;
+  replaced_from_month: number;
+  changed_at: string;
+}
+
+export interface PeselChange {
+  id: number;
+  member_id: number;
+  pesel: string;
+  gender: string;
+  date_of_birth: string;
+  replaced_from_year: number;
+  replaced_from_month: number;
+  changed_at: string;
+}
+
+export interface ChangePesel {
+  pesel: string;
+  effective_from: Period;
+}
+
+export interface TransferMember {
+  member_id: number;
+  organization_id: number;
+  transferred_on: string;
+}
+
+export type ClashResolution = 'keep_target' | 'keep_source' | 'sum';
+
+export interface ClashChoice {
+  period: Period;
+  resolution: ClashResolution;
+}
+
+export interface MergeRequest {
+  source_id: number;
+  target_id: number;
+  resolution?: ClashResolution;
+  choices?: ClashChoice[];
+}
+
+export interface ContributionAmounts {
+  employee_basic: string;
+  employee_additional: string;
+  employer_basic: string;
+  employer_additional: string;
+  reduced_basic_flag: string;
+}
+
+export interface MergeClash {
+  period: Period;
+  source: ContributionAmounts;
+  target: ContributionAmounts;
+  resolution: ClashResolution;
+  merged: ContributionAmounts;
+}
+
+export interface MergePreview {
+  source: Member;
+  target: Member;
+  moved: Period[];
+  clashes: MergeClash[];
+  generation_ids: number[];
+}
+
+export interface MergeResult {
+  moved: number;
+  clashes: number;
+  generations_updated: number;
+  audit: AuditEntry;
 }
 
 export interface CreateMember {
@@ -55,6 +226,8 @@ export interface UpdateMember {
   doc_type?: string;
   doc_number?: string;
   status?: string;
+  /** First period of the new details; without it the edit is a correction. */
+  effective_from?: Period | null;
 }
 
 export interface ContributionWithMember {
@@ -92,11 +265,86 @@ export interface UpsertContribution {
   reduced_basic_flag?: string;
 }
 
+export interface RowError {
+  /** Position of the row in the submitted batch. */
+  index: number;
+  member_id: number;
+  error: AppErrorBody;
+}
+
+/** Either every row was saved, or none was and `errors` says why. */
+export interface UpsertBatchResult {
+  saved: number;
+  errors: RowError[];
+}
+
+export interface JournalEntry {
+  id: number;
+  organization_id: number;
+  action: string;
+  undone: boolean;
+  created_at: string;
+}
+
 export interface Period {
   year: number;
   month: number;
 }
 
+export interface SalaryBase {
+  member_id: number;
+  salary: string;
+}
+
+/** Percentages and rates are decimal strings, e.g. "3.5". */
+export type PrefillStrategy =
+  | { kind: 'copy'; source?: Period | null }
+  | { kind: 'zero' }
+  | { kind: 'salary'; bases: SalaryBase[]; employee_additional_rate?: string; employer_additional_rate?: string }
+  | { kind: 'scale'; source?: Period | null; percent: string };
+
+export interface PrefillRequest {
+  organization_id: number;
+  period: Period;
+  strategy: PrefillStrategy;
+  /** Only these members; all active members when absent. */
+  member_ids?: number[] | null;
+  /** Replace rows prefilled earlier. Rows entered by hand are never replaced. */
+  overwrite_prefilled?: boolean;
+}
+
+export interface PrefillRow {
+  member_id: number;
+  first_name: string;
+  last_name: string;
+  employee_basic: string;
+  employee_additional: string;
+  employer_basic: string;
+  employer_additional: string;
+  reduced_basic_flag: string;
+  overwrites: boolean;
+}
+
+export type SkipReason = 'manual' | 'prefilled' | 'no_salary';
+
+export interface SkippedMember {
+  member_id: number;
+  first_name: string;
+  last_name: string;
+  reason: SkipReason;
+}
+
+export interface PrefillPreview {
+  rows: PrefillRow[];
+  skipped: SkippedMember[];
+}
+
+export interface PrefillResult {
+  created: number;
+  overwritten: number;
+  skipped: number;
+}
+
 export interface Generation {
   id: number;
   organization_id: number;
@@ -104,6 +352,8 @@ export interface Generation {
   period_month: number;
   generated_at: string;
   file_path: string;
+  checksum: string;
+  encrypted: boolean;
   total_employee_basic: string;
   total_employer_basic: string;
   member_count: number;
@@ -111,7 +361,7 @@ export interface Generation {
 
 export interface GenerateResult {
   generation: Generation;
-  zip_bytes: number[];
+  output_path: string | null;
   total_employee_basic: string;
   total_employee_additional: string;
   total_employer_basic: string;
@@ -119,6 +369,233 @@ export interface GenerateResult {
   member_count: number;
 }
 
+export interface BatchRequest {
+  organization_ids: number[] | null;
+  year: number;
+  month: number;
+  output_dir: string | null;
+}
+
+export type BatchStatus = 'success' | 'warning' | 'failure';
+
+export interface BatchProgress {
+  current: number;
+  total: number;
+  organization_id: number;
+  organization_name: string;
+}
+
+export interface BatchItem {
+  organization_id: number;
+  organization_name: string;
+  status: BatchStatus;
+  prefilled: number;
+  generation_id: number | null;
+  file_path: string | null;
+  member_count: number;
+  total_employee_basic: string;
+  total_employer_basic: string;
+  messages: BackendMessage[];
+}
+
+export interface BatchSummary {
+  items: BatchItem[];
+  succeeded: number;
+  warnings: number;
+  failed: number;
+}
+
+export type Language = 'pl' | 'en';
+
+export interface AppSettings {
+  archive_dir: string;
+  output_dir: string;
+  file_name_template: string;
+  /** Format of `<KONTAKT>` when an organization has none of its own. */
+  contact_template: string;
+  /** Language of backend messages, errors included. */
+  language: Language;
+}
+
+export interface OrganizationSettings {
+  organization_id: number;
+  output_dir: string;
+  file_name_template: string;
+  /** Format of `<KONTAKT>`, with {name}, {phone} and {email}; empty uses the global one. */
+  contact_template: string;
+  has_zip_password: boolean;
+  retention_years: number | null;
+}
+
+export interface UpdateOrganizationSettings {
+  output_dir: string;
+  file_name_template: string;
+  contact_template?: string;
+  /** Omit to keep the current password, empty string removes it. */
+  zip_password?: string | null;
+  retention_years?: number | null;
+}
+
+export type ArchiveStatus = 'ok' | 'missing' | 'modified' | 'unchecked';
+
+export interface ArchiveCheck {
+  generation_id: number;
+  period_year: number;
+  period_month: number;
+  file_path: string;
+  status: ArchiveStatus;
+}
+
+export interface DatabaseStatus {
+  encrypted: boolean;
+  locked: boolean;
+}
+
+export interface RetentionCandidate {
+  member_id: number;
+  first_name: string;
+  last_name: string;
+  pesel: string;
+  status: string;
+  terminated_at: string;
+}
+
+export interface RetentionPreview {
+  organization_id: number;
+  retention_years: number;
+  cutoff: string;
+  members: RetentionCandidate[];
+  generation_ids: number[];
+}
+
+export interface AuditEntry {
+  id: number;
+  organization_id: number | null;
+  action: string;
+  details: Record<string, unknown>;
+  created_at: string;
+}
+
+export interface RetentionResult {
+  anonymized: number;
+  generations_updated: number;
+  audit: AuditEntry;
+}
+
+export interface TrashedOrganization {
+  organization: Organization;
+  /** Generations of the organization, deleted along with it. */
+  generations: number;
+  can_purge: boolean;
+}
+
+export interface TrashedMember {
+  member: Member;
+  organization_name: string;
+  /** Generations whose snapshot lists the member. */
+  generations: number;
+  can_purge: boolean;
+}
+
+export interface Trash {
+  organizations: TrashedOrganization[];
+  members: TrashedMember[];
+}
+
+export type SubjectQuery = { member: number } | { pesel: string };
+
+export type ExportFormat = 'json' | 'html';
+
+export interface SubjectGenerationEntry {
+  generation_id: number;
+  period_year: number;
+  period_month: number;
+  generated_at: string;
+  file_path: string;
+  entry: Record<string, unknown>;
+}
+
+export type PersonKey = { pesel: string } | { document: { doc_type: string; doc_number: string } };
+
+export interface Membership {
+  organization_id: number;
+  organization_name: string;
+  member: Member;
+}
+
+export interface ConflictingValue {
+  value: string;
+  member_ids: number[];
+}
+
+export interface IdentityConflict {
+  field: string;
+  values: ConflictingValue[];
+}
+
+export interface Person {
+  key: PersonKey;
+  memberships: Membership[];
+  conflicts: IdentityConflict[];
+}
+
+export interface PersonFilter {
+  multiple_organizations?: boolean;
+  conflicts_only?: boolean;
+}
+
+export interface PersonContribution extends ContributionWithMember {
+  organization_id: number;
+  organization_name: string;
+}
+
+export interface PersonReport {
+  person: Person;
+  contributions: PersonContribution[];
+  total_employee_basic: string;
+  total_employee_additional: string;
+  total_employer_basic: string;
+  total_employer_additional: string;
+}
+
+/** Employer of a membership; a sole proprietor's PESEL is left out. */
+export interface SubjectEmployer {
+  id: number;
+  name: string;
+  nip: string;
+  regon: string;
+}
+
+export interface SubjectRecord {
+  organization: SubjectEmployer;
+  member: Member;
+  contributions: ContributionWithMember[];
+  generations: SubjectGenerationEntry[];
+  transfers: MemberTransfer[];
+  pesel_history: PeselChange[];
+  details_history: DetailsChange[];
+}
+
+export interface SubjectReport {
+  pesel: string;
+  created_at: string;
+  records: SubjectRecord[];
+}
+
+export type BackupKind = 'startup' | 'migration' | 'daily' | 'restore';
+
+export interface BackupFile {
+  path: string;
+  kind: BackupKind;
+  created_at: string;
+  size: number;
+}
+
+export interface BackupOptions {
+  compress?: boolean;
+  password?: string | null;
+}
+
 export interface PeselValidationResult {
   valid: boolean;
   date_of_birth: string | null;
@@ -1,5 +1,3 @@
-import { invoke } from './invoke';
-
 export const POLISH_MONTHS = [
   '', 'Styczeń', 'Luty', 'Marzec', 'Kwiecień', 'Maj', 'Czerwiec',
   'Lipiec', 'Sierpień', 'Wrzesień', 'Październik', 'Listopad', 'Grudzień'
@@ -62,8 +60,8 @@ export function validateMoney(value: string): string | null {
   return null;
 }
 
-/** Save ZIP bytes to file via native dialog + Rust write */
-export async function saveZipFile(zipBytes: number[], suggestedName: string): Promise<boolean> {
+/** Ask the user where to save a ZIP via native dialog. Returns the chosen path or null. */
+export async function pickZipPath(suggestedName: string): Promise<string | null> {
   let saveFn: (options: any) => Promise<string | null>;
 
   if (typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window) {
@@ -74,15 +72,25 @@ export async function saveZipFile(zipBytes: number[], suggestedName: string): Pr
     saveFn = async () => `/mock/${suggestedName}`;
   }
 
-  const path = await saveFn({
+  return saveFn({
     defaultPath: suggestedName,
     filters: [{ name: 'ZIP', extensions: ['zip'] }],
   });
+}
 
-  if (!path) return false;
+/** Ask the user for a directory via native dialog. Returns the chosen path or null. */
+export async function pickDirectory(): Promise<string | null> {
+  if (typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window) {
+    const { open } = await import('@tauri-apps/plugin-dialog');
+    return open({ directory: true });
+  }
+  // Mock: pretend user picked a directory
+  return '/mock/batch';
+}
 
-  await invoke<void>('save_zip_file', { zipBytes, path });
-  return true;
+/** Default archive name for a period ("SKLADKA_202512.zip") */
+export function zipFileName(year: number, month: number): string {
+  return `SKLADKA_${year}${String(month).padStart(2, '0')}.zip`;
 }
 
 export function currentPeriod(): { year: number; month: number } {
@@ -48,16 +48,67 @@ export function validateNip(nip: string): { valid: boolean; error?: string } {
 }
 
 const REGON_WEIGHTS = [8, 9, 2, 3, 4, 5, 6, 7];
+const REGON_14_WEIGHTS = [2, 4, 8, 5, 0, 9, 7, 3, 6, 1, 2, 4, 8];
 
+function regonChecksumMatches(digits: number[], weights: number[]): boolean {
+  const sum = digits.slice(0, -1).reduce((acc, d, i) => acc + d * weights[i], 0);
+  const check = sum % 11 === 10 ? 0 : sum % 11;
+  return check === digits[digits.length - 1];
+}
+
+/** 9 digits, or 14 for a local unit, whose first nine are the parent's REGON. */
 export function validateRegon(regon: string): { valid: boolean; error?: string } {
   const clean = regon.replace(/\D/g, '');
-  if (clean.length !== 9) return { valid: false, error: 'REGON musi mieć 9 cyfr' };
+  if (clean.length !== 9 && clean.length !== 14) return { valid: false, error: 'REGON musi mieć 9 lub 14 cyfr' };
 
   const digits = clean.split('').map(Number);
-  const sum = digits.slice(0, 8).reduce((acc, d, i) => acc + d * REGON_WEIGHTS[i], 0);
-  let check = sum % 11;
-  if (check === 10) check = 0;
+  if (!regonChecksumMatches(digits.slice(0, 9), REGON_WEIGHTS)
+    || (digits.length === 14 && !regonChecksumMatches(digits, REGON_14_WEIGHTS))) {
+    return { valid: false, error: 'Nieprawidłowa suma kontrolna' };
+  }
+  return { valid: true };
+}
+
+/** NRB (26 digits) or IBAN, spaces allowed; checked modulo 97 as in ISO 13616. */
+export function validateBankAccount(account: string): { valid: boolean; error?: string } {
+  let iban = account.replace(/[\s-]/g, '').toUpperCase();
+  if (/^\d+$/.test(iban)) iban = `PL${iban}`;
+  if (!/^[A-Z]{2}[0-9A-Z]{13,32}$/.test(iban) || (iban.startsWith('PL') && iban.length !== 28)) {
+    return { valid: false, error: 'Numer rachunku musi mieć 26 cyfr (NRB) lub być poprawnym numerem IBAN' };
+  }
+
+  const rearranged = iban.slice(4) + iban.slice(0, 4);
+  let remainder = 0;
+  for (const c of rearranged) {
+    const value = parseInt(c, 36);
+    remainder = (remainder * (value < 10 ? 10 : 100) + value) % 97;
+  }
+  if (remainder !== 1) return { valid: false, error: 'Nieprawidłowa suma kontrolna numeru rachunku' };
+  return { valid: true };
+}
+
+export function validatePostalCode(code: string): { valid: boolean; error?: string } {
+  if (!/^\d{2}-\d{3}$/.test(code)) return { valid: false, error: 'Kod pocztowy musi mieć postać 00-000' };
+  return { valid: true };
+}
+
+export function validateKrs(krs: string): { valid: boolean; error?: string } {
+  if (!/^\d{10}$/.test(krs)) return { valid: false, error: 'Numer KRS musi mieć 10 cyfr' };
+  return { valid: true };
+}
+
+export function validatePhone(phone: string): { valid: boolean; error?: string } {
+  const international = phone.startsWith('+');
+  const number = international ? phone.slice(1) : phone;
+  const digits = number.replace(/[ -]/g, '');
+  const lengthOk = international ? digits.length >= 8 && digits.length <= 15 : digits.length === 9;
+  if (!lengthOk || !/^\d+$/.test(digits) || /^[ -]|[ -]$/.test(number)) {
+    return { valid: false, error: 'Numer telefonu musi mieć 9 cyfr lub zaczynać się od + i numeru kierunkowego kraju' };
+  }
+  return { valid: true };
+}
 
-  if (check !== digits[8]) return { valid: false, error: 'Nieprawidłowa suma kontrolna' };
+export function validateEmail(email: string): { valid: boolean; error?: string } {
+  if (!/^[^@\s]+@[^@\s.]+(\.[^@\s.]+)+$/.test(email)) return { valid: false, error: 'Nieprawidłowy adres e-mail' };
   return { valid: true };
 }
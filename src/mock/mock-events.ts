@@ -0,0 +1,15 @@
+// In-memory stand-in for Tauri events, so mock handlers can report progress.
+
+type Listener = (payload: any) => void;
+
+const listeners = new Map<string, Set<Listener>>();
+
+export function listenMock<T>(event: string, handler: (payload: T) => void): () => void {
+  if (!listeners.has(event)) listeners.set(event, new Set());
+  listeners.get(event)!.add(handler);
+  return () => listeners.get(event)?.delete(handler);
+}
+
+export function emitMock(event: string, payload: unknown) {
+  listeners.get(event)?.forEach(handler => handler(payload));
+}
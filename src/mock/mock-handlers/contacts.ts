@@ -0,0 +1,83 @@
+import { store, now } from '../mock-store';
+import type { ContactPerson, SaveContactPerson } from '../../lib/types';
+import type { AppErrorBody } from '../../lib/errors';
+import { validateEmail, validatePhone } from '../../lib/validation';
+
+function validationError(key: string, message: string, field: string): AppErrorBody {
+  return { code: 'validation', field, message_key: key, params: {}, message };
+}
+
+function validate(data: SaveContactPerson) {
+  if (!data.name.trim()) {
+    throw validationError('contact.name_required', 'Imię i nazwisko osoby kontaktowej jest wymagane', 'name');
+  }
+  const phone = data.phone ? validatePhone(data.phone) : { valid: true };
+  if (!phone.valid) throw validationError('phone.format', phone.error!, 'phone');
+  const email = data.email ? validateEmail(data.email) : { valid: true };
+  if (!email.valid) throw validationError('email.format', email.error!, 'email');
+}
+
+function find(id: number): ContactPerson {
+  const contact = store.contacts.find(c => c.id === id);
+  if (!contact) throw new Error(`Contact ${id} not found`);
+  return contact;
+}
+
+function makeDefault(contact: ContactPerson) {
+  for (const other of store.contacts) {
+    if (other.organization_id === contact.organization_id) other.is_default = other.id === contact.id;
+  }
+  contact.updated_at = now();
+}
+
+/** Default first, then by name, as the backend lists them. */
+export function list_contacts(args: { organizationId: number }): ContactPerson[] {
+  return store.contacts
+    .filter(c => c.organization_id === args.organizationId)
+    .sort((a, b) => Number(b.is_default) - Number(a.is_default) || a.name.localeCompare(b.name) || a.id - b.id)
+    .map(c => ({ ...c }));
+}
+
+export function create_contact(args: { organizationId: number; data: SaveContactPerson }): ContactPerson {
+  validate(args.data);
+  const contact: ContactPerson = {
+    id: store.nextContactId++,
+    organization_id: args.organizationId,
+    name: args.data.name,
+    phone: args.data.phone,
+    email: args.data.email,
+    is_default: false,
+    created_at: now(),
+    updated_at: now(),
+  };
+  store.contacts.push(contact);
+  if (!store.contacts.some(c => c.organization_id === args.organizationId && c.is_default)) {
+    makeDefault(contact);
+  }
+  return { ...contact };
+}
+
+export function update_contact(args: { id: number; data: SaveContactPerson }): ContactPerson {
+  validate(args.data);
+  const contact = find(args.id);
+  contact.name = args.data.name;
+  contact.phone = args.data.phone;
+  contact.email = args.data.email;
+  contact.updated_at = now();
+  return { ...contact };
+}
+
+export function set_default_contact(args: { id: number }): ContactPerson {
+  const contact = find(args.id);
+  makeDefault(contact);
+  return { ...contact };
+}
+
+export function delete_contact(args: { id: number }): void {
+  const contact = find(args.id);
+  store.contacts = store.contacts.filter(c => c.id !== args.id);
+  if (contact.is_default) {
+    const next = list_contacts({ organizationId: contact.organization_id })[0];
+    if (next) makeDefault(find(next.id));
+  }
+}
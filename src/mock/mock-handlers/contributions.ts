@@ -1,14 +1,20 @@
 import { store, now } from '../mock-store';
+import { journaled } from './journal';
+import { matchesFilter } from './members';
 import type { RawContribution } from '../mock-store';
-import type { ContributionWithMember, UpsertContribution, Period } from '../../lib/types';
+import type {
+  ContributionWithMember, UpsertContribution, UpsertBatchResult, RowError, Period, MemberFilter,
+  PrefillRequest, PrefillPreview, PrefillResult, SkipReason, PeselChange, DetailsChange,
+} from '../../lib/types';
 
 export function list_contributions(args: {
   organizationId: number;
   year: number;
   month: number;
+  filter?: MemberFilter | null;
 }): ContributionWithMember[] {
   const orgMemberIds = store.members
-    .filter(m => m.organization_id === args.organizationId)
+    .filter(m => m.organization_id === args.organizationId && !m.archived_at && matchesFilter(m, args.filter ?? {}))
     .map(m => m.id);
 
   const contribs = store.contributions.filter(
@@ -18,61 +24,82 @@ export function list_contributions(args: {
       c.period_month === args.month
   );
 
-  return contribs.map(c => {
-    const member = store.members.find(m => m.id === c.member_id)!;
-    return {
-      id: c.id,
-      member_id: c.member_id,
-      period_year: c.period_year,
-      period_month: c.period_month,
-      employee_basic: c.employee_basic,
-      employee_additional: c.employee_additional,
-      employer_basic: c.employer_basic,
-      employer_additional: c.employer_additional,
-      reduced_basic_flag: c.reduced_basic_flag,
-      source: c.source,
-      updated_at: c.updated_at,
-      pesel: member.pesel,
-      first_name: member.first_name,
-      last_name: member.last_name,
-      gender: member.gender,
-      date_of_birth: member.date_of_birth,
-      citizenship: member.citizenship,
-      second_name: member.second_name,
-      doc_type: member.doc_type,
-      doc_number: member.doc_number,
-      member_status: member.status,
-    };
-  });
+  return contribs.map(withMember);
+}
+
+export function withMember(c: RawContribution): ContributionWithMember {
+  const member = store.members.find(m => m.id === c.member_id)!;
+  // Periods before a PESEL correction or a dated change keep the values that applied then
+  const applies = (h: { member_id: number; replaced_from_year: number; replaced_from_month: number }) =>
+    h.member_id === c.member_id &&
+    (c.period_year < h.replaced_from_year ||
+      (c.period_year === h.replaced_from_year && c.period_month < h.replaced_from_month));
+  const byPeriod = (a: PeselChange | DetailsChange, b: PeselChange | DetailsChange) =>
+    a.replaced_from_year - b.replaced_from_year || a.replaced_from_month - b.replaced_from_month;
+  const former = store.peselHistory.filter(applies).sort(byPeriod)[0];
+  const details = store.detailsHistory.filter(applies).sort(byPeriod)[0] ?? member;
+  return {
+    id: c.id,
+    member_id: c.member_id,
+    period_year: c.period_year,
+    period_month: c.period_month,
+    employee_basic: c.employee_basic,
+    employee_additional: c.employee_additional,
+    employer_basic: c.employer_basic,
+    employer_additional: c.employer_additional,
+    reduced_basic_flag: c.reduced_basic_flag,
+    source: c.source,
+    updated_at: c.updated_at,
+    pesel: former?.pesel ?? member.pesel,
+    first_name: details.first_name,
+    last_name: details.last_name,
+    gender: former?.gender ?? member.gender,
+    date_of_birth: former?.date_of_birth ?? member.date_of_birth,
+    citizenship: details.citizenship,
+    second_name: details.second_name,
+    doc_type: details.doc_type,
+    doc_number: details.doc_number,
+    member_status: member.status,
+  };
+}
+
+function organizationOf(memberId: number): number {
+  const member = store.members.find(m => m.id === memberId);
+  if (!member) throw new Error(`Member ${memberId} not found`);
+  return member.organization_id;
 }
 
 export function upsert_contribution(args: { data: UpsertContribution }): void {
+  journaled(organizationOf(args.data.member_id), 'contribution.upsert', () => applyUpsert(args.data));
+}
+
+function applyUpsert(data: UpsertContribution): void {
   const existing = store.contributions.find(
     c =>
-      c.member_id === args.data.member_id &&
-      c.period_year === args.data.period_year &&
-      c.period_month === args.data.period_month
+      c.member_id === data.member_id &&
+      c.period_year === data.period_year &&
+      c.period_month === data.period_month
   );
 
   if (existing) {
-    existing.employee_basic = args.data.employee_basic ?? existing.employee_basic;
-    existing.employee_additional = args.data.employee_additional ?? existing.employee_additional;
-    existing.employer_basic = args.data.employer_basic ?? existing.employer_basic;
-    existing.employer_additional = args.data.employer_additional ?? existing.employer_additional;
-    existing.reduced_basic_flag = args.data.reduced_basic_flag ?? existing.reduced_basic_flag;
+    existing.employee_basic = data.employee_basic ?? existing.employee_basic;
+    existing.employee_additional = data.employee_additional ?? existing.employee_additional;
+    existing.employer_basic = data.employer_basic ?? existing.employer_basic;
+    existing.employer_additional = data.employer_additional ?? existing.employer_additional;
+    existing.reduced_basic_flag = data.reduced_basic_flag ?? existing.reduced_basic_flag;
     existing.source = 'manual';
     existing.updated_at = now();
   } else {
     const contrib: RawContribution = {
       id: store.nextContributionId++,
-      member_id: args.data.member_id,
-      period_year: args.data.period_year,
-      period_month: args.data.period_month,
-      employee_basic: args.data.employee_basic ?? '0.00',
-      employee_additional: args.data.employee_additional ?? '0.00',
-      employer_basic: args.data.employer_basic ?? '0.00',
-      employer_additional: args.data.employer_additional ?? '0.00',
-      reduced_basic_flag: args.data.reduced_basic_flag ?? 'N',
+      member_id: data.member_id,
+      period_year: data.period_year,
+      period_month: data.period_month,
+      employee_basic: data.employee_basic ?? '0.00',
+      employee_additional: data.employee_additional ?? '0.00',
+      employer_basic: data.employer_basic ?? '0.00',
+      employer_additional: data.employer_additional ?? '0.00',
+      reduced_basic_flag: data.reduced_basic_flag ?? 'N',
       source: 'manual',
       updated_at: now(),
     };
@@ -80,86 +107,155 @@ export function upsert_contribution(args: { data: UpsertContribution }): void {
   }
 }
 
-export function prefill_contributions(args: {
-  organizationId: number;
-  year: number;
-  month: number;
-}): number {
-  // Determine previous period
-  let prevYear = args.year;
-  let prevMonth = args.month - 1;
-  if (prevMonth === 0) {
-    prevMonth = 12;
-    prevYear--;
+const AMOUNT_FIELDS = ['employee_basic', 'employee_additional', 'employer_basic', 'employer_additional'] as const;
+
+export function upsert_contributions(args: { rows: UpsertContribution[] }): UpsertBatchResult {
+  const errors: RowError[] = [];
+  args.rows.forEach((row, index) => {
+    const field = AMOUNT_FIELDS.find(f => row[f] != null && !/^\d+(\.\d{1,2})?$/.test(row[f]!.trim()));
+    if (field) {
+      errors.push({
+        index,
+        member_id: row.member_id,
+        error: {
+          code: 'validation',
+          field,
+          message_key: 'money.invalid',
+          params: { value: row[field]! },
+          message: `Błąd walidacji: nieprawidłowa kwota '${row[field]}'`,
+        },
+      });
+    }
+  });
+  if (errors.length > 0) return { saved: 0, errors };
+
+  const organizationIds = new Set(args.rows.map(row => organizationOf(row.member_id)));
+  for (const organizationId of organizationIds) {
+    journaled(organizationId, 'contribution.batch', () =>
+      args.rows.filter(row => organizationOf(row.member_id) === organizationId).forEach(applyUpsert)
+    );
   }
+  return { saved: args.rows.length, errors };
+}
 
-  const orgMemberIds = store.members
-    .filter(m => m.organization_id === args.organizationId && m.status === 'active')
-    .map(m => m.id);
+const EMPLOYEE_BASIC_RATE = 2;
+const EMPLOYEE_REDUCED_BASIC_RATE = 0.5;
+const EMPLOYER_BASIC_RATE = 1.5;
+
+/** `rate` percent of a dot-decimal amount, rounded half up to grosze. */
+function percentOf(amount: string, rate: number): string {
+  const grosze = Math.round(Math.round(parseFloat(amount) * 100) * rate / 100);
+  return (grosze / 100).toFixed(2);
+}
+
+function periodKey(p: Period): number {
+  return p.year * 100 + p.month;
+}
 
-  // Members who already have contributions for the target period
-  const existingMemberIds = new Set(
+function planPrefill(request: PrefillRequest): PrefillPreview {
+  const { strategy, period } = request;
+  const members = store.members.filter(
+    m =>
+      m.organization_id === request.organization_id &&
+      m.status === 'active' &&
+      !m.archived_at &&
+      (!request.member_ids || request.member_ids.includes(m.id))
+  );
+  const source = strategy.kind === 'copy' || strategy.kind === 'scale' ? strategy.source ?? null : null;
+
+  const previousOf = (memberId: number): RawContribution | undefined =>
     store.contributions
-      .filter(
-        c =>
-          orgMemberIds.includes(c.member_id) &&
-          c.period_year === args.year &&
-          c.period_month === args.month
+      .filter(c =>
+        c.member_id === memberId &&
+        (source
+          ? c.period_year === source.year && c.period_month === source.month
+          : periodKey({ year: c.period_year, month: c.period_month }) < periodKey(period))
       )
-      .map(c => c.member_id)
-  );
+      .sort((a, b) => b.period_year * 100 + b.period_month - (a.period_year * 100 + a.period_month))[0];
 
-  // Previous period contributions for members missing current period
-  const prevContribs = store.contributions.filter(
-    c =>
-      orgMemberIds.includes(c.member_id) &&
-      !existingMemberIds.has(c.member_id) &&
-      c.period_year === prevYear &&
-      c.period_month === prevMonth
-  );
+  const preview: PrefillPreview = { rows: [], skipped: [] };
+  for (const m of members.sort((a, b) => a.last_name.localeCompare(b.last_name))) {
+    const existing = store.contributions.find(
+      c => c.member_id === m.id && c.period_year === period.year && c.period_month === period.month
+    );
+    const skip = (reason: SkipReason) =>
+      preview.skipped.push({ member_id: m.id, first_name: m.first_name, last_name: m.last_name, reason });
+    if (existing?.source === 'manual') { skip('manual'); continue; }
+    if (existing && !request.overwrite_prefilled) { skip('prefilled'); continue; }
 
-  let count = 0;
-  for (const prev of prevContribs) {
-    const contrib: RawContribution = {
-      id: store.nextContributionId++,
-      member_id: prev.member_id,
-      period_year: args.year,
-      period_month: args.month,
-      employee_basic: prev.employee_basic,
-      employee_additional: prev.employee_additional,
-      employer_basic: prev.employer_basic,
-      employer_additional: prev.employer_additional,
-      reduced_basic_flag: prev.reduced_basic_flag,
-      source: 'prefill',
-      updated_at: now(),
+    const prev = previousOf(m.id);
+    const base = {
+      employee_basic: prev?.employee_basic ?? '0.00',
+      employee_additional: prev?.employee_additional ?? '0.00',
+      employer_basic: prev?.employer_basic ?? '0.00',
+      employer_additional: prev?.employer_additional ?? '0.00',
+      reduced_basic_flag: prev?.reduced_basic_flag ?? 'N',
     };
-    store.contributions.push(contrib);
-    count++;
+    let amounts = base;
+    if (strategy.kind === 'zero') {
+      amounts = { employee_basic: '0.00', employee_additional: '0.00', employer_basic: '0.00', employer_additional: '0.00', reduced_basic_flag: 'N' };
+    } else if (strategy.kind === 'scale') {
+      const factor = 100 + parseFloat(strategy.percent);
+      amounts = {
+        employee_basic: percentOf(base.employee_basic, factor),
+        employee_additional: percentOf(base.employee_additional, factor),
+        employer_basic: percentOf(base.employer_basic, factor),
+        employer_additional: percentOf(base.employer_additional, factor),
+        reduced_basic_flag: base.reduced_basic_flag,
+      };
+    } else if (strategy.kind === 'salary') {
+      const salary = strategy.bases.find(b => b.member_id === m.id)?.salary;
+      if (!salary) { skip('no_salary'); continue; }
+      const reduced = base.reduced_basic_flag === 'T';
+      amounts = {
+        employee_basic: percentOf(salary, reduced ? EMPLOYEE_REDUCED_BASIC_RATE : EMPLOYEE_BASIC_RATE),
+        employee_additional: percentOf(salary, parseFloat(strategy.employee_additional_rate ?? '0')),
+        employer_basic: percentOf(salary, EMPLOYER_BASIC_RATE),
+        employer_additional: percentOf(salary, parseFloat(strategy.employer_additional_rate ?? '0')),
+        reduced_basic_flag: base.reduced_basic_flag,
+      };
+    }
+
+    preview.rows.push({ member_id: m.id, first_name: m.first_name, last_name: m.last_name, ...amounts, overwrites: !!existing });
   }
+  return preview;
+}
 
-  // Also create zero contributions for active members with no previous data and no current data
-  for (const memberId of orgMemberIds) {
-    if (existingMemberIds.has(memberId)) continue;
-    if (prevContribs.some(c => c.member_id === memberId)) continue;
+export function preview_prefill(args: { request: PrefillRequest }): PrefillPreview {
+  return planPrefill(args.request);
+}
 
-    const contrib: RawContribution = {
-      id: store.nextContributionId++,
-      member_id: memberId,
-      period_year: args.year,
-      period_month: args.month,
-      employee_basic: '0.00',
-      employee_additional: '0.00',
-      employer_basic: '0.00',
-      employer_additional: '0.00',
-      reduced_basic_flag: 'N',
-      source: 'prefill',
-      updated_at: now(),
-    };
-    store.contributions.push(contrib);
-    count++;
-  }
+export function prefill_contributions(args: { request: PrefillRequest }): PrefillResult {
+  return journaled(args.request.organization_id, 'contribution.prefill', () => applyPrefill(args.request));
+}
 
-  return count;
+function applyPrefill(request: PrefillRequest): PrefillResult {
+  const { period } = request;
+  const preview = planPrefill(request);
+  for (const row of preview.rows) {
+    const { member_id, first_name: _f, last_name: _l, overwrites: _o, ...amounts } = row;
+    const existing = store.contributions.find(
+      c => c.member_id === member_id && c.period_year === period.year && c.period_month === period.month
+    );
+    if (existing) {
+      Object.assign(existing, amounts, { updated_at: now() });
+    } else {
+      store.contributions.push({
+        id: store.nextContributionId++,
+        member_id,
+        period_year: period.year,
+        period_month: period.month,
+        ...amounts,
+        source: 'prefilled',
+        updated_at: now(),
+      });
+    }
+  }
+  return {
+    created: preview.rows.filter(r => !r.overwrites).length,
+    overwritten: preview.rows.filter(r => r.overwrites).length,
+    skipped: preview.skipped.length,
+  };
 }
 
 export function get_available_periods(args: { organizationId: number }): Period[] {
@@ -0,0 +1,50 @@
+import { store } from '../mock-store';
+import type { BackupFile, DatabaseStatus } from '../../lib/types';
+
+export function get_database_status(): DatabaseStatus {
+  return { encrypted: store.databasePassword !== null, locked: store.databaseLocked };
+}
+
+export function unlock_database(args: { password: string }): DatabaseStatus {
+  if (args.password !== store.databasePassword) {
+    throw new Error('Błąd walidacji: Nieprawidłowe hasło do bazy danych');
+  }
+  store.databaseLocked = false;
+  return get_database_status();
+}
+
+export function encrypt_database(args: { password: string }): DatabaseStatus {
+  if (store.databasePassword !== null) {
+    throw new Error('Błąd walidacji: Baza danych jest już zaszyfrowana');
+  }
+  checkNewPassword(args.password);
+  store.databasePassword = args.password;
+  return get_database_status();
+}
+
+export function change_database_password(args: { currentPassword: string; newPassword: string }): DatabaseStatus {
+  if (store.databasePassword === null) {
+    throw new Error('Błąd walidacji: Baza danych nie jest zaszyfrowana');
+  }
+  if (args.currentPassword !== store.databasePassword) {
+    throw new Error('Błąd walidacji: Nieprawidłowe hasło do bazy danych');
+  }
+  checkNewPassword(args.newPassword);
+  store.databasePassword = args.newPassword;
+  return get_database_status();
+}
+
+// Backups are not written in mock — browser can't write files
+export function create_backup(_args: { path: string }): void {}
+
+export function list_backups(): BackupFile[] {
+  return [];
+}
+
+export function restore_backup(_args: { path: string }): void {}
+
+function checkNewPassword(password: string) {
+  if (password.length < 8) {
+    throw new Error('Błąd walidacji: Hasło do bazy danych musi mieć co najmniej 8 znaków');
+  }
+}
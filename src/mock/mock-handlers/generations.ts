@@ -1,15 +1,20 @@
 import { store, now } from '../mock-store';
 import type { StoredGeneration } from '../mock-store';
-import type { Generation, GenerateResult } from '../../lib/types';
+import type { ArchiveCheck, BatchItem, BatchRequest, BatchSummary, Generation, GenerateResult } from '../../lib/types';
 import { sumMoney } from '../../lib/utils';
+import { emitMock } from '../mock-events';
+import { cutOffJournal } from './journal';
+import { list_organizations } from './organizations';
 
 export function generate_ppk(args: {
   organizationId: number;
   year: number;
   month: number;
+  path: string | null;
+  password?: string | null;
 }): GenerateResult {
   const orgMemberIds = store.members
-    .filter(m => m.organization_id === args.organizationId && m.status === 'active')
+    .filter(m => m.organization_id === args.organizationId && m.status === 'active' && !m.archived_at)
     .map(m => m.id);
 
   const contribs = store.contributions.filter(
@@ -30,7 +35,9 @@ export function generate_ppk(args: {
     period_year: args.year,
     period_month: args.month,
     generated_at: now(),
-    file_path: `mock_ppk_${args.year}_${String(args.month).padStart(2, '0')}.zip`,
+    file_path: `/mock/archive/${args.organizationId}/SKLADKA_${args.year}${String(args.month).padStart(2, '0')}.zip`,
+    checksum: '',
+    encrypted: !!args.password || !!store.organizationSettings.get(args.organizationId)?.has_zip_password,
     total_employee_basic: totalEmployeeBasic,
     total_employer_basic: totalEmployerBasic,
     member_count: contribs.length,
@@ -38,12 +45,10 @@ export function generate_ppk(args: {
   store.generations.push(gen);
 
   const generation: Generation = { ...gen };
-  // Stub zip_bytes — a minimal placeholder
-  const zipBytes = [80, 75, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
 
   const result: GenerateResult = {
     generation,
-    zip_bytes: zipBytes,
+    output_path: args.path,
     total_employee_basic: totalEmployeeBasic,
     total_employee_additional: totalEmployeeAdditional,
     total_employer_basic: totalEmployerBasic,
@@ -52,6 +57,7 @@ export function generate_ppk(args: {
   };
 
   store.generationResults.set(gen.id, result);
+  cutOffJournal(args.organizationId);
   return result;
 }
 
@@ -68,10 +74,77 @@ export function get_generation(args: { id: number }): GenerateResult {
   return { ...result, generation: { ...result.generation } };
 }
 
-export function export_generation(args: { id: number }): GenerateResult {
-  return get_generation(args);
+export function export_generation(args: { id: number; path: string }): GenerateResult {
+  // No file is written in mock — browser can't write files
+  return { ...get_generation(args), output_path: args.path };
 }
 
-export function save_zip_file(_args: { zipBytes: number[]; path: string }): void {
-  // No-op in mock — browser can't write files
+export function verify_archive(args: { organizationId: number }): ArchiveCheck[] {
+  return store.generations
+    .filter(g => g.organization_id === args.organizationId)
+    .map(g => ({
+      generation_id: g.id,
+      period_year: g.period_year,
+      period_month: g.period_month,
+      file_path: g.file_path,
+      status: 'unchecked' as const,
+    }));
+}
+
+export function generate_batch(args: { request: BatchRequest }): BatchSummary {
+  const { request } = args;
+  const orgs = list_organizations().filter(
+    o => !request.organization_ids || request.organization_ids.includes(o.id)
+  );
+  const month = String(request.month).padStart(2, '0');
+
+  const items: BatchItem[] = orgs.map((org, i) => {
+    emitMock('batch-progress', {
+      current: i + 1,
+      total: orgs.length,
+      organization_id: org.id,
+      organization_name: org.name,
+    });
+
+    const memberIds = store.members
+      .filter(m => m.organization_id === org.id && m.status === 'active' && !m.archived_at)
+      .map(m => m.id);
+    const empty = !store.contributions.some(
+      c => memberIds.includes(c.member_id) && c.period_year === request.year && c.period_month === request.month
+    );
+    if (empty) {
+      const message = 'Brak składek do wygenerowania dla wybranego okresu';
+      return {
+        organization_id: org.id,
+        organization_name: org.name,
+        status: 'failure',
+        prefilled: 0,
+        generation_id: null,
+        file_path: null,
+        member_count: 0,
+        total_employee_basic: '0.00',
+        total_employer_basic: '0.00',
+        messages: [{ field: null, message_key: 'generation.empty', params: {}, message }],
+      };
+    }
+
+    const identifier = org.id_type === 'PESEL' ? org.pesel : org.id_type === 'REGON' ? org.regon : org.nip;
+    const path = `${request.output_dir ?? '/mock/output'}/${identifier}_SKLADKA_${request.year}_${month}.zip`;
+    const result = generate_ppk({ organizationId: org.id, year: request.year, month: request.month, path });
+    return {
+      organization_id: org.id,
+      organization_name: org.name,
+      status: 'success',
+      prefilled: 0,
+      generation_id: result.generation.id,
+      file_path: result.output_path,
+      member_count: result.member_count,
+      total_employee_basic: result.total_employee_basic,
+      total_employer_basic: result.total_employer_basic,
+      messages: [],
+    };
+  });
+
+  const count = (status: BatchItem['status']) => items.filter(i => i.status === status).length;
+  return { items, succeeded: count('success'), warnings: count('warning'), failed: count('failure') };
 }
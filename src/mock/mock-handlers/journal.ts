@@ -0,0 +1,74 @@
+import { store, now } from '../mock-store';
+import type { OrgSnapshot, StoredJournalEntry } from '../mock-store';
+import type { JournalEntry } from '../../lib/types';
+
+function snapshot(organizationId: number): OrgSnapshot {
+  const members = store.members.filter(m => m.organization_id === organizationId);
+  const ids = new Set(members.map(m => m.id));
+  return {
+    members: members.map(m => ({ ...m })),
+    contributions: store.contributions.filter(c => ids.has(c.member_id)).map(c => ({ ...c })),
+    detailsHistory: store.detailsHistory.filter(h => ids.has(h.member_id)).map(h => ({ ...h })),
+  };
+}
+
+function restore(organizationId: number, state: OrgSnapshot) {
+  const ids = new Set(store.members.filter(m => m.organization_id === organizationId).map(m => m.id));
+  store.members = [...store.members.filter(m => m.organization_id !== organizationId), ...state.members.map(m => ({ ...m }))];
+  store.contributions = [
+    ...store.contributions.filter(c => !ids.has(c.member_id)),
+    ...state.contributions.map(c => ({ ...c })),
+  ];
+  store.detailsHistory = [
+    ...store.detailsHistory.filter(h => !ids.has(h.member_id)),
+    ...state.detailsHistory.map(h => ({ ...h })),
+  ];
+}
+
+function summary({ before: _b, after: _a, ...entry }: StoredJournalEntry): JournalEntry {
+  return { ...entry };
+}
+
+/** Run a mock operation and journal the organization's state around it. */
+export function journaled<T>(organizationId: number, action: string, operation: () => T): T {
+  const before = snapshot(organizationId);
+  const result = operation();
+  store.journal = store.journal.filter(e => e.organization_id !== organizationId || !e.undone);
+  store.journal.push({
+    id: store.nextJournalId++,
+    organization_id: organizationId,
+    action,
+    undone: false,
+    created_at: now(),
+    before,
+    after: snapshot(organizationId),
+  });
+  return result;
+}
+
+export function cutOffJournal(organizationId: number) {
+  store.journal = store.journal.filter(e => e.organization_id !== organizationId);
+}
+
+export function list_journal(args: { organizationId: number }): JournalEntry[] {
+  return store.journal
+    .filter(e => e.organization_id === args.organizationId)
+    .reverse()
+    .map(summary);
+}
+
+export function undo(args: { organizationId: number }): JournalEntry | null {
+  const entry = store.journal.filter(e => e.organization_id === args.organizationId && !e.undone).pop();
+  if (!entry) return null;
+  restore(args.organizationId, entry.before);
+  entry.undone = true;
+  return summary(entry);
+}
+
+export function redo(args: { organizationId: number }): JournalEntry | null {
+  const entry = store.journal.find(e => e.organization_id === args.organizationId && e.undone);
+  if (!entry) return null;
+  restore(args.organizationId, entry.after);
+  entry.undone = false;
+  return summary(entry);
+}
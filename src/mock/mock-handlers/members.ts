@@ -1,13 +1,91 @@
 import { store, now } from '../mock-store';
+import type { RawContribution, StoredTransfer } from '../mock-store';
+import { journaled, cutOffJournal } from './journal';
 import { validatePesel } from '../../lib/validation';
-import type { Member, CreateMember, UpdateMember, PeselValidationResult } from '../../lib/types';
+import { sumMoney } from '../../lib/utils';
+import type { AppErrorBody } from '../../lib/errors';
+import type {
+  Member, CreateMember, UpdateMember, PeselValidationResult, MemberFilter, MemberQuery, MemberPage, MemberSort,
+  MemberTransfer, TransferMember, MergeRequest, MergePreview, MergeResult, MergeClash, ClashResolution,
+  ContributionAmounts, AuditEntry, ChangePesel, PeselChange, DetailsChange,
+} from '../../lib/types';
 
 export function list_members(args: { organizationId: number }): Member[] {
   return store.members
-    .filter(m => m.organization_id === args.organizationId)
+    .filter(m => m.organization_id === args.organizationId && !m.archived_at)
     .map(m => ({ ...m }));
 }
 
+function validationError(key: string, message: string, field: string | null = null): AppErrorBody {
+  return { code: 'validation', field, message_key: key, params: {}, message };
+}
+
+function yearsBefore(day: string, years: number): string {
+  const [y, m, d] = day.split('-').map(Number);
+  const date = new Date(Date.UTC(y - years, m - 1, d));
+  // Clamp 29 February to the 28th, as the backend does
+  if (date.getUTCMonth() !== m - 1) date.setUTCDate(0);
+  return date.toISOString().slice(0, 10);
+}
+
+export function matchesFilter(m: Member, filter: MemberFilter): boolean {
+  const words = (filter.search ?? '').toLowerCase().split(/\s+/).filter(Boolean);
+  const fields = [m.first_name, m.last_name, m.second_name, m.pesel, m.doc_number].map(f => f.toLowerCase());
+  if (!words.every(w => fields.some(f => f.includes(w)))) return false;
+  if (filter.status && m.status !== filter.status) return false;
+  if (filter.citizenship && m.citizenship !== filter.citizenship.trim().toUpperCase()) return false;
+  if (filter.min_age != null || filter.max_age != null) {
+    if (filter.min_age != null && filter.max_age != null && filter.min_age > filter.max_age) {
+      throw validationError('member.age_range', 'Nieprawidłowy przedział wieku', 'min_age');
+    }
+    const on = filter.age_on ?? now().slice(0, 10);
+    if (!m.date_of_birth) return false;
+    if (filter.min_age != null && m.date_of_birth > yearsBefore(on, filter.min_age)) return false;
+    if (filter.max_age != null && m.date_of_birth <= yearsBefore(on, filter.max_age + 1)) return false;
+  }
+  if (filter.missing_documents && m.doc_type !== '' && m.doc_number !== '') return false;
+  return true;
+}
+
+const SORT_COLUMNS: Record<MemberSort, (keyof Member)[]> = {
+  last_name: ['last_name', 'first_name'],
+  first_name: ['first_name', 'last_name'],
+  pesel: ['pesel'],
+  date_of_birth: ['date_of_birth'],
+  status: ['status', 'last_name', 'first_name'],
+  created_at: ['created_at'],
+};
+
+export function query_members(args: { organizationId: number; query: MemberQuery }): MemberPage {
+  const { query } = args;
+  const columns = [...SORT_COLUMNS[query.sort ?? 'last_name'], 'id' as const];
+  const direction = query.descending ? -1 : 1;
+  const compare = (a: Member, b: Member) => {
+    for (const c of columns) {
+      if (a[c]! < b[c]!) return -direction;
+      if (a[c]! > b[c]!) return direction;
+    }
+    return 0;
+  };
+
+  const matching = store.members
+    .filter(m => m.organization_id === args.organizationId && !m.archived_at && matchesFilter(m, query))
+    .sort(compare);
+  let start = 0;
+  if (query.after != null) {
+    const cursor = store.members.find(m => m.id === query.after);
+    if (!cursor) throw new Error(`Member ${query.after} not found`);
+    start = matching.filter(m => compare(m, cursor) <= 0).length;
+  }
+  const limit = Math.min(Math.max(query.limit ?? 100, 1), 1000);
+  const page = matching.slice(start, start + limit);
+  return {
+    members: page.map(m => ({ ...m })),
+    total: matching.length,
+    next: start + limit < matching.length ? page[page.length - 1].id : null,
+  };
+}
+
 export function get_member(args: { id: number }): Member {
   const member = store.members.find(m => m.id === args.id);
   if (!member) throw new Error(`Member ${args.id} not found`);
@@ -15,21 +93,34 @@ export function get_member(args: { id: number }): Member {
 }
 
 export function create_member(args: { data: CreateMember }): Member {
+  const archived = store.members.find(
+    m => m.organization_id === args.data.organization_id && m.pesel === args.data.pesel && m.archived_at,
+  );
+  if (archived) {
+    throw validationError('member.archived_exists', 'Uczestnik z tym numerem PESEL jest w koszu; przywróć go zamiast dodawać ponownie', 'pesel');
+  }
+  return journaled(args.data.organization_id, 'member.create', () => createMember(args.data));
+}
+
+function createMember(data: CreateMember): Member {
   const member: Member = {
     id: store.nextMemberId++,
-    organization_id: args.data.organization_id,
-    pesel: args.data.pesel,
-    first_name: args.data.first_name,
-    last_name: args.data.last_name,
-    gender: args.data.gender,
-    date_of_birth: args.data.date_of_birth,
-    citizenship: args.data.citizenship ?? 'PL',
-    second_name: args.data.second_name ?? '',
-    doc_type: args.data.doc_type ?? '',
-    doc_number: args.data.doc_number ?? '',
+    organization_id: data.organization_id,
+    pesel: data.pesel,
+    first_name: data.first_name,
+    last_name: data.last_name,
+    gender: data.gender,
+    date_of_birth: data.date_of_birth,
+    citizenship: data.citizenship ?? 'PL',
+    second_name: data.second_name ?? '',
+    doc_type: data.doc_type ?? '',
+    doc_number: data.doc_number ?? '',
     status: 'active',
+    terminated_at: null,
+    anonymized_at: null,
     created_at: now(),
     updated_at: now(),
+    archived_at: null,
   };
   store.members.push(member);
   return { ...member };
@@ -38,29 +129,310 @@ export function create_member(args: { data: CreateMember }): Member {
 export function update_member(args: { id: number; data: UpdateMember }): Member {
   const member = store.members.find(m => m.id === args.id);
   if (!member) throw new Error(`Member ${args.id} not found`);
+  return journaled(member.organization_id, 'member.update', () => updateMember(member, args.data));
+}
 
-  member.first_name = args.data.first_name;
-  member.last_name = args.data.last_name;
-  member.gender = args.data.gender;
-  member.date_of_birth = args.data.date_of_birth;
-  member.citizenship = args.data.citizenship ?? member.citizenship;
-  member.second_name = args.data.second_name ?? member.second_name;
-  member.doc_type = args.data.doc_type ?? member.doc_type;
-  member.doc_number = args.data.doc_number ?? member.doc_number;
-  member.status = args.data.status ?? member.status;
+function updateMember(member: Member, data: UpdateMember): Member {
+  const changed =
+    member.first_name !== data.first_name ||
+    member.last_name !== data.last_name ||
+    member.second_name !== (data.second_name ?? member.second_name) ||
+    member.citizenship !== (data.citizenship ?? member.citizenship) ||
+    member.doc_type !== (data.doc_type ?? member.doc_type) ||
+    member.doc_number !== (data.doc_number ?? member.doc_number);
+  const from = data.effective_from;
+  if (from && changed) {
+    const last = list_details_history({ id: member.id }).at(-1);
+    if (last && (from.year < last.replaced_from_year ||
+      (from.year === last.replaced_from_year && from.month <= last.replaced_from_month))) {
+      const period = `${last.replaced_from_year}-${String(last.replaced_from_month).padStart(2, '0')}`;
+      throw validationError('member.details_effective_from', `Zmiana danych musi obowiązywać od okresu późniejszego niż ${period}`, 'effective_from');
+    }
+    store.detailsHistory.push({
+      id: store.nextDetailsChangeId++,
+      member_id: member.id,
+      first_name: member.first_name,
+      last_name: member.last_name,
+      second_name: member.second_name,
+      citizenship: member.citizenship,
+      doc_type: member.doc_type,
+      doc_number: member.doc_number,
+      replaced_from_year: from.year,
+      replaced_from_month: from.month,
+      changed_at: now(),
+    });
+  }
+
+  member.first_name = data.first_name;
+  member.last_name = data.last_name;
+  member.gender = data.gender;
+  member.date_of_birth = data.date_of_birth;
+  member.citizenship = data.citizenship ?? member.citizenship;
+  member.second_name = data.second_name ?? member.second_name;
+  member.doc_type = data.doc_type ?? member.doc_type;
+  member.doc_number = data.doc_number ?? member.doc_number;
+  const status = data.status ?? member.status;
+  if (status === 'active') {
+    member.terminated_at = null;
+  } else if (member.status === 'active' || !member.terminated_at) {
+    member.terminated_at = now().slice(0, 10);
+  }
+  member.status = status;
+  member.updated_at = now();
+
+  return { ...member };
+}
+
+export function list_details_history(args: { id: number }): DetailsChange[] {
+  return store.detailsHistory
+    .filter(h => h.member_id === args.id)
+    .sort((a, b) => a.replaced_from_year - b.replaced_from_year || a.replaced_from_month - b.replaced_from_month || a.id - b.id)
+    .map(h => ({ ...h }));
+}
+
+export function change_member_pesel(args: { id: number; data: ChangePesel }): Member {
+  const { id, data } = args;
+  const member = store.members.find(m => m.id === id);
+  if (!member) throw new Error(`Member ${id} not found`);
+  const pesel = data.pesel.trim();
+  const result = validatePesel(pesel);
+  if (!result.valid || !result.info) {
+    const key = pesel.length !== 11 ? 'pesel.length' : /^\d+$/.test(pesel) ? 'pesel.checksum' : 'pesel.digits';
+    throw validationError(key, result.error ?? 'Nieprawidłowy PESEL', 'pesel');
+  }
+  if (member.anonymized_at) {
+    throw validationError('member.pesel_anonymized', 'Nie można zmienić numeru PESEL zanonimizowanego uczestnika');
+  }
+  if (member.pesel === pesel) {
+    throw validationError('member.pesel_unchanged', 'Nowy numer PESEL jest taki sam jak obecny', 'pesel');
+  }
+  if (store.members.some(m => m.organization_id === member.organization_id && m.pesel === pesel)) {
+    throw validationError('member.pesel_exists', 'Uczestnik z tym numerem PESEL już istnieje w tej organizacji', 'pesel');
+  }
+  const last = list_pesel_history({ id }).at(-1);
+  const from = data.effective_from;
+  if (last && (from.year < last.replaced_from_year ||
+    (from.year === last.replaced_from_year && from.month <= last.replaced_from_month))) {
+    const period = `${last.replaced_from_year}-${String(last.replaced_from_month).padStart(2, '0')}`;
+    throw validationError('member.pesel_effective_from', `Zmiana musi obowiązywać od okresu późniejszego niż ${period}`, 'effective_from');
+  }
+
+  const change: PeselChange = {
+    id: store.nextPeselChangeId++,
+    member_id: id,
+    pesel: member.pesel,
+    gender: member.gender,
+    date_of_birth: member.date_of_birth,
+    replaced_from_year: from.year,
+    replaced_from_month: from.month,
+    changed_at: now(),
+  };
+  store.peselHistory.push(change);
+  member.pesel = pesel;
+  member.gender = result.info.gender;
+  member.date_of_birth = result.info.dateOfBirth;
   member.updated_at = now();
 
+  store.auditLog.push({
+    id: store.nextAuditId++,
+    organization_id: member.organization_id,
+    action: 'member_pesel_change',
+    details: { member_id: id, history_id: change.id, effective_from: from },
+    created_at: now(),
+  });
+  cutOffJournal(member.organization_id);
   return { ...member };
 }
 
-export function delete_member(args: { id: number }): void {
-  const idx = store.members.findIndex(m => m.id === args.id);
-  if (idx === -1) throw new Error(`Member ${args.id} not found`);
+export function list_pesel_history(args: { id: number }): PeselChange[] {
+  return store.peselHistory
+    .filter(h => h.member_id === args.id)
+    .sort((a, b) => a.replaced_from_year - b.replaced_from_year || a.replaced_from_month - b.replaced_from_month || a.id - b.id)
+    .map(h => ({ ...h }));
+}
+
+function withNames(t: StoredTransfer): MemberTransfer {
+  const from = store.members.find(m => m.id === t.from_member_id)!;
+  const to = store.members.find(m => m.id === t.to_member_id)!;
+  const name = (id: number) => store.organizations.find(o => o.id === id)?.name ?? '';
+  return {
+    ...t,
+    from_organization_id: from.organization_id,
+    from_organization_name: name(from.organization_id),
+    to_organization_id: to.organization_id,
+    to_organization_name: name(to.organization_id),
+  };
+}
 
-  // Cascade: remove member's contributions
-  store.contributions = store.contributions.filter(c => c.member_id !== args.id);
+export function transfer_member(args: { data: TransferMember }): MemberTransfer {
+  const { data } = args;
+  const source = store.members.find(m => m.id === data.member_id);
+  if (!source) throw new Error(`Member ${data.member_id} not found`);
+  if (source.status !== 'active') {
+    throw validationError('member.transfer_inactive', 'Przenieść można tylko aktywnego uczestnika');
+  }
+  if (source.organization_id === data.organization_id) {
+    throw validationError('member.transfer_same_organization', 'Uczestnik należy już do tej organizacji', 'organization_id');
+  }
+
+  let target = store.members.find(m => m.organization_id === data.organization_id && m.pesel === source.pesel);
+  if (target?.status === 'active') {
+    throw validationError('member.transfer_exists', 'Uczestnik jest już aktywny w organizacji docelowej', 'organization_id');
+  }
+  const { id: _id, organization_id: _org, created_at: _c, ...identity } = source;
+  if (target) {
+    Object.assign(target, identity, { terminated_at: null, archived_at: null, updated_at: now() });
+  } else {
+    target = { ...source, id: store.nextMemberId++, organization_id: data.organization_id, created_at: now(), updated_at: now() };
+    store.members.push(target);
+  }
+  source.status = 'terminated';
+  source.terminated_at = data.transferred_on;
+  source.updated_at = now();
+
+  const transfer: StoredTransfer = {
+    id: store.nextTransferId++,
+    from_member_id: source.id,
+    to_member_id: target.id,
+    transferred_on: data.transferred_on,
+    created_at: now(),
+  };
+  store.transfers.push(transfer);
+  return withNames(transfer);
+}
+
+export function list_member_transfers(args: { memberId: number }): MemberTransfer[] {
+  return store.transfers
+    .filter(t => t.from_member_id === args.memberId || t.to_member_id === args.memberId)
+    .sort((a, b) => a.transferred_on.localeCompare(b.transferred_on) || a.id - b.id)
+    .map(withNames);
+}
+
+function amountsOf(c: RawContribution): ContributionAmounts {
+  return {
+    employee_basic: c.employee_basic,
+    employee_additional: c.employee_additional,
+    employer_basic: c.employer_basic,
+    employer_additional: c.employer_additional,
+    reduced_basic_flag: c.reduced_basic_flag,
+  };
+}
+
+function resolve(resolution: ClashResolution, source: ContributionAmounts, target: ContributionAmounts): ContributionAmounts {
+  if (resolution === 'keep_target') return { ...target };
+  if (resolution === 'keep_source') return { ...source };
+  return {
+    employee_basic: sumMoney([source.employee_basic, target.employee_basic]),
+    employee_additional: sumMoney([source.employee_additional, target.employee_additional]),
+    employer_basic: sumMoney([source.employer_basic, target.employer_basic]),
+    employer_additional: sumMoney([source.employer_additional, target.employer_additional]),
+    reduced_basic_flag: target.reduced_basic_flag,
+  };
+}
+
+export function preview_merge(args: { request: MergeRequest }): MergePreview {
+  const { request } = args;
+  if (request.source_id === request.target_id) {
+    throw validationError('merge.same_member', 'Nie można scalić uczestnika z nim samym');
+  }
+  const source = store.members.find(m => m.id === request.source_id);
+  const target = store.members.find(m => m.id === request.target_id);
+  if (!source) throw new Error(`Member ${request.source_id} not found`);
+  if (!target) throw new Error(`Member ${request.target_id} not found`);
+  if (source.organization_id !== target.organization_id) {
+    throw validationError('merge.other_organization', 'Scalać można tylko uczestników tej samej organizacji', 'target_id');
+  }
+
+  const moved: MergePreview['moved'] = [];
+  const clashes: MergeClash[] = [];
+  const rows = store.contributions
+    .filter(c => c.member_id === source.id)
+    .sort((a, b) => a.period_year - b.period_year || a.period_month - b.period_month);
+  for (const c of rows) {
+    const period = { year: c.period_year, month: c.period_month };
+    const kept = store.contributions.find(
+      k => k.member_id === target.id && k.period_year === c.period_year && k.period_month === c.period_month
+    );
+    if (!kept) {
+      moved.push(period);
+      continue;
+    }
+    const resolution = request.choices?.find(ch => ch.period.year === period.year && ch.period.month === period.month)
+      ?.resolution ?? request.resolution ?? 'keep_target';
+    clashes.push({
+      period,
+      source: amountsOf(c),
+      target: amountsOf(kept),
+      resolution,
+      merged: resolve(resolution, amountsOf(c), amountsOf(kept)),
+    });
+  }
+  // Mock generations keep no snapshots to rewrite
+  return { source: { ...source }, target: { ...target }, moved, clashes, generation_ids: [] };
+}
+
+export function merge_members(args: { request: MergeRequest }): MergeResult {
+  const plan = preview_merge(args);
+  const { source, target } = plan;
+  for (const clash of plan.clashes) {
+    const kept = store.contributions.find(
+      k => k.member_id === target.id && k.period_year === clash.period.year && k.period_month === clash.period.month
+    )!;
+    if (clash.resolution !== 'keep_target') {
+      Object.assign(kept, clash.merged, { source: 'manual', updated_at: now() });
+    }
+    store.contributions = store.contributions.filter(
+      c => !(c.member_id === source.id && c.period_year === clash.period.year && c.period_month === clash.period.month)
+    );
+  }
+  for (const c of store.contributions.filter(c => c.member_id === source.id)) {
+    c.member_id = target.id;
+    c.updated_at = now();
+  }
+  for (const t of store.transfers) {
+    if (t.from_member_id === source.id) t.from_member_id = target.id;
+    if (t.to_member_id === source.id) t.to_member_id = target.id;
+  }
+  store.transfers = store.transfers.filter(t => t.from_member_id !== t.to_member_id);
+  store.members = store.members.filter(m => m.id !== source.id);
+  store.peselHistory = store.peselHistory.filter(h => h.member_id !== source.id);
+  store.detailsHistory = store.detailsHistory.filter(h => h.member_id !== source.id);
+
+  const audit: AuditEntry = {
+    id: store.nextAuditId++,
+    organization_id: target.organization_id,
+    action: 'member_merge',
+    details: {
+      source_id: source.id,
+      target_id: target.id,
+      moved: plan.moved,
+      clashes: plan.clashes.map(c => ({ period: c.period, resolution: c.resolution, source: c.source, target: c.target })),
+      generation_ids: [],
+    },
+    created_at: now(),
+  };
+  store.auditLog.push(audit);
+  cutOffJournal(target.organization_id);
+  return { moved: plan.moved.length, clashes: plan.clashes.length, generations_updated: 0, audit };
+}
+
+export function archive_member(args: { id: number }): void {
+  const member = store.members.find(m => m.id === args.id);
+  if (!member) throw new Error(`Member ${args.id} not found`);
+
+  journaled(member.organization_id, 'member.archive', () => {
+    store.members.find(m => m.id === args.id)!.archived_at ??= now();
+  });
+}
+
+export function restore_member(args: { id: number }): Member {
+  const member = store.members.find(m => m.id === args.id);
+  if (!member) throw new Error(`Member ${args.id} not found`);
 
-  store.members.splice(idx, 1);
+  journaled(member.organization_id, 'member.restore', () => {
+    store.members.find(m => m.id === args.id)!.archived_at = null;
+  });
+  return get_member(args);
 }
 
 export function validate_pesel(args: { peselStr: string }): PeselValidationResult {
@@ -1,8 +1,25 @@
 import { store, now } from '../mock-store';
-import type { CreateOrganization, Organization } from '../../lib/types';
+import type { CreateOrganization, EmployerProfile, Organization } from '../../lib/types';
+
+function profileOf(data: CreateOrganization): EmployerProfile {
+  return {
+    street: data.street,
+    building_number: data.building_number,
+    apartment_number: data.apartment_number,
+    postal_code: data.postal_code,
+    city: data.city,
+    krs: data.krs,
+    bank_account: data.bank_account,
+    management_agreement_number: data.management_agreement_number,
+    management_agreement_date: data.management_agreement_date,
+    operation_agreement_number: data.operation_agreement_number,
+    operation_agreement_date: data.operation_agreement_date,
+    collection_account: data.collection_account,
+  };
+}
 
 export function list_organizations(): Organization[] {
-  return [...store.organizations];
+  return store.organizations.filter(o => !o.archived_at).map(o => ({ ...o }));
 }
 
 export function get_organization(args: { id: number }): Organization {
@@ -17,9 +34,12 @@ export function create_organization(args: { data: CreateOrganization }): Organiz
     name: args.data.name,
     nip: args.data.nip,
     regon: args.data.regon,
-    contact_person: args.data.contact_person,
+    id_type: args.data.id_type,
+    pesel: args.data.id_type === 'PESEL' ? args.data.pesel || null : null,
+    ...profileOf(args.data),
     created_at: now(),
     updated_at: now(),
+    archived_at: null,
   };
   store.organizations.push(org);
   return { ...org };
@@ -31,31 +51,24 @@ export function update_organization(args: { id: number; data: CreateOrganization
   org.name = args.data.name;
   org.nip = args.data.nip;
   org.regon = args.data.regon;
-  org.contact_person = args.data.contact_person;
+  org.id_type = args.data.id_type;
+  org.pesel = args.data.id_type === 'PESEL' ? args.data.pesel || null : null;
+  Object.assign(org, profileOf(args.data));
   org.updated_at = now();
   return { ...org };
 }
 
-export function delete_organization(args: { id: number }): void {
-  const idx = store.organizations.findIndex(o => o.id === args.id);
-  if (idx === -1) throw new Error(`Organization ${args.id} not found`);
-
-  // Cascade: find members of this org
-  const memberIds = store.members
-    .filter(m => m.organization_id === args.id)
-    .map(m => m.id);
-
-  // Remove contributions for those members
-  store.contributions = store.contributions.filter(
-    c => !memberIds.includes(c.member_id)
-  );
-
-  // Remove members
-  store.members = store.members.filter(m => m.organization_id !== args.id);
-
-  // Remove generations
-  store.generations = store.generations.filter(g => g.organization_id !== args.id);
+export function archive_organization(args: { id: number }): void {
+  const org = store.organizations.find(o => o.id === args.id);
+  if (!org) throw new Error(`Organization ${args.id} not found`);
+  org.archived_at ??= now();
+  org.updated_at = now();
+}
 
-  // Remove the org
-  store.organizations.splice(idx, 1);
+export function restore_organization(args: { id: number }): Organization {
+  const org = store.organizations.find(o => o.id === args.id);
+  if (!org) throw new Error(`Organization ${args.id} not found`);
+  org.archived_at = null;
+  org.updated_at = now();
+  return { ...org };
 }
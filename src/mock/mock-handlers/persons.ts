@@ -0,0 +1,127 @@
+import { store } from '../mock-store';
+import type {
+  Member, Person, PersonFilter, PersonKey, PersonReport, PersonContribution, IdentityConflict,
+} from '../../lib/types';
+import type { AppErrorBody } from '../../lib/errors';
+import { withMember } from './contributions';
+import { sumMoney } from '../../lib/utils';
+
+const normalize = (value: string) => value.trim().split(/\s+/).filter(Boolean).join(' ').toUpperCase();
+const compact = (value: string) => value.replace(/\s+/g, '').toUpperCase();
+
+function documentOf(m: Member): string | null {
+  const number = compact(m.doc_number);
+  return m.doc_type.trim() && number ? `${m.doc_type.trim()} ${number}` : null;
+}
+
+const FIELDS: [string, (m: Member) => string][] = [
+  ['pesel', m => m.pesel],
+  ['last_name', m => m.last_name],
+  ['first_name', m => m.first_name],
+  ['second_name', m => m.second_name],
+  ['gender', m => m.gender],
+  ['date_of_birth', m => m.date_of_birth],
+  ['citizenship', m => m.citizenship],
+  ['document', m => documentOf(m) ?? ''],
+];
+
+function conflicts(members: Member[]): IdentityConflict[] {
+  return FIELDS.flatMap(([field, valueOf]) => {
+    const values = new Map<string, { value: string; member_ids: number[] }>();
+    for (const m of members) {
+      const value = valueOf(m);
+      const key = normalize(value);
+      if (!key) continue;
+      const entry = values.get(key) ?? { value: value.trim(), member_ids: [] };
+      entry.member_ids.push(m.id);
+      values.set(key, entry);
+    }
+    return values.size > 1 ? [{ field, values: [...values.values()] }] : [];
+  });
+}
+
+function people(): Person[] {
+  const archivedOrgs = new Set(store.organizations.filter(o => o.archived_at).map(o => o.id));
+  const members = store.members.filter(m => !m.anonymized_at && !m.archived_at && !archivedOrgs.has(m.organization_id));
+  const groups: Member[][] = [];
+  for (const m of members) {
+    const doc = documentOf(m);
+    const linked = groups.filter(g => g.some(o => o.pesel === m.pesel || (doc !== null && documentOf(o) === doc)));
+    const merged = [...linked.flat(), m];
+    for (const g of linked) groups.splice(groups.indexOf(g), 1);
+    groups.push(merged);
+  }
+
+  return groups
+    .map(group => {
+      const first = group[0];
+      const doc = group.map(documentOf).find(d => d !== null);
+      const key: PersonKey = group.every(m => m.pesel === first.pesel) || !doc
+        ? { pesel: first.pesel }
+        : { document: { doc_type: doc.split(' ')[0], doc_number: doc.split(' ')[1] } };
+      return {
+        key,
+        memberships: group.map(member => ({
+          organization_id: member.organization_id,
+          organization_name: store.organizations.find(o => o.id === member.organization_id)?.name ?? '',
+          member: { ...member },
+        })),
+        conflicts: conflicts(group),
+      };
+    })
+    .sort((a, b) =>
+      a.memberships[0].member.last_name.localeCompare(b.memberships[0].member.last_name) ||
+      a.memberships[0].member.first_name.localeCompare(b.memberships[0].member.first_name)
+    );
+}
+
+function matches(m: Member, key: PersonKey): boolean {
+  if ('pesel' in key) return m.pesel === key.pesel.trim();
+  return documentOf(m) === `${key.document.doc_type.trim()} ${compact(key.document.doc_number)}`;
+}
+
+export function list_persons(args: { filter: PersonFilter }): Person[] {
+  return people().filter(
+    p =>
+      (!args.filter.multiple_organizations || new Set(p.memberships.map(m => m.organization_id)).size > 1) &&
+      (!args.filter.conflicts_only || p.conflicts.length > 0)
+  );
+}
+
+export function get_person(args: { key: PersonKey }): Person {
+  const person = people().find(p => p.memberships.some(m => matches(m.member, args.key)));
+  if (!person) {
+    throw {
+      code: 'not_found',
+      field: null,
+      message_key: 'person.not_found',
+      params: {},
+      message: 'Nie znaleziono osoby',
+    } satisfies AppErrorBody;
+  }
+  return person;
+}
+
+export function get_person_report(args: { key: PersonKey }): PersonReport {
+  const person = get_person(args);
+  const contributions: PersonContribution[] = person.memberships
+    .flatMap(m =>
+      store.contributions
+        .filter(c => c.member_id === m.member.id)
+        .map(c => ({ ...withMember(c), organization_id: m.organization_id, organization_name: m.organization_name }))
+    )
+    .sort(
+      (a, b) =>
+        a.period_year - b.period_year ||
+        a.period_month - b.period_month ||
+        a.organization_name.localeCompare(b.organization_name)
+    );
+  return {
+    person,
+    contributions,
+    total_employee_basic: sumMoney(contributions.map(c => c.employee_basic)),
+    total_employee_additional: sumMoney(contributions.map(c => c.employee_additional)),
+    total_employer_basic: sumMoney(contributions.map(c => c.employer_basic)),
+    total_employer_additional: sumMoney(contributions.map(c => c.employer_additional)),
+  };
+}
@@ -0,0 +1,82 @@
+import { store, now } from '../mock-store';
+import type { AuditEntry, Member, RetentionPreview, RetentionResult } from '../../lib/types';
+import type { AppErrorBody } from '../../lib/errors';
+import { cutOffJournal } from './journal';
+
+function dueMembers(organizationId: number): { years: number; cutoff: string; members: Member[] } {
+  const years = store.organizationSettings.get(organizationId)?.retention_years;
+  if (!years) {
+    throw {
+      code: 'validation',
+      field: null,
+      message_key: 'retention.no_policy',
+      params: {},
+      message: 'Błąd walidacji: Organizacja nie ma ustawionego okresu przechowywania danych',
+    } satisfies AppErrorBody;
+  }
+  const date = new Date();
+  date.setFullYear(date.getFullYear() - years);
+  const cutoff = date.toISOString().slice(0, 10);
+
+  const members = store.members.filter(
+    m =>
+      m.organization_id === organizationId &&
+      m.status !== 'active' &&
+      !m.anonymized_at &&
+      m.terminated_at !== null &&
+      m.terminated_at <= cutoff
+  );
+  return { years, cutoff, members };
+}
+
+// Snapshots are not kept in mock, so no generations are reported as affected
+export function preview_retention(args: { organizationId: number }): RetentionPreview {
+  const { years, cutoff, members } = dueMembers(args.organizationId);
+  return {
+    organization_id: args.organizationId,
+    retention_years: years,
+    cutoff,
+    members: members.map(m => ({
+      member_id: m.id,
+      first_name: m.first_name,
+      last_name: m.last_name,
+      pesel: m.pesel,
+      status: m.status,
+      terminated_at: m.terminated_at ?? '',
+    })),
+    generation_ids: [],
+  };
+}
+
+export function apply_retention(args: { organizationId: number }): RetentionResult {
+  const { years, cutoff, members } = dueMembers(args.organizationId);
+  for (const m of members) {
+    m.pesel = `ANON${m.id}`;
+    m.first_name = '';
+    m.last_name = 'Zanonimizowany';
+    m.second_name = '';
+    m.date_of_birth = '';
+    m.doc_type = '';
+    m.doc_number = '';
+    m.anonymized_at = now();
+    store.peselHistory = store.peselHistory.filter(h => h.member_id !== m.id);
+    store.detailsHistory = store.detailsHistory.filter(h => h.member_id !== m.id);
+  }
+
+  const audit: AuditEntry = {
+    id: store.nextAuditId++,
+    organization_id: args.organizationId,
+    action: 'retention_anonymize',
+    details: { retention_years: years, cutoff, member_ids: members.map(m => m.id), generation_ids: [] },
+    created_at: now(),
+  };
+  store.auditLog.push(audit);
+  cutOffJournal(args.organizationId);
+  return { anonymized: members.length, generations_updated: 0, audit };
+}
+
+export function list_audit_log(args: { organizationId: number }): AuditEntry[] {
+  return store.auditLog
+    .filter(e => e.organization_id === args.organizationId)
+    .sort((a, b) => b.id - a.id);
+}
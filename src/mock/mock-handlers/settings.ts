@@ -0,0 +1,35 @@
+import { store } from '../mock-store';
+import type { AppSettings, OrganizationSettings, UpdateOrganizationSettings } from '../../lib/types';
+
+export function get_app_settings(): AppSettings {
+  return { ...store.appSettings };
+}
+
+export function update_app_settings(args: { data: AppSettings }): AppSettings {
+  store.appSettings = { ...args.data };
+  return { ...store.appSettings };
+}
+
+export function get_organization_settings(args: { organizationId: number }): OrganizationSettings {
+  const settings = store.organizationSettings.get(args.organizationId);
+  return settings
+    ? { ...settings }
+    : { organization_id: args.organizationId, output_dir: '', file_name_template: '', contact_template: '', has_zip_password: false, retention_years: null };
+}
+
+export function update_organization_settings(args: {
+  organizationId: number;
+  data: UpdateOrganizationSettings;
+}): OrganizationSettings {
+  const previous = get_organization_settings(args);
+  const settings: OrganizationSettings = {
+    organization_id: args.organizationId,
+    output_dir: args.data.output_dir,
+    file_name_template: args.data.file_name_template,
+    contact_template: args.data.contact_template ?? previous.contact_template,
+    has_zip_password: args.data.zip_password == null ? previous.has_zip_password : args.data.zip_password !== '',
+    retention_years: args.data.retention_years ?? null,
+  };
+  store.organizationSettings.set(args.organizationId, settings);
+  return { ...settings };
+}
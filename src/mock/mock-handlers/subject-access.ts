@@ -0,0 +1,59 @@
+import { store, now } from '../mock-store';
+import type { SubjectQuery, SubjectReport, ExportFormat } from '../../lib/types';
+import { withMember } from './contributions';
+import { list_member_transfers, list_pesel_history, list_details_history } from './members';
+
+export function get_subject_data(args: { query: SubjectQuery }): SubjectReport {
+  const pesel = 'member' in args.query
+    ? store.members.find(m => m.id === (args.query as { member: number }).member)?.pesel
+    : args.query.pesel.trim();
+  const members = store.members.filter(
+    m => m.pesel === pesel || store.peselHistory.some(h => h.member_id === m.id && h.pesel === pesel)
+  );
+  if (!pesel || members.length === 0) {
+    throw new Error('Nie znaleziono: Brak danych dla podanego numeru PESEL');
+  }
+
+  return {
+    pesel,
+    created_at: now(),
+    records: members.map(member => {
+      const contributions = store.contributions
+        .filter(c => c.member_id === member.id)
+        .sort((a, b) => a.period_year - b.period_year || a.period_month - b.period_month)
+        .map(withMember);
+      // Snapshots are not kept in mock; a generation counts when it covers one of the member's periods
+      const generations = store.generations
+        .filter(g => g.organization_id === member.organization_id)
+        .flatMap(g => {
+          const row = contributions.find(c => c.period_year === g.period_year && c.period_month === g.period_month);
+          return row
+            ? [{
+                generation_id: g.id,
+                period_year: g.period_year,
+                period_month: g.period_month,
+                generated_at: g.generated_at,
+                file_path: g.file_path,
+                entry: { ...row },
+              }]
+            : [];
+        });
+      const { id, name, nip, regon } = store.organizations.find(o => o.id === member.organization_id)!;
+      return {
+        organization: { id, name, nip, regon },
+        member: { ...member },
+        contributions,
+        generations,
+        transfers: list_member_transfers({ memberId: member.id }),
+        pesel_history: list_pesel_history({ id: member.id }),
+        details_history: list_details_history({ id: member.id }),
+      };
+    }),
+  };
+}
+
+// No file is written in mock — browser can't write files
+export function export_subject_data(args: { query: SubjectQuery; format: ExportFormat; path: string }): string {
+  get_subject_data(args);
+  return args.path;
+}
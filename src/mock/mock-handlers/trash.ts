@@ -0,0 +1,119 @@
+import { store, now } from '../mock-store';
+import type { AuditEntry, Member, Trash } from '../../lib/types';
+import type { AppErrorBody } from '../../lib/errors';
+import { cutOffJournal } from './journal';
+
+function validationError(key: string, message: string): AppErrorBody {
+  return { code: 'validation', field: null, message_key: key, params: {}, message };
+}
+
+function cutoff(organizationId: number): string | null {
+  const years = store.organizationSettings.get(organizationId)?.retention_years;
+  if (!years) return null;
+  const date = new Date();
+  date.setFullYear(date.getFullYear() - years);
+  return date.toISOString().slice(0, 10);
+}
+
+// Snapshots are not kept in mock; a generation counts when the member had a
+// contribution for its period
+function memberGenerations(member: Member): number {
+  return store.generations.filter(
+    g =>
+      g.organization_id === member.organization_id &&
+      store.contributions.some(
+        c => c.member_id === member.id && c.period_year === g.period_year && c.period_month === g.period_month,
+      ),
+  ).length;
+}
+
+function memberPurgeable(member: Member, generations: number): boolean {
+  if (generations === 0) return true;
+  const day = cutoff(member.organization_id);
+  return day !== null && member.terminated_at !== null && member.terminated_at <= day;
+}
+
+function organizationPurgeable(organizationId: number): boolean {
+  const generations = store.generations.filter(g => g.organization_id === organizationId);
+  if (generations.length === 0) return true;
+  const day = cutoff(organizationId);
+  return day !== null && generations.every(g => g.generated_at.slice(0, 10) <= day);
+}
+
+export function list_trash(): Trash {
+  const organizations = store.organizations
+    .filter(o => o.archived_at)
+    .map(o => ({
+      organization: { ...o },
+      generations: store.generations.filter(g => g.organization_id === o.id).length,
+      can_purge: organizationPurgeable(o.id),
+    }));
+  const members = store.members
+    .filter(m => m.archived_at)
+    .flatMap(m => {
+      const org = store.organizations.find(o => o.id === m.organization_id);
+      if (!org || org.archived_at) return [];
+      const generations = memberGenerations(m);
+      return [{ member: { ...m }, organization_name: org.name, generations, can_purge: memberPurgeable(m, generations) }];
+    });
+  return { organizations, members };
+}
+
+export function purge_member(args: { id: number }): AuditEntry {
+  const member = store.members.find(m => m.id === args.id);
+  if (!member) throw new Error(`Member ${args.id} not found`);
+  if (!member.archived_at) {
+    throw validationError('trash.not_archived', 'Trwale usunąć można tylko dane przeniesione do kosza');
+  }
+  const generations = memberGenerations(member);
+  if (!memberPurgeable(member, generations)) {
+    throw validationError('trash.purge_blocked', 'Dane występują w zapisanych generacjach; można je usunąć dopiero po upływie okresu przechowywania');
+  }
+
+  store.contributions = store.contributions.filter(c => c.member_id !== args.id);
+  store.members = store.members.filter(m => m.id !== args.id);
+  store.transfers = store.transfers.filter(t => t.from_member_id !== args.id && t.to_member_id !== args.id);
+  store.peselHistory = store.peselHistory.filter(h => h.member_id !== args.id);
+  store.detailsHistory = store.detailsHistory.filter(h => h.member_id !== args.id);
+
+  const audit: AuditEntry = {
+    id: store.nextAuditId++,
+    organization_id: member.organization_id,
+    action: 'trash_purge_member',
+    details: { member_id: args.id, generations },
+    created_at: now(),
+  };
+  store.auditLog.push(audit);
+  cutOffJournal(member.organization_id);
+  return audit;
+}
+
+export function purge_organization(args: { id: number }): AuditEntry {
+  const org = store.organizations.find(o => o.id === args.id);
+  if (!org) throw new Error(`Organization ${args.id} not found`);
+  if (!org.archived_at) {
+    throw validationError('trash.not_archived', 'Trwale usunąć można tylko dane przeniesione do kosza');
+  }
+  if (!organizationPurgeable(args.id)) {
+    throw validationError('trash.purge_blocked', 'Dane występują w zapisanych generacjach; można je usunąć dopiero po upływie okresu przechowywania');
+  }
+
+  const memberIds = new Set(store.members.filter(m => m.organization_id === args.id).map(m => m.id));
+  const generations = store.generations.filter(g => g.organization_id === args.id).length;
+  store.contributions = store.contributions.filter(c => !memberIds.has(c.member_id));
+  store.members = store.members.filter(m => !memberIds.has(m.id));
+  store.generations = store.generations.filter(g => g.organization_id !== args.id);
+  store.journal = store.journal.filter(e => e.organization_id !== args.id);
+  store.contacts = store.contacts.filter(c => c.organization_id !== args.id);
+  store.organizations = store.organizations.filter(o => o.id !== args.id);
+
+  const audit: AuditEntry = {
+    id: store.nextAuditId++,
+    organization_id: null,
+    action: 'trash_purge_organization',
+    details: { organization_id: args.id, generations },
+    created_at: now(),
+  };
+  store.auditLog.push(audit);
+  return audit;
+}
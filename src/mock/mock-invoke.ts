@@ -1,7 +1,15 @@
 import * as orgHandlers from './mock-handlers/organizations';
+import * as contactHandlers from './mock-handlers/contacts';
 import * as memberHandlers from './mock-handlers/members';
 import * as contribHandlers from './mock-handlers/contributions';
 import * as genHandlers from './mock-handlers/generations';
+import * as settingsHandlers from './mock-handlers/settings';
+import * as retentionHandlers from './mock-handlers/retention';
+import * as personHandlers from './mock-handlers/persons';
+import * as subjectAccessHandlers from './mock-handlers/subject-access';
+import * as databaseHandlers from './mock-handlers/database';
+import * as journalHandlers from './mock-handlers/journal';
+import * as trashHandlers from './mock-handlers/trash';
 
 type Handler = (args: any) => any;
 
@@ -11,28 +19,89 @@ const handlers: Record<string, Handler> = {
   get_organization: orgHandlers.get_organization,
   create_organization: orgHandlers.create_organization,
   update_organization: orgHandlers.update_organization,
-  delete_organization: orgHandlers.delete_organization,
+  archive_organization: orgHandlers.archive_organization,
+  restore_organization: orgHandlers.restore_organization,
+
+  // Contact persons
+  list_contacts: contactHandlers.list_contacts,
+  create_contact: contactHandlers.create_contact,
+  update_contact: contactHandlers.update_contact,
+  set_default_contact: contactHandlers.set_default_contact,
+  delete_contact: contactHandlers.delete_contact,
 
   // Members
   list_members: memberHandlers.list_members,
+  list_details_history: memberHandlers.list_details_history,
+  change_member_pesel: memberHandlers.change_member_pesel,
+  list_pesel_history: memberHandlers.list_pesel_history,
+  transfer_member: memberHandlers.transfer_member,
+  list_member_transfers: memberHandlers.list_member_transfers,
+  preview_merge: memberHandlers.preview_merge,
+  merge_members: memberHandlers.merge_members,
+  query_members: memberHandlers.query_members,
   get_member: memberHandlers.get_member,
   create_member: memberHandlers.create_member,
   update_member: memberHandlers.update_member,
-  delete_member: memberHandlers.delete_member,
+  archive_member: memberHandlers.archive_member,
+  restore_member: memberHandlers.restore_member,
   validate_pesel: memberHandlers.validate_pesel,
 
   // Contributions
   list_contributions: contribHandlers.list_contributions,
   upsert_contribution: contribHandlers.upsert_contribution,
+  upsert_contributions: contribHandlers.upsert_contributions,
+  preview_prefill: contribHandlers.preview_prefill,
   prefill_contributions: contribHandlers.prefill_contributions,
   get_available_periods: contribHandlers.get_available_periods,
 
   // Generations
   generate_ppk: genHandlers.generate_ppk,
+  generate_batch: genHandlers.generate_batch,
   list_generations: genHandlers.list_generations,
   get_generation: genHandlers.get_generation,
   export_generation: genHandlers.export_generation,
-  save_zip_file: genHandlers.save_zip_file,
+  verify_archive: genHandlers.verify_archive,
+
+  // Undo/redo
+  list_journal: journalHandlers.list_journal,
+  undo: journalHandlers.undo,
+  redo: journalHandlers.redo,
+
+  // Settings
+  get_app_settings: settingsHandlers.get_app_settings,
+  update_app_settings: settingsHandlers.update_app_settings,
+  get_organization_settings: settingsHandlers.get_organization_settings,
+  update_organization_settings: settingsHandlers.update_organization_settings,
+
+  // Retention
+  preview_retention: retentionHandlers.preview_retention,
+  apply_retention: retentionHandlers.apply_retention,
+
+  // Audit
+  list_audit_log: retentionHandlers.list_audit_log,
+
+  // Trash
+  list_trash: trashHandlers.list_trash,
+  purge_member: trashHandlers.purge_member,
+  purge_organization: trashHandlers.purge_organization,
+
+  // Persons
+  list_persons: personHandlers.list_persons,
+  get_person: personHandlers.get_person,
+  get_person_report: personHandlers.get_person_report,
+
+  // Subject access
+  get_subject_data: subjectAccessHandlers.get_subject_data,
+  export_subject_data: subjectAccessHandlers.export_subject_data,
+
+  // Database
+  get_database_status: databaseHandlers.get_database_status,
+  unlock_database: databaseHandlers.unlock_database,
+  encrypt_database: databaseHandlers.encrypt_database,
+  change_database_password: databaseHandlers.change_database_password,
+  create_backup: databaseHandlers.create_backup,
+  list_backups: databaseHandlers.list_backups,
+  restore_backup: databaseHandlers.restore_backup,
 };
 
 export async function mockInvoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
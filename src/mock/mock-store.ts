@@ -21,25 +21,65 @@ export interface StoredGeneration {
   period_month: number;
   generated_at: string;
   file_path: string;
+  checksum: string;
+  encrypted: boolean;
   total_employee_basic: string;
   total_employer_basic: string;
   member_count: number;
 }
 
-import type { Organization, Member } from '../lib/types';
+import type {
+  Organization, ContactPerson, Member, AppSettings, OrganizationSettings, AuditEntry, JournalEntry, PeselChange, DetailsChange,
+} from '../lib/types';
+
+/** Members of one organization with their contributions, for mock undo/redo. */
+export interface OrgSnapshot {
+  members: Member[];
+  contributions: RawContribution[];
+  detailsHistory: DetailsChange[];
+}
+
+export interface StoredJournalEntry extends JournalEntry {
+  before: OrgSnapshot;
+  after: OrgSnapshot;
+}
 import type { GenerateResult } from '../lib/types';
 
+export interface StoredTransfer {
+  id: number;
+  from_member_id: number;
+  to_member_id: number;
+  transferred_on: string;
+  created_at: string;
+}
+
 export const store = {
   organizations: [] as Organization[],
+  contacts: [] as ContactPerson[],
   members: [] as Member[],
   contributions: [] as RawContribution[],
   generations: [] as StoredGeneration[],
   generationResults: new Map<number, GenerateResult>(),
+  appSettings: { archive_dir: '/mock/archive', output_dir: '', file_name_template: '', contact_template: '', language: 'pl' } as AppSettings,
+  organizationSettings: new Map<number, OrganizationSettings>(),
+  auditLog: [] as AuditEntry[],
+  journal: [] as StoredJournalEntry[],
+  transfers: [] as StoredTransfer[],
+  peselHistory: [] as PeselChange[],
+  detailsHistory: [] as DetailsChange[],
+  databasePassword: null as string | null,
+  databaseLocked: false,
 
   nextOrgId: 1,
+  nextContactId: 1,
   nextMemberId: 1,
   nextContributionId: 1,
   nextGenerationId: 1,
+  nextAuditId: 1,
+  nextJournalId: 1,
+  nextTransferId: 1,
+  nextPeselChangeId: 1,
+  nextDetailsChangeId: 1,
 };
 
 export function now(): string {